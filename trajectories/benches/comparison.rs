@@ -25,6 +25,7 @@ fn compare(c: &mut Criterion) {
                 &waypoints,
                 PathOptions {
                     max_deviation: 0.001,
+                    ..PathOptions::default()
                 },
             );
 
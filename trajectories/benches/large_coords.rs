@@ -24,6 +24,7 @@ fn test_2(c: &mut Criterion) {
                     &waypoints,
                     PathOptions {
                         max_deviation: 100.0,
+                        ..PathOptions::default()
                     },
                 );
 
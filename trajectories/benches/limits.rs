@@ -160,6 +160,7 @@ fn bench_max_velocity_at_accel_limited(c: &mut Criterion) {
                     &waypoints(),
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -171,8 +172,12 @@ fn bench_max_velocity_at_accel_limited(c: &mut Criterion) {
                 let mut i = 0.0;
 
                 while i < len {
-                    let _pos =
-                        max_velocity_at(&p, i, LimitType::Acceleration(options.acceleration_limit));
+                    let _pos = max_velocity_at(
+                        &p,
+                        i,
+                        LimitType::Acceleration(options.acceleration_limit),
+                        &options,
+                    );
 
                     i += step;
                 }
@@ -196,6 +201,7 @@ fn bench_max_velocity_at_vel_limited(c: &mut Criterion) {
                     &waypoints(),
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -207,7 +213,12 @@ fn bench_max_velocity_at_vel_limited(c: &mut Criterion) {
                 let mut i = 0.0;
 
                 while i < len {
-                    let _pos = max_velocity_at(&p, i, LimitType::Velocity(options.velocity_limit));
+                    let _pos = max_velocity_at(
+                        &p,
+                        i,
+                        LimitType::Velocity(options.velocity_limit),
+                        &options,
+                    );
 
                     i += step;
                 }
@@ -231,6 +242,7 @@ fn bench_max_velocity_deriv_at_vel_limited(c: &mut Criterion) {
                     &waypoints(),
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -271,6 +283,7 @@ fn bench_max_velocity_deriv_at_accel_limited(c: &mut Criterion) {
                     &waypoints(),
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -311,6 +324,7 @@ fn bench_max_acceleration_at(c: &mut Criterion) {
                     &waypoints(),
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -7,9 +7,11 @@ use trajectories::{test_helpers::TestCoord3, Path, PathOptions};
 
 const DEVIATION: f64 = 0.01;
 const NUM_POINTS: usize = 100;
+/// Number of times to repeat the waypoints to create a path with many segments
+const NUM_REPEATS: usize = 100;
 
-fn long_path_bench(c: &mut Criterion) {
-    let waypoints: Vec<TestCoord3> = vec![
+fn waypoints() -> Vec<TestCoord3> {
+    vec![
         TestCoord3::new(00.897, 00.111, 0.1234),
         TestCoord3::new(4.0, 0.0, 4.0),
         TestCoord3::new(10.897, 00.111, 1.1234),
@@ -136,7 +138,11 @@ fn long_path_bench(c: &mut Criterion) {
         TestCoord3::new(40.897, 60.111, 0.1234),
         TestCoord3::new(3.0, 0.0, 5.0),
         TestCoord3::new(0.0, 0.0, 0.0),
-    ];
+    ]
+}
+
+fn long_path_bench(c: &mut Criterion) {
+    let waypoints = waypoints();
 
     c.bench_function("benchmark long path", move |b| {
         b.iter_with_setup(
@@ -145,6 +151,7 @@ fn long_path_bench(c: &mut Criterion) {
                     &waypoints,
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 );
                 let len = p.len();
@@ -165,5 +172,87 @@ fn long_path_bench(c: &mut Criterion) {
     });
 }
 
-criterion_group!(long_path, long_path_bench);
+fn long_path_lookup_bench(c: &mut Criterion) {
+    let waypoints = waypoints()
+        .iter()
+        .cycle()
+        .take(waypoints().len() * NUM_REPEATS)
+        .cloned()
+        .collect::<Vec<TestCoord3>>();
+
+    let p = Path::from_waypoints(
+        &waypoints,
+        PathOptions {
+            max_deviation: DEVIATION,
+            ..PathOptions::default()
+        },
+    );
+    let len = p.len();
+    let step = len / NUM_POINTS as f64;
+
+    c.bench_function(
+        &format!("benchmark long path lookup ({} segments)", p.segments.len()),
+        move |b| {
+            b.iter(|| {
+                let mut i = 0.0;
+
+                while i < len {
+                    let _pos = p.position(i);
+                    let _tangent_curvature = p.tangent_and_curvature(i);
+                    let _next = p.next_switching_point(i);
+
+                    i += step;
+                }
+            })
+        },
+    );
+}
+
+fn long_path_project_bench(c: &mut Criterion) {
+    let waypoints = waypoints()
+        .iter()
+        .cycle()
+        .take(waypoints().len() * NUM_REPEATS)
+        .cloned()
+        .collect::<Vec<TestCoord3>>();
+
+    let p = Path::from_waypoints(
+        &waypoints,
+        PathOptions {
+            max_deviation: DEVIATION,
+            ..PathOptions::default()
+        },
+    );
+    let len = p.len();
+    let step = len / NUM_POINTS as f64;
+    let offset = TestCoord3::new(0.1, -0.2, 0.3);
+
+    // Build the index before timing
+    let _ = p.project(&TestCoord3::zeros());
+
+    c.bench_function(
+        &format!(
+            "benchmark long path project ({} segments)",
+            p.segments.len()
+        ),
+        move |b| {
+            b.iter(|| {
+                let mut i = 0.0;
+
+                while i < len {
+                    let _projected = p.project(&(p.position(i) + offset));
+
+                    i += step;
+                }
+            })
+        },
+    );
+}
+
+criterion_group!(
+    long_path,
+    long_path_bench,
+    long_path_lookup_bench,
+    long_path_project_bench
+);
 criterion_main!(long_path);
@@ -30,6 +30,7 @@ fn create_path(c: &mut Criterion) {
                     &waypoints,
                     PathOptions {
                         max_deviation: DEVIATION,
+                        ..PathOptions::default()
                     },
                 )
             })
@@ -61,6 +62,7 @@ fn create_path_and_iterate(c: &mut Criterion) {
                         &waypoints,
                         PathOptions {
                             max_deviation: DEVIATION,
+                            ..PathOptions::default()
                         },
                     )
                 },
@@ -103,6 +105,7 @@ fn get_position(c: &mut Criterion) {
                         &waypoints,
                         PathOptions {
                             max_deviation: DEVIATION,
+                            ..PathOptions::default()
                         },
                     )
                 },
@@ -129,6 +132,7 @@ fn get_tangent(c: &mut Criterion) {
             &waypoints,
             PathOptions {
                 max_deviation: DEVIATION,
+                ..PathOptions::default()
             },
         );
 
@@ -154,6 +158,7 @@ fn get_curvature(c: &mut Criterion) {
             &waypoints,
             PathOptions {
                 max_deviation: DEVIATION,
+                ..PathOptions::default()
             },
         );
 
@@ -179,6 +184,7 @@ fn get_segment_at_position(c: &mut Criterion) {
             &waypoints,
             PathOptions {
                 max_deviation: DEVIATION,
+                ..PathOptions::default()
             },
         );
 
@@ -40,6 +40,7 @@ fn get_next_switching_point(c: &mut Criterion) {
             &waypoints,
             PathOptions {
                 max_deviation: DEVIATION,
+                ..PathOptions::default()
             },
         );
 
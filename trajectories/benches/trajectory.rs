@@ -23,6 +23,7 @@ fn get_positions(c: &mut Criterion) {
                 &waypoints,
                 PathOptions {
                     max_deviation: 0.001,
+                    ..PathOptions::default()
                 },
             );
 
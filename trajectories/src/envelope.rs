@@ -0,0 +1,117 @@
+//! Versioned envelope around serialized paths and trajectories
+//!
+//! Serialized data is wrapped as `{ version, data }`. The version is checked before the data is
+//! deserialized, so data written by an incompatible version of this crate produces a clear error
+//! instead of a confusing decoding failure.
+
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Version of the serialized format
+///
+/// This must be incremented whenever the serialized layout of a path or trajectory changes.
+pub(crate) const FORMAT_VERSION: u32 = 3;
+
+const FIELDS: &[&str] = &["version", "data"];
+
+#[derive(Serialize)]
+#[serde(rename = "Envelope")]
+struct EnvelopeRef<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+/// Serialize `data` wrapped in a versioned envelope
+pub(crate) fn serialize<T, S>(data: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    EnvelopeRef {
+        version: FORMAT_VERSION,
+        data,
+    }
+    .serialize(serializer)
+}
+
+/// Deserialize data wrapped in a versioned envelope, rejecting unsupported versions
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct("Envelope", FIELDS, EnvelopeVisitor(PhantomData))
+}
+
+fn check_version<E>(version: u32) -> Result<(), E>
+where
+    E: de::Error,
+{
+    if version == FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(E::custom(format!(
+            "unsupported format version {}, expected {}",
+            version, FORMAT_VERSION
+        )))
+    }
+}
+
+struct EnvelopeVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for EnvelopeVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a versioned envelope")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        check_version(version)?;
+
+        seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<T, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut has_version = false;
+        let mut data = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => {
+                    let value = map.next_value()?;
+
+                    check_version(value)?;
+
+                    has_version = true;
+                }
+                "data" => data = Some(map.next_value()?),
+                _ => {
+                    let _: IgnoredAny = map.next_value()?;
+                }
+            }
+        }
+
+        if !has_version {
+            return Err(de::Error::missing_field("version"));
+        }
+
+        data.ok_or_else(|| de::Error::missing_field("data"))
+    }
+}
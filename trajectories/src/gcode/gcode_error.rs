@@ -0,0 +1,98 @@
+use crate::PathError;
+use std::error::Error;
+use std::fmt;
+
+/// Errors that can occur when parsing a G-code program
+///
+/// Each variant carries the 1-based line number of the program the error occurred on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GcodeError {
+    /// A word is not a letter followed by a number
+    InvalidWord {
+        /// Line number
+        line: usize,
+        /// The invalid word
+        word: String,
+    },
+
+    /// A G or M code is not supported
+    UnsupportedCode {
+        /// Line number
+        line: usize,
+        /// The unsupported code, e.g. `G28`
+        code: String,
+    },
+
+    /// An axis word refers to an axis that the path does not have
+    AxisOutOfRange {
+        /// Line number
+        line: usize,
+        /// Axis letter
+        axis: char,
+    },
+
+    /// A move was given before any motion mode (`G0`, `G1`, `G2` or `G3`) was set
+    NoMotionMode {
+        /// Line number
+        line: usize,
+    },
+
+    /// An arc has no valid center, e.g. its start and end points are different distances from
+    /// the center or its radius is too small to reach the end point
+    InvalidArc {
+        /// Line number
+        line: usize,
+    },
+
+    /// An arc's plane axes have different axis weights, which would stretch it into an ellipse
+    /// where its length is measured
+    UnevenArcWeights {
+        /// Line number
+        line: usize,
+    },
+
+    /// A sequence of moves does not form a valid path
+    InvalidPath {
+        /// Line number
+        line: usize,
+        /// Reason the path is invalid
+        reason: PathError,
+    },
+}
+
+impl fmt::Display for GcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GcodeError::InvalidWord { line, word } => {
+                write!(f, "Invalid word \"{}\" on line {}", word, line)
+            }
+            GcodeError::UnsupportedCode { line, code } => {
+                write!(f, "Unsupported code {} on line {}", code, line)
+            }
+            GcodeError::AxisOutOfRange { line, axis } => {
+                write!(f, "Axis {} on line {} is out of range", axis, line)
+            }
+            GcodeError::NoMotionMode { line } => {
+                write!(f, "Move on line {} has no motion mode set", line)
+            }
+            GcodeError::InvalidArc { line } => write!(f, "Invalid arc on line {}", line),
+            GcodeError::UnevenArcWeights { line } => write!(
+                f,
+                "Arc on line {} is in a plane with different axis weights",
+                line
+            ),
+            GcodeError::InvalidPath { line, reason } => {
+                write!(f, "Invalid path at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl Error for GcodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GcodeError::InvalidPath { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+}
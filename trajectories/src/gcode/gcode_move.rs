@@ -0,0 +1,58 @@
+use crate::Path;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Type of motion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveKind {
+    /// Rapid positioning move (`G0`)
+    Rapid,
+
+    /// Move at the programmed feed rate (`G1`, `G2` or `G3`)
+    Feed,
+}
+
+/// A continuous move parsed from a G-code program
+///
+/// Consecutive linear moves of the same kind and feed rate are joined into a single blended path.
+/// Each arc is its own move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeMove<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Whether this is a rapid or feed move
+    pub kind: MoveKind,
+
+    /// Feed rate in millimeters per minute
+    ///
+    /// This is `None` for rapid moves, or if no feed rate has been programmed.
+    pub feed_rate: Option<f64>,
+
+    /// Path followed by this move, in millimeters
+    pub path: Path<N>,
+
+    /// 1-based source line number of each segment in `path`
+    ///
+    /// A blend between two linear moves is attributed to the line of the second move.
+    pub segment_lines: Vec<usize>,
+}
+
+impl<N> GcodeMove<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the 1-based source line number of the segment at a position along the path
+    pub fn source_line(&self, position_along_path: f64) -> usize {
+        self.segment_lines[self.path.segment_index_at_position(position_along_path)]
+    }
+}
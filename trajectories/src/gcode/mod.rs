@@ -0,0 +1,808 @@
+//! G-code front end
+//!
+//! Parses a G-code program into a list of moves, each with a `Path` that can be used to create a
+//! `Trajectory`. The following codes are supported:
+//!
+//! * `G0`, `G1` - rapid and linear moves
+//! * `G2`, `G3` - clockwise and counterclockwise arcs, using either `I`/`J`/`K` center offsets or
+//!   an `R` radius. A negative radius selects the longer of the two possible arcs. Arcs that
+//!   also move axes outside the arc plane become helices.
+//! * `G4` - dwell
+//! * `G17`, `G18`, `G19` - XY, ZX and YZ arc planes
+//! * `G20`, `G21` - inch and millimeter units
+//! * `G90`, `G91` - absolute and relative positioning
+//! * `G90.1`, `G91.1` - absolute and relative arc centers
+//! * `F` - feed rate in units per minute
+//!
+//! `G40`, `G49`, `G54`, `G80` and `G94` are accepted and ignored. `M` codes are ignored, but end
+//! the current move as the machine must stop to execute them. `N`, `P`, `S` and `T` words are
+//! ignored. Comments in parentheses or after a semicolon are skipped.
+//!
+//! Axis words `X`, `Y`, `Z`, `A`, `B` and `C` map to axes 0 to 5 of the path. All positions are
+//! converted to millimeters, and the machine is assumed to start at the origin. Axis weights in the
+//! path options apply to arcs as well as linear moves, so both axes of an arc's plane must have
+//! the same weight.
+
+mod gcode_error;
+mod gcode_move;
+
+pub use self::gcode_error::GcodeError;
+pub use self::gcode_move::{GcodeMove, MoveKind};
+use crate::path::{CircularPathSegment, HelixPathSegment, PathSegment, ARC_RADIUS_TOLERANCE};
+use crate::{ArcDirection, Coord, Path, PathError, PathOptions, TRAJECTORY_EPSILON};
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Axis letters in the order of the axes they map to
+const AXES: [char; 6] = ['X', 'Y', 'Z', 'A', 'B', 'C'];
+
+const MM_PER_INCH: f64 = 25.4;
+
+/// Parse a G-code program into a list of moves
+///
+/// Consecutive linear moves with the same kind and feed rate are joined into a single path,
+/// blended with the given options. Each arc becomes a move with a path containing a single
+/// circular segment.
+pub fn parse<N>(program: &str, options: PathOptions<N>) -> Result<Vec<GcodeMove<N>>, GcodeError>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let mut parser = Parser::new(options);
+
+    for (index, line) in program.lines().enumerate() {
+        parser.parse_line(index + 1, line)?;
+    }
+
+    parser.finish()
+}
+
+/// Modal motion mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Motion {
+    Rapid,
+    Linear,
+    Arc(ArcDirection),
+}
+
+/// Consecutive linear moves that will be blended into a single path
+struct Chain<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    kind: MoveKind,
+    feed_rate: Option<f64>,
+    waypoints: Vec<Coord<N>>,
+    /// Source line of the move ending at each waypoint after the first
+    lines: Vec<usize>,
+}
+
+impl<N> Chain<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    /// Whether a move to `target` would double back on the last move, which cannot be blended
+    fn reverses(&self, target: &Coord<N>) -> bool {
+        match self.waypoints.as_slice() {
+            [.., previous, last] => {
+                let previous_normalised = (last - previous).normalize();
+                let next_normalised = (target - last).normalize();
+
+                (previous_normalised + next_normalised).norm() < TRAJECTORY_EPSILON
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Parser<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    options: PathOptions<N>,
+    position: Coord<N>,
+    motion: Option<Motion>,
+    /// Feed rate in millimeters per minute
+    feed_rate: Option<f64>,
+    /// Millimeters per program unit
+    units: f64,
+    absolute: bool,
+    absolute_arc_centers: bool,
+    /// First axis, second axis and normal axis of the arc plane
+    plane: (usize, usize, usize),
+    chain: Option<Chain<N>>,
+    moves: Vec<GcodeMove<N>>,
+}
+
+impl<N> Parser<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn new(options: PathOptions<N>) -> Self {
+        Self {
+            options,
+            position: Coord::zeros(),
+            motion: None,
+            feed_rate: None,
+            units: 1.0,
+            absolute: true,
+            absolute_arc_centers: false,
+            plane: (0, 1, 2),
+            chain: None,
+            moves: Vec::new(),
+        }
+    }
+
+    fn parse_line(&mut self, line: usize, text: &str) -> Result<(), GcodeError> {
+        let mut motion = None;
+        let mut stop = false;
+        let mut feed_rate = None;
+        let mut axes = Vec::new();
+        let mut offsets = [None; 3];
+        let mut radius = None;
+
+        for (letter, value) in words(line, text)? {
+            match letter {
+                'G' => match (value * 10.0).round() as i64 {
+                    0 => motion = Some(Motion::Rapid),
+                    10 => motion = Some(Motion::Linear),
+                    20 => motion = Some(Motion::Arc(ArcDirection::Clockwise)),
+                    30 => motion = Some(Motion::Arc(ArcDirection::Counterclockwise)),
+                    40 => stop = true,
+                    170 => self.plane = (0, 1, 2),
+                    180 => self.plane = (2, 0, 1),
+                    190 => self.plane = (1, 2, 0),
+                    200 => self.units = MM_PER_INCH,
+                    210 => self.units = 1.0,
+                    900 => self.absolute = true,
+                    910 => self.absolute = false,
+                    901 => self.absolute_arc_centers = true,
+                    911 => self.absolute_arc_centers = false,
+                    400 | 490 | 540 | 800 | 940 => (),
+                    _ => {
+                        return Err(GcodeError::UnsupportedCode {
+                            line,
+                            code: format!("G{}", value),
+                        })
+                    }
+                },
+                'M' => stop = true,
+                'F' => feed_rate = Some(value),
+                'I' => offsets[0] = Some(value),
+                'J' => offsets[1] = Some(value),
+                'K' => offsets[2] = Some(value),
+                'R' => radius = Some(value),
+                'N' | 'P' | 'S' | 'T' => (),
+                _ => match AXES.iter().position(|axis| *axis == letter) {
+                    Some(axis) if axis < N::dim() => axes.push((axis, value)),
+                    Some(_) => return Err(GcodeError::AxisOutOfRange { line, axis: letter }),
+                    None => {
+                        return Err(GcodeError::InvalidWord {
+                            line,
+                            word: format!("{}{}", letter, value),
+                        })
+                    }
+                },
+            }
+        }
+
+        // Units apply to every word on the line, wherever the unit code appears
+        if let Some(feed_rate) = feed_rate {
+            self.feed_rate = Some(feed_rate * self.units);
+        }
+
+        if stop {
+            self.flush()?;
+        }
+
+        if motion.is_some() {
+            self.motion = motion;
+        }
+
+        let has_arc_words = offsets.iter().any(Option::is_some) || radius.is_some();
+
+        if axes.is_empty() && !has_arc_words {
+            return Ok(());
+        }
+
+        let mut target = self.position.clone();
+
+        for (axis, value) in axes {
+            let value = value * self.units;
+
+            target[axis] = if self.absolute {
+                value
+            } else {
+                target[axis] + value
+            };
+        }
+
+        match self.motion {
+            Some(Motion::Rapid) => self.linear_move(line, MoveKind::Rapid, target),
+            Some(Motion::Linear) => self.linear_move(line, MoveKind::Feed, target),
+            Some(Motion::Arc(direction)) => self.arc_move(line, target, direction, offsets, radius),
+            None => Err(GcodeError::NoMotionMode { line }),
+        }
+    }
+
+    fn linear_move(
+        &mut self,
+        line: usize,
+        kind: MoveKind,
+        target: Coord<N>,
+    ) -> Result<(), GcodeError> {
+        if (&target - &self.position).norm() < TRAJECTORY_EPSILON {
+            return Ok(());
+        }
+
+        let feed_rate = match kind {
+            MoveKind::Rapid => None,
+            MoveKind::Feed => self.feed_rate,
+        };
+
+        let continues = self.chain.as_ref().map_or(false, |chain| {
+            chain.kind == kind && chain.feed_rate == feed_rate && !chain.reverses(&target)
+        });
+
+        if !continues {
+            self.flush()?;
+        }
+
+        let position = self.position.clone();
+
+        let chain = self.chain.get_or_insert_with(|| Chain {
+            kind,
+            feed_rate,
+            waypoints: vec![position],
+            lines: Vec::new(),
+        });
+
+        chain.waypoints.push(target.clone());
+        chain.lines.push(line);
+
+        self.position = target;
+
+        Ok(())
+    }
+
+    fn arc_move(
+        &mut self,
+        line: usize,
+        target: Coord<N>,
+        direction: ArcDirection,
+        offsets: [Option<f64>; 3],
+        radius: Option<f64>,
+    ) -> Result<(), GcodeError> {
+        let (a, b, _) = self.plane;
+
+        for axis in [a, b].iter() {
+            if *axis >= N::dim() {
+                return Err(GcodeError::AxisOutOfRange {
+                    line,
+                    axis: AXES[*axis],
+                });
+            }
+        }
+
+        let start = self.position.clone();
+
+        let center = match radius {
+            Some(radius) => radius_center(&start, &target, a, b, radius * self.units, direction)
+                .ok_or(GcodeError::InvalidArc { line })?,
+            None => {
+                let mut center = start.clone();
+
+                for axis in [a, b].iter() {
+                    if let Some(offset) = offsets[*axis] {
+                        let offset = offset * self.units;
+
+                        center[*axis] = if self.absolute_arc_centers {
+                            offset
+                        } else {
+                            start[*axis] + offset
+                        };
+                    }
+                }
+
+                center
+            }
+        };
+
+        // Arcs that also move axes outside their plane are helical
+        let helical = (0..N::dim())
+            .any(|axis| axis != a && axis != b && (target[axis] - start[axis]).abs() > 0.0);
+
+        // With axis weights, build the arc in the weighted space its length is measured in. It
+        // only stays circular there if both axes of its plane have the same weight.
+        let weights = self.options.axis_weights;
+
+        if let Some(weights) = &weights {
+            if (weights[a] - weights[b]).abs() > TRAJECTORY_EPSILON {
+                return Err(GcodeError::UnevenArcWeights { line });
+            }
+        }
+
+        let weighted = |point: &Coord<N>| match &weights {
+            Some(weights) => point.component_mul(weights),
+            None => point.clone(),
+        };
+
+        let (start_weighted, target_weighted, center_weighted) =
+            (weighted(&start), weighted(&target), weighted(&center));
+
+        let segment = if helical {
+            HelixPathSegment::from_arc(
+                &start_weighted,
+                &target_weighted,
+                &center_weighted,
+                (a, b),
+                direction,
+            )
+            .map(|helix| match &weights {
+                Some(weights) => PathSegment::Helix(helix.unweighted(weights)),
+                None => PathSegment::Helix(helix),
+            })
+        } else {
+            CircularPathSegment::from_arc(
+                &start_weighted,
+                &target_weighted,
+                &center_weighted,
+                (a, b),
+                direction,
+            )
+            .map(|arc| match &weights {
+                Some(weights) => PathSegment::Circular(arc.unweighted(weights)),
+                None => PathSegment::Circular(arc),
+            })
+        }
+        .map_err(|_| GcodeError::InvalidArc { line })?;
+
+        self.flush()?;
+
+        self.moves.push(GcodeMove {
+            kind: MoveKind::Feed,
+            feed_rate: self.feed_rate,
+            path: Path::from_segments(vec![segment]),
+            segment_lines: vec![line],
+        });
+
+        self.position = target;
+
+        Ok(())
+    }
+
+    /// Turn the current chain of linear moves into a blended path
+    fn flush(&mut self) -> Result<(), GcodeError> {
+        let chain = match self.chain.take() {
+            Some(chain) => chain,
+            None => return Ok(()),
+        };
+
+        let path = Path::try_from_waypoints(&chain.waypoints, self.options).map_err(|reason| {
+            let move_index = match reason {
+                PathError::ZeroLengthSegment { index } => index,
+                PathError::NonFiniteCoordinate { index } | PathError::DegenerateBlend { index } => {
+                    index.saturating_sub(1)
+                }
+                _ => 0,
+            };
+
+            GcodeError::InvalidPath {
+                line: chain.lines[move_index.min(chain.lines.len() - 1)],
+                reason,
+            }
+        })?;
+
+        // Segments alternate between a linear segment for each move and a blend at the start of
+        // the next move
+        let segment_lines = (0..path.segments.len())
+            .map(|index| chain.lines[(index + 1) / 2])
+            .collect();
+
+        self.moves.push(GcodeMove {
+            kind: chain.kind,
+            feed_rate: chain.feed_rate,
+            path,
+            segment_lines,
+        });
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<GcodeMove<N>>, GcodeError> {
+        self.flush()?;
+
+        Ok(self.moves)
+    }
+}
+
+/// Split a line into upper case letter and number pairs, skipping comments
+fn words(line: usize, text: &str) -> Result<Vec<(char, f64)>, GcodeError> {
+    let mut words = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => break,
+            '(' => {
+                for c in &mut chars {
+                    if c == ')' {
+                        break;
+                    }
+                }
+            }
+            '%' => (),
+            c if c.is_whitespace() => (),
+            c if c.is_ascii_alphabetic() => {
+                let mut number = String::new();
+
+                while let Some(&next) = chars.peek() {
+                    // Some programs put spaces between a letter and its number, like `X 10`
+                    if number.is_empty() && next.is_whitespace() {
+                        let _ = chars.next();
+                    } else if next.is_ascii_digit()
+                        || next == '.'
+                        || (number.is_empty() && (next == '-' || next == '+'))
+                    {
+                        number.push(next);
+                        let _ = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                let value = number.parse().map_err(|_| GcodeError::InvalidWord {
+                    line,
+                    word: format!("{}{}", c, number),
+                })?;
+
+                words.push((c.to_ascii_uppercase(), value));
+            }
+            c => {
+                return Err(GcodeError::InvalidWord {
+                    line,
+                    word: c.to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(words)
+}
+
+/// Find the center of an arc given by its radius
+///
+/// A positive radius gives the arc that turns through less than half a circle. Returns `None` if
+/// the radius is too small to reach the end point.
+fn radius_center<N>(
+    start: &Coord<N>,
+    end: &Coord<N>,
+    a: usize,
+    b: usize,
+    radius: f64,
+    direction: ArcDirection,
+) -> Option<Coord<N>>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let delta_a = end[a] - start[a];
+    let delta_b = end[b] - start[b];
+    let chord = delta_a.hypot(delta_b);
+    let abs_radius = radius.abs();
+
+    if chord < TRAJECTORY_EPSILON
+        || abs_radius < chord / 2.0 - ARC_RADIUS_TOLERANCE * abs_radius.max(1.0)
+    {
+        return None;
+    }
+
+    // Distance from the middle of the chord to the center
+    let height = (abs_radius.powi(2) - chord.powi(2) / 4.0).max(0.0).sqrt();
+
+    // Short counterclockwise arcs have their center to the left of the chord
+    let side = if (direction == ArcDirection::Clockwise) == (radius < 0.0) {
+        1.0
+    } else {
+        -1.0
+    };
+
+    let mut center = start.clone();
+
+    center[a] = (start[a] + end[a]) / 2.0 - side * height * delta_b / chord;
+    center[b] = (start[b] + end[b]) / 2.0 + side * height * delta_a / chord;
+
+    Some(center)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{PathItem, Trajectory, TrajectoryOptions};
+    use nalgebra::{Vector2, U2, U3};
+    use std::f64::consts::PI;
+
+    fn parse3(program: &str) -> Result<Vec<GcodeMove<U3>>, GcodeError> {
+        parse(program, PathOptions::default())
+    }
+
+    fn end_position(gcode_move: &GcodeMove<U3>) -> TestCoord3 {
+        gcode_move.path.position(gcode_move.path.len())
+    }
+
+    #[test]
+    fn linear_moves_are_blended() {
+        let moves = parse3("G21 G90\nG1 F600 X10\nY10\nX0").unwrap();
+
+        assert_eq!(moves.len(), 1);
+
+        let gcode_move = &moves[0];
+
+        assert_eq!(gcode_move.kind, MoveKind::Feed);
+        assert_eq!(gcode_move.feed_rate, Some(600.0));
+        assert_eq!(gcode_move.path.segments.len(), 5);
+        assert_eq!(gcode_move.segment_lines, vec![2, 3, 3, 4, 4]);
+        assert_near!(gcode_move.path.position(0.0), TestCoord3::zeros());
+        assert_near!(end_position(gcode_move), TestCoord3::new(0.0, 10.0, 0.0));
+        assert_eq!(gcode_move.source_line(5.0), 2);
+        assert_eq!(gcode_move.source_line(15.0), 3);
+        assert_eq!(gcode_move.source_line(gcode_move.path.len()), 4);
+    }
+
+    #[test]
+    fn kind_and_feed_changes_split_moves() {
+        let moves = parse3("G0 X10\nG1 X20 F100\nX25\nF200 X30\nM5\nX40").unwrap();
+
+        let summary = moves
+            .iter()
+            .map(|m| (m.kind, m.feed_rate, m.segment_lines.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (MoveKind::Rapid, None, vec![1]),
+                (MoveKind::Feed, Some(100.0), vec![2, 3, 3]),
+                (MoveKind::Feed, Some(200.0), vec![4]),
+                (MoveKind::Feed, Some(200.0), vec![6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reversal_splits_moves() {
+        let moves = parse3("G1 X10 F100\nX0").unwrap();
+
+        assert_eq!(moves.len(), 2);
+        assert_near!(end_position(&moves[1]), TestCoord3::zeros());
+    }
+
+    #[test]
+    fn spaces_between_letters_and_numbers() {
+        let moves = parse3("G1 X 10 F 100\nG 1 Y -10").unwrap();
+
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].feed_rate, Some(100.0));
+        assert_near!(end_position(&moves[0]), TestCoord3::new(10.0, -10.0, 0.0));
+    }
+
+    #[test]
+    fn relative_inch_moves() {
+        let moves = parse3("G20 G91\nG1 X1 F10\nY1 (comment X5) ; X9\nG90 X0 Y0").unwrap();
+
+        assert_eq!(moves[0].feed_rate, Some(254.0));
+        assert_near!(end_position(&moves[0]), TestCoord3::zeros());
+        assert_near!(
+            moves[0].path.position(20.0),
+            TestCoord3::new(20.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn counterclockwise_arc() {
+        let moves = parse3("G1 X10 F100\nG3 X0 Y10 I-10").unwrap();
+
+        assert_eq!(moves.len(), 2);
+
+        let arc = &moves[1];
+
+        assert_eq!(arc.segment_lines, vec![2]);
+        assert_near!(arc.path.len(), PI / 2.0 * 10.0);
+        assert_near!(
+            arc.path.position(arc.path.len() / 2.0),
+            TestCoord3::new(10.0 * (PI / 4.0).cos(), 10.0 * (PI / 4.0).sin(), 0.0)
+        );
+        assert_near!(end_position(arc), TestCoord3::new(0.0, 10.0, 0.0));
+    }
+
+    #[test]
+    fn clockwise_full_circle() {
+        let moves = parse3("G2 I5 F100").unwrap();
+
+        let circle = &moves[0];
+
+        assert_near!(circle.path.len(), 2.0 * PI * 5.0);
+        assert_near!(
+            circle.path.position(circle.path.len() / 4.0),
+            TestCoord3::new(5.0, 5.0, 0.0)
+        );
+        assert_near!(end_position(circle), TestCoord3::zeros());
+    }
+
+    #[test]
+    fn radius_arcs() {
+        let moves = parse3("G2 X10 R5 F100\nG3 X20 R5\nG2 X20 Y10 R-10").unwrap();
+
+        assert_near!(
+            moves[0].path.position(moves[0].path.len() / 2.0),
+            TestCoord3::new(5.0, 5.0, 0.0)
+        );
+        assert_near!(
+            moves[1].path.position(moves[1].path.len() / 2.0),
+            TestCoord3::new(15.0, -5.0, 0.0)
+        );
+        // Long way around, turning through 300 degrees
+        assert_near!(moves[2].path.len(), 5.0 / 3.0 * PI * 10.0);
+        assert_near!(end_position(&moves[2]), TestCoord3::new(20.0, 10.0, 0.0));
+    }
+
+    #[test]
+    fn arc_planes() {
+        let moves = parse3("G18 G2 X10 R5 F100\nG19 G3 Y10 R5").unwrap();
+
+        // Counterclockwise arcs turn from Z to X in the ZX plane and from Y to Z in the YZ plane,
+        // so these arcs both bulge into -Z
+        assert_near!(
+            moves[0].path.position(moves[0].path.len() / 2.0),
+            TestCoord3::new(5.0, 0.0, -5.0)
+        );
+        assert_near!(
+            moves[1].path.position(moves[1].path.len() / 2.0),
+            TestCoord3::new(10.0, 5.0, -5.0)
+        );
+    }
+
+    #[test]
+    fn helical_arcs() {
+        // Two turns of a thread milling pass, ramping down 1mm per turn
+        let moves = parse3("G1 X5 F100\nG2 X5 Z-1 I-5\nG2 X5 Z-2 I-5").unwrap();
+
+        assert_eq!(moves.len(), 3);
+
+        for (kind, z) in [(1, -1.0), (2, -2.0)].iter() {
+            let path = &moves[*kind].path;
+
+            assert!(match path.segments[0] {
+                PathSegment::Helix(_) => true,
+                _ => false,
+            });
+            assert_near!(path.position(path.len()), TestCoord3::new(5.0, 0.0, *z));
+            assert_near!(
+                path.position(path.len() / 4.0),
+                TestCoord3::new(0.0, -5.0, z + 0.75)
+            );
+        }
+    }
+
+    #[test]
+    fn weighted_arcs() {
+        let options = PathOptions {
+            axis_weights: Some(TestCoord3::new(2.0, 2.0, 0.5)),
+            ..PathOptions::default()
+        };
+
+        let moves = parse::<U3>("G1 X10 F100\nG3 X0 Y10 I-10\nG2 X0 Y10 Z4 J-5", options).unwrap();
+
+        // Lengths are measured with the weights, but positions are not weighted
+        let arc = &moves[1].path;
+
+        assert_near!(arc.len(), PI / 2.0 * 10.0 * 2.0);
+        assert_near!(
+            arc.position(arc.len() / 2.0),
+            TestCoord3::new(10.0 * (PI / 4.0).cos(), 10.0 * (PI / 4.0).sin(), 0.0)
+        );
+
+        let helix = &moves[2].path;
+
+        assert_near!(helix.len(), (2.0 * PI * 5.0 * 2.0).hypot(4.0 * 0.5));
+        assert_near!(
+            helix.position(helix.len() / 4.0),
+            TestCoord3::new(5.0, 5.0, 1.0)
+        );
+        assert_near!(helix.position(helix.len()), TestCoord3::new(0.0, 10.0, 4.0));
+
+        assert_eq!(
+            parse::<U3>(
+                "G1 X10 F100\nG3 X0 Y10 I-10",
+                PathOptions {
+                    axis_weights: Some(TestCoord3::new(1.0, 2.0, 1.0)),
+                    ..PathOptions::default()
+                }
+            ),
+            Err(GcodeError::UnevenArcWeights { line: 2 })
+        );
+    }
+
+    #[test]
+    fn two_axis_paths() {
+        let moves = parse::<U2>("G1 X1 Y1 F100\nG2 X2 Y0 I1", PathOptions::default()).unwrap();
+
+        assert_eq!(moves.len(), 2);
+        assert_near!(
+            moves[1].path.position(moves[1].path.len()),
+            Vector2::new(2.0, 0.0)
+        );
+
+        assert_eq!(
+            parse::<U2>("G1 Z1 F100", PathOptions::default()),
+            Err(GcodeError::AxisOutOfRange { line: 1, axis: 'Z' })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse3("G1 X1 F100\nG28"),
+            Err(GcodeError::UnsupportedCode {
+                line: 2,
+                code: "G28".into()
+            })
+        );
+        assert_eq!(parse3("X1"), Err(GcodeError::NoMotionMode { line: 1 }));
+        assert_eq!(
+            parse3("G1 X1 B1"),
+            Err(GcodeError::AxisOutOfRange { line: 1, axis: 'B' })
+        );
+        assert_eq!(parse3("G2 X10 R1"), Err(GcodeError::InvalidArc { line: 1 }));
+        assert_eq!(parse3("G2 X10 I1"), Err(GcodeError::InvalidArc { line: 1 }));
+        assert_eq!(
+            parse3("G1 X1.2.3"),
+            Err(GcodeError::InvalidWord {
+                line: 1,
+                word: "X1.2.3".into()
+            })
+        );
+    }
+
+    #[test]
+    fn moves_create_trajectories() {
+        let moves =
+            parse3("G0 X5 Y5\nG1 X10 F100\nG3 X15 Y10 J5\nG1 Y15 X10\nG2 I-2.5 J-2.5").unwrap();
+
+        assert_eq!(moves.len(), 5);
+
+        for gcode_move in moves.iter() {
+            let trajectory = Trajectory::new(
+                &gcode_move.path,
+                TrajectoryOptions {
+                    timestep: 0.01,
+                    ..TrajectoryOptions::default()
+                },
+            )
+            .unwrap();
+
+            assert_near!(
+                trajectory.position(trajectory.duration()),
+                end_position(gcode_move)
+            );
+        }
+    }
+}
@@ -47,15 +47,28 @@ extern crate log;
 
 #[macro_use]
 mod macros;
+#[cfg(feature = "serde")]
+mod envelope;
+pub mod gcode;
 mod path;
+mod pose;
 pub mod prelude;
 #[doc(hidden)]
 pub mod test_helpers;
 mod trajectory;
 mod trajectory_builder;
 
-pub use crate::path::{Path, PathItem, PathOptions};
-pub use crate::trajectory::{Trajectory, TrajectoryOptions};
+pub use crate::path::{
+    ArcDirection, BSplinePathSegment, Blend, CircularPathSegment, ClothoidPathSegment,
+    CustomPathSegment, HelixPathSegment, LinearPathSegment, Path, PathBuilder, PathError, PathItem,
+    PathOptions, PathSegment, PathTransform, Segment, SplinePathSegment, TransformedPathSegment,
+    TrimmedPathSegment,
+};
+pub use crate::pose::{PosePath, PosePathOptions, PoseTrajectory, PoseTrajectoryOptions};
+pub use crate::trajectory::{
+    KinematicState, Sample, Trajectory, TrajectoryError, TrajectoryOptions,
+    TrajectoryOptionsBuilder, TrajectoryOptionsError, TrajectorySampler, TrajectorySamples,
+};
 use nalgebra::VectorN;
 
 /// Type alias for all vector operations
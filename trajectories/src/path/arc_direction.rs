@@ -0,0 +1,13 @@
+/// Direction of travel around an arc
+///
+/// Directions are given looking down the normal of the arc plane. For an arc in the plane formed
+/// by axes `a` and `b`, counterclockwise turns from axis `a` towards axis `b`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ArcDirection {
+    /// Turn from the second plane axis towards the first, like `G2`
+    Clockwise,
+
+    /// Turn from the first plane axis towards the second, like `G3`
+    Counterclockwise,
+}
@@ -0,0 +1,298 @@
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Number of arc length samples taken along each span between two breakpoints
+const SAMPLES_PER_SPAN: usize = 16;
+
+/// Maximum number of Newton iterations used to find the curve parameter at a distance
+const NEWTON_ITERATIONS: usize = 8;
+
+/// Number of bisection or golden section iterations used to locate switching points
+const SEARCH_ITERATIONS: usize = 64;
+
+/// Five point Gauss-Legendre quadrature nodes on `[-1, 1]`
+const GAUSS_NODES: [f64; 5] = [
+    0.0,
+    -0.538_469_310_105_683_1,
+    0.538_469_310_105_683_1,
+    -0.906_179_845_938_664,
+    0.906_179_845_938_664,
+];
+
+/// Five point Gauss-Legendre quadrature weights
+const GAUSS_WEIGHTS: [f64; 5] = [
+    0.568_888_888_888_888_9,
+    0.478_628_670_499_366_5,
+    0.478_628_670_499_366_5,
+    0.236_926_885_056_189_1,
+    0.236_926_885_056_189_1,
+];
+
+/// A curve evaluated at a parameter that is not its arc length
+pub(crate) trait ParametricCurve<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the position, first derivative and second derivative at a curve parameter
+    fn derivatives(&self, parameter: f64) -> (Coord<N>, Coord<N>, Coord<N>);
+
+    /// Get the arc length between two parameters using Gauss-Legendre quadrature
+    fn length_between(&self, low: f64, high: f64) -> f64 {
+        let half = (high - low) / 2.0;
+        let middle = (high + low) / 2.0;
+
+        GAUSS_NODES
+            .iter()
+            .zip(GAUSS_WEIGHTS.iter())
+            .map(|(node, weight)| weight * self.derivatives(middle + half * node).1.norm())
+            .sum::<f64>()
+            * half
+    }
+
+    /// Get the curvature at a curve parameter
+    fn curvature_at(&self, parameter: f64) -> Coord<N> {
+        let (_, first, second) = self.derivatives(parameter);
+
+        curvature_from(&first, &second)
+    }
+
+    /// Find the parameter where one component of the tangent crosses zero between two parameters
+    fn tangent_zero(&self, axis: usize, mut low: f64, mut high: f64) -> f64 {
+        let low_sign = self.derivatives(low).1[axis].signum();
+
+        for _ in 0..SEARCH_ITERATIONS {
+            let middle = (low + high) / 2.0;
+
+            if self.derivatives(middle).1[axis].signum() == low_sign {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+
+    /// Find the parameter of greatest curvature between two parameters
+    fn curvature_peak(&self, mut low: f64, mut high: f64) -> f64 {
+        let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+
+        for _ in 0..SEARCH_ITERATIONS {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+
+            if self.curvature_at(a).norm() > self.curvature_at(b).norm() {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+}
+
+/// Get the rate of change of the unit tangent with respect to arc length from the first and
+/// second derivatives of a curve
+pub(crate) fn curvature_from<N>(first: &Coord<N>, second: &Coord<N>) -> Coord<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let speed_squared = first.norm_squared();
+    let tangent = first / speed_squared.sqrt();
+
+    (second - &tangent * tangent.dot(second)) / speed_squared
+}
+
+/// Arc length sampled along a parametric curve, used to map distances back to curve parameters
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct ArcLengthTable {
+    /// Curve parameter at each sample
+    parameters: Vec<f64>,
+
+    /// Arc length from the start of the curve at each sample
+    lengths: Vec<f64>,
+}
+
+impl ArcLengthTable {
+    /// Sample the arc length of a curve between each pair of consecutive breakpoints
+    pub fn new<N, C>(curve: &C, breakpoints: &[f64]) -> Self
+    where
+        N: DimName + Copy,
+        DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+        <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+        Owned<f64, N>: Copy,
+        C: ParametricCurve<N>,
+    {
+        let mut parameters = vec![breakpoints[0]];
+        let mut lengths = vec![0.0];
+
+        for span in breakpoints.windows(2) {
+            let (start, end) = (span[0], span[1]);
+
+            for sample in 1..=SAMPLES_PER_SPAN {
+                let low = parameters[parameters.len() - 1];
+                let high = if sample == SAMPLES_PER_SPAN {
+                    end
+                } else {
+                    start + (end - start) * sample as f64 / SAMPLES_PER_SPAN as f64
+                };
+
+                lengths.push(lengths[lengths.len() - 1] + curve.length_between(low, high));
+                parameters.push(high);
+            }
+        }
+
+        Self {
+            parameters,
+            lengths,
+        }
+    }
+
+    /// Get the total length of the curve
+    pub fn length(&self) -> f64 {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    /// Get the index of the sample interval containing a value
+    fn sample_index(samples: &[f64], value: f64) -> usize {
+        samples
+            .partition_point(|sample| *sample <= value)
+            .max(1)
+            .min(samples.len() - 1)
+    }
+
+    /// Get the arc length from the start of the curve to a parameter
+    pub fn length_at<N, C>(&self, curve: &C, parameter: f64) -> f64
+    where
+        N: DimName + Copy,
+        DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+        <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+        Owned<f64, N>: Copy,
+        C: ParametricCurve<N>,
+    {
+        let index = Self::sample_index(&self.parameters, parameter);
+
+        self.lengths[index - 1] + curve.length_between(self.parameters[index - 1], parameter)
+    }
+
+    /// Get the curve parameter at a distance from the start of the curve
+    pub fn parameter_at<N, C>(&self, curve: &C, distance: f64) -> f64
+    where
+        N: DimName + Copy,
+        DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+        <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+        Owned<f64, N>: Copy,
+        C: ParametricCurve<N>,
+    {
+        let length = self.length();
+        let distance = distance.max(0.0).min(length);
+        let index = Self::sample_index(&self.lengths, distance);
+
+        let (low, high) = (self.parameters[index - 1], self.parameters[index]);
+        let (low_length, high_length) = (self.lengths[index - 1], self.lengths[index]);
+
+        // Interpolate between samples for a first guess, then refine it with Newton's method
+        let mut parameter = if high_length > low_length {
+            low + (high - low) * (distance - low_length) / (high_length - low_length)
+        } else {
+            low
+        };
+
+        for _ in 0..NEWTON_ITERATIONS {
+            let error = low_length + curve.length_between(low, parameter) - distance;
+            let speed = curve.derivatives(parameter).1.norm();
+
+            if error.abs() <= std::f64::EPSILON * length || speed <= 0.0 {
+                break;
+            }
+
+            parameter = (parameter - error / speed).max(low).min(high);
+        }
+
+        parameter
+    }
+
+    /// Get the unit tangent and curvature at a distance from the start of the curve
+    pub fn tangent_and_curvature<N, C>(&self, curve: &C, distance: f64) -> (Coord<N>, Coord<N>)
+    where
+        N: DimName + Copy,
+        DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+        <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+        Owned<f64, N>: Copy,
+        C: ParametricCurve<N>,
+    {
+        let (_, first, second) = curve.derivatives(self.parameter_at(curve, distance));
+
+        (first.normalize(), curvature_from(&first, &second))
+    }
+
+    /// Find switching points numerically where a component of the tangent crosses zero, and at
+    /// local maxima of curvature
+    ///
+    /// Positions are relative to the start of the curve.
+    pub fn switching_points<N, C>(&self, curve: &C) -> Vec<f64>
+    where
+        N: DimName + Copy,
+        DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+        <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+        Owned<f64, N>: Copy,
+        C: ParametricCurve<N>,
+    {
+        let mut switching_points = Vec::new();
+
+        let samples = self
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let (_, first, second) = curve.derivatives(*parameter);
+
+                (*parameter, first, curvature_from(&first, &second).norm())
+            })
+            .collect::<Vec<_>>();
+
+        for parts in samples.windows(2) {
+            let (low, low_first, _) = &parts[0];
+            let (high, high_first, _) = &parts[1];
+
+            for axis in 0..low_first.len() {
+                if low_first[axis] * high_first[axis] < 0.0 {
+                    let parameter = curve.tangent_zero(axis, *low, *high);
+
+                    switching_points.push(self.length_at(curve, parameter));
+                }
+            }
+        }
+
+        for parts in samples.windows(3) {
+            let (low, _, before) = parts[0];
+            let (_, _, current) = parts[1];
+            let (high, _, after) = parts[2];
+
+            if current > before && current >= after {
+                let parameter = curve.curvature_peak(low, high);
+
+                switching_points.push(self.length_at(curve, parameter));
+            }
+        }
+
+        switching_points
+            .sort_unstable_by(|a, b| a.partial_cmp(b).expect("Could not sort switching points"));
+
+        switching_points.dedup_by(|a, b| (*a - *b).abs() < TRAJECTORY_EPSILON);
+
+        switching_points
+    }
+}
@@ -0,0 +1,22 @@
+/// Type of curve used to blend the corner at each waypoint of a path
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Blend {
+    /// Circular arc
+    ///
+    /// Curvature jumps where the arc meets the linear segments on either side, so each end of the
+    /// blend is a discontinuous switching point.
+    Circular,
+
+    /// Pair of clothoids (Euler spirals)
+    ///
+    /// Curvature increases linearly from zero to the middle of the blend and back down again, so
+    /// the path is curvature continuous where the blend meets the linear segments.
+    Clothoid,
+}
+
+impl Default for Blend {
+    fn default() -> Self {
+        Blend::Circular
+    }
+}
@@ -0,0 +1,642 @@
+use super::{ArcLengthTable, ParametricCurve, PathError, PathItem, PathTransform};
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// B-spline or NURBS path segment
+///
+/// A rational B-spline curve defined by its degree, control points, weights and knot vector, as
+/// produced by CAD/CAM exports. Derivatives are evaluated exactly from the basis functions, and
+/// the curve is reparameterised by arc length so that distances along it behave like distances
+/// along any other segment.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct BSplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Polynomial degree of each span
+    degree: usize,
+
+    /// Control points
+    control_points: Vec<Coord<N>>,
+
+    /// Weight of each control point
+    weights: Vec<f64>,
+
+    /// Knot vector, with `degree + 1` more knots than there are control points
+    knots: Vec<f64>,
+
+    /// Arc length samples along the curve
+    arc_length: ArcLengthTable,
+
+    /// Length of this segment
+    pub length: f64,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> BSplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a NURBS curve from its degree, control points, weights and knot vector
+    ///
+    /// There must be one positive weight for each control point, and `degree + 1` more knots than
+    /// control points. Knots must not decrease, and no interior knot may be repeated more than
+    /// `degree` times. Returns `PathError::InvalidBSpline` if any of these do not hold or the curve
+    /// has no length, or `PathError::NonFiniteCoordinate` if a control point has a NaN or infinite
+    /// coordinate.
+    pub fn new(
+        degree: usize,
+        control_points: &[Coord<N>],
+        weights: &[f64],
+        knots: &[f64],
+    ) -> Result<Self, PathError> {
+        let count = control_points.len();
+
+        if degree == 0
+            || count < degree + 1
+            || weights.len() != count
+            || knots.len() != count + degree + 1
+        {
+            return Err(PathError::InvalidBSpline);
+        }
+
+        if let Some(index) = control_points
+            .iter()
+            .position(|point| point.iter().any(|c| !c.is_finite()))
+        {
+            return Err(PathError::NonFiniteCoordinate { index });
+        }
+
+        if weights
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight <= 0.0)
+            || knots.iter().any(|knot| !knot.is_finite())
+            || knots.windows(2).any(|pair| pair[1] < pair[0])
+            || knots[degree] >= knots[count]
+        {
+            return Err(PathError::InvalidBSpline);
+        }
+
+        // A knot repeated more than `degree` times inside the curve splits it in two
+        let breakpoints = Self::breakpoints(degree, knots);
+
+        if breakpoints[1..breakpoints.len() - 1]
+            .iter()
+            .any(|breakpoint| Self::multiplicity(knots, *breakpoint) > degree)
+        {
+            return Err(PathError::InvalidBSpline);
+        }
+
+        let mut segment = Self {
+            degree,
+            control_points: control_points.to_vec(),
+            weights: weights.to_vec(),
+            knots: knots.to_vec(),
+            arc_length: ArcLengthTable::default(),
+            length: 0.0,
+            start_offset: 0.0,
+            end_offset: 0.0,
+        };
+
+        segment.arc_length = ArcLengthTable::new(&segment, &breakpoints);
+        segment.length = segment.arc_length.length();
+        segment.end_offset = segment.length;
+
+        if segment.length.is_nan() || segment.length <= TRAJECTORY_EPSILON {
+            return Err(PathError::InvalidBSpline);
+        }
+
+        Ok(segment)
+    }
+
+    /// Create a non-rational B-spline curve, where every control point has a weight of 1
+    pub fn from_control_points(
+        degree: usize,
+        control_points: &[Coord<N>],
+        knots: &[f64],
+    ) -> Result<Self, PathError> {
+        Self::new(
+            degree,
+            control_points,
+            &vec![1.0; control_points.len()],
+            knots,
+        )
+    }
+
+    /// Get the degree of this curve
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Get the control points of this curve
+    pub fn control_points(&self) -> &[Coord<N>] {
+        &self.control_points
+    }
+
+    /// Get the weight of each control point
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Get the knot vector of this curve
+    pub fn knots(&self) -> &[f64] {
+        &self.knots
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.length,
+            ..self
+        }
+    }
+
+    /// Get this curve traversed from end to start, with the same start offset
+    ///
+    /// Control points and weights are reversed, and the knot vector is mirrored.
+    pub fn reversed(&self) -> Self {
+        let (first, last) = (self.knots[0], self.knots[self.knots.len() - 1]);
+
+        let control_points = self
+            .control_points
+            .iter()
+            .rev()
+            .cloned()
+            .collect::<Vec<_>>();
+        let weights = self.weights.iter().rev().cloned().collect::<Vec<_>>();
+        let knots = self
+            .knots
+            .iter()
+            .rev()
+            .map(|knot| first + last - knot)
+            .collect::<Vec<_>>();
+
+        Self::new(self.degree, &control_points, &weights, &knots)
+            .expect("Reversed curve must be valid")
+            .with_start_offset(self.start_offset)
+    }
+
+    /// Get this curve moved by a transform, with the same start offset
+    ///
+    /// NURBS curves keep their form under any affine transform, so only the control points move.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Self {
+        let control_points = self
+            .control_points
+            .iter()
+            .map(|point| transform.transform_point(point))
+            .collect::<Vec<_>>();
+
+        Self::new(self.degree, &control_points, &self.weights, &self.knots)
+            .expect("Transformed curve must be valid")
+            .with_start_offset(self.start_offset)
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points for this curve
+    ///
+    /// Switching points are found numerically where a component of the tangent crosses zero and
+    /// at local maxima of curvature, and are added at knots where curvature may jump. Positions
+    /// are relative to the start of the curve.
+    pub fn switching_points(&self) -> Vec<f64> {
+        let mut switching_points = self.arc_length.switching_points(self);
+
+        let breakpoints = Self::breakpoints(self.degree, &self.knots);
+
+        switching_points.extend(
+            breakpoints[1..breakpoints.len() - 1]
+                .iter()
+                .filter(|knot| Self::multiplicity(&self.knots, **knot) + 2 > self.degree)
+                .map(|knot| self.arc_length.length_at(self, *knot)),
+        );
+
+        switching_points
+            .sort_unstable_by(|a, b| a.partial_cmp(b).expect("Could not sort switching points"));
+
+        switching_points.dedup_by(|a, b| (*a - *b).abs() < TRAJECTORY_EPSILON);
+
+        switching_points
+    }
+
+    /// Get the distinct knot values within the domain of the curve
+    fn breakpoints(degree: usize, knots: &[f64]) -> Vec<f64> {
+        let mut breakpoints = knots[degree..knots.len() - degree].to_vec();
+
+        breakpoints.dedup();
+
+        breakpoints
+    }
+
+    /// Get the number of times a value appears in the knot vector
+    fn multiplicity(knots: &[f64], knot: f64) -> usize {
+        knots.iter().filter(|k| **k == knot).count()
+    }
+
+    /// Get the index of the knot span containing a parameter, clamped to the domain of the curve
+    fn span(&self, parameter: f64) -> usize {
+        let last = self.control_points.len() - 1;
+
+        let mut span = (self.knots.partition_point(|knot| *knot <= parameter))
+            .saturating_sub(1)
+            .max(self.degree)
+            .min(last);
+
+        // Step back over empty spans at the end of the curve
+        while self.knots[span] >= self.knots[span + 1] {
+            span -= 1;
+        }
+
+        span
+    }
+
+    /// Get the values, first and second derivatives of the basis functions that are non zero in a
+    /// span, for control points `span - degree` to `span`
+    fn basis_functions(&self, span: usize, parameter: f64) -> [Vec<f64>; 3] {
+        let degree = self.degree;
+        let knots = &self.knots;
+        let first_index = span - degree;
+
+        // Treat 0/0 as 0 where repeated knots give basis functions with no support
+        let ratio = |a: f64, b: f64| if b == 0.0 { 0.0 } else { a / b };
+
+        // Basis function values of each degree up to `degree`, with trailing zeros so the
+        // recurrences below can look one function past the end
+        let mut values = vec![vec![0.0; degree + 3]; degree + 1];
+        values[0][degree] = 1.0;
+
+        for order in 1..=degree {
+            for j in 0..=degree {
+                let i = first_index + j;
+
+                values[order][j] = ratio(parameter - knots[i], knots[i + order] - knots[i])
+                    * values[order - 1][j]
+                    + ratio(
+                        knots[i + order + 1] - parameter,
+                        knots[i + order + 1] - knots[i + 1],
+                    ) * values[order - 1][j + 1];
+            }
+        }
+
+        // First derivative of a basis function of a given degree, from those of one degree lower
+        let derivative = |order: usize, j: usize, lower: &[f64]| {
+            if order == 0 {
+                return 0.0;
+            }
+
+            let i = first_index + j;
+
+            order as f64
+                * (ratio(lower[j], knots[i + order] - knots[i])
+                    - ratio(lower[j + 1], knots[i + order + 1] - knots[i + 1]))
+        };
+
+        let first = (0..=degree)
+            .map(|j| derivative(degree, j, &values[degree - 1]))
+            .collect::<Vec<f64>>();
+
+        let lower_first = (0..degree + 2)
+            .map(|j| {
+                if degree >= 2 {
+                    derivative(degree - 1, j, &values[degree - 2])
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<f64>>();
+
+        let second = (0..=degree)
+            .map(|j| derivative(degree, j, &lower_first))
+            .collect::<Vec<f64>>();
+
+        [values[degree][0..=degree].to_vec(), first, second]
+    }
+}
+
+impl<N> ParametricCurve<N> for BSplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the position, first derivative and second derivative at a curve parameter
+    fn derivatives(&self, parameter: f64) -> (Coord<N>, Coord<N>, Coord<N>) {
+        let span = self.span(parameter);
+        let [values, first, second] = self.basis_functions(span, parameter);
+
+        // Weighted sums of the control points and of the weights alone, with their derivatives
+        let mut points = [Coord::<N>::zeros(), Coord::zeros(), Coord::zeros()];
+        let mut weights = [0.0; 3];
+
+        for j in 0..=self.degree {
+            let index = span - self.degree + j;
+            let weight = self.weights[index];
+            let point = &self.control_points[index] * weight;
+
+            for (k, basis) in [values[j], first[j], second[j]].iter().enumerate() {
+                points[k] += &point * *basis;
+                weights[k] += weight * basis;
+            }
+        }
+
+        // Quotient rule for the rational curve
+        let position = &points[0] / weights[0];
+        let first = (&points[1] - &position * weights[1]) / weights[0];
+        let second =
+            (&points[2] - &first * (2.0 * weights[1]) - &position * weights[2]) / weights[0];
+
+        (position, first, second)
+    }
+}
+
+impl<N> PathItem<N> for BSplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the arc length of this curve
+    fn len(&self) -> f64 {
+        self.length
+    }
+
+    /// Get position at a point along the curve
+    fn position(&self, distance_along_curve: f64) -> Coord<N> {
+        let parameter = self
+            .arc_length
+            .parameter_at(self, distance_along_curve - self.start_offset);
+
+        self.derivatives(parameter).0
+    }
+
+    /// Get the unit tangent at a point along the curve
+    fn tangent(&self, distance_along_curve: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_curve).0
+    }
+
+    /// Get the curvature (second derivative with respect to arc length) at a point along the
+    /// curve
+    fn curvature(&self, distance_along_curve: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_curve).1
+    }
+
+    fn tangent_and_curvature(&self, distance_along_curve: f64) -> (Coord<N>, Coord<N>) {
+        self.arc_length
+            .tangent_and_curvature(self, distance_along_curve - self.start_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{Path, PathSegment, Trajectory};
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn rational_circle() {
+        // Half of a unit circle in the XY plane as a quadratic NURBS curve
+        let w = 0.5f64.sqrt();
+        let circle = BSplinePathSegment::new(
+            2,
+            &[
+                TestCoord3::new(1.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(0.0, 1.0, 0.0),
+                TestCoord3::new(-1.0, 1.0, 0.0),
+                TestCoord3::new(-1.0, 0.0, 0.0),
+            ],
+            &[1.0, w, 1.0, w, 1.0],
+            &[0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 2.0],
+        )
+        .unwrap();
+
+        assert_near!(circle.len(), PI);
+
+        for i in 0..=20 {
+            let angle = PI * i as f64 / 20.0;
+            let (tangent, curvature) = circle.tangent_and_curvature(angle);
+
+            assert_near!(
+                circle.position(angle),
+                TestCoord3::new(angle.cos(), angle.sin(), 0.0)
+            );
+            assert_near!(tangent, TestCoord3::new(-angle.sin(), angle.cos(), 0.0));
+            assert_near!(curvature, TestCoord3::new(-angle.cos(), -angle.sin(), 0.0));
+        }
+    }
+
+    #[test]
+    fn clamped_ends() {
+        let curve = BSplinePathSegment::from_control_points(
+            3,
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(2.0, 0.0, 0.5),
+                TestCoord3::new(3.0, 1.0, 1.0),
+                TestCoord3::new(4.0, 0.0, 1.0),
+            ],
+            &[0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+        )
+        .unwrap()
+        .with_start_offset(3.0);
+
+        assert_near!(curve.position(3.0), TestCoord3::new(0.0, 0.0, 0.0));
+        assert_near!(
+            curve.position(curve.end_offset()),
+            TestCoord3::new(4.0, 0.0, 1.0)
+        );
+        assert_near!(
+            curve.tangent(3.0),
+            TestCoord3::new(1.0, 1.0, 0.0).normalize()
+        );
+    }
+
+    #[test]
+    fn arc_length_parameterisation() {
+        let curve = BSplinePathSegment::from_control_points(
+            3,
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(2.0, 0.0, 0.5),
+                TestCoord3::new(3.0, 1.0, 1.0),
+                TestCoord3::new(4.0, 0.0, 1.0),
+            ],
+            &[0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+        )
+        .unwrap()
+        .with_start_offset(2.0);
+        let h = 1e-5;
+
+        for i in 1..100 {
+            let distance = 2.0 + curve.len() * i as f64 / 100.0;
+            let (tangent, curvature) = curve.tangent_and_curvature(distance);
+
+            assert!((tangent.norm() - 1.0).abs() < 1e-9);
+
+            let position_derivative =
+                (curve.position(distance + h) - curve.position(distance - h)) / (2.0 * h);
+            let tangent_derivative =
+                (curve.tangent(distance + h) - curve.tangent(distance - h)) / (2.0 * h);
+
+            assert!((position_derivative - tangent).norm() < 1e-6);
+            assert!((tangent_derivative - curvature).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn finds_switching_points() {
+        let w = 0.5f64.sqrt();
+        let circle = BSplinePathSegment::new(
+            2,
+            &[
+                TestCoord3::new(1.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(0.0, 1.0, 0.0),
+                TestCoord3::new(-1.0, 1.0, 0.0),
+                TestCoord3::new(-1.0, 0.0, 0.0),
+            ],
+            &[1.0, w, 1.0, w, 1.0],
+            &[0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 2.0],
+        )
+        .unwrap();
+
+        // The Y tangent of the half circle reverses at the top, which is also a double knot
+        let points = circle.switching_points();
+
+        assert!(points.iter().any(|point| (point - FRAC_PI_2).abs() < 1e-6));
+
+        let curve = BSplinePathSegment::from_control_points(
+            3,
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(2.0, 0.0, 0.5),
+                TestCoord3::new(3.0, 1.0, 1.0),
+                TestCoord3::new(4.0, 0.0, 1.0),
+            ],
+            &[0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+        )
+        .unwrap();
+        let points = curve.switching_points();
+
+        assert!(!points.is_empty());
+
+        for point in points {
+            let tangent = curve.tangent(point);
+
+            let tangent_zero = tangent.iter().any(|c| c.abs() < 1e-6);
+            let curvature_peak = curve.curvature(point).norm()
+                >= curve.curvature(point - 1e-3).norm()
+                && curve.curvature(point).norm() >= curve.curvature(point + 1e-3).norm();
+
+            assert!(tangent_zero || curvature_peak);
+        }
+    }
+
+    #[test]
+    fn invalid_curves() {
+        let points = [
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.0),
+        ];
+        let knots = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+
+        // Wrong number of knots
+        assert_eq!(
+            BSplinePathSegment::from_control_points(2, &points, &knots[1..]),
+            Err(PathError::InvalidBSpline)
+        );
+
+        // Negative weight
+        assert_eq!(
+            BSplinePathSegment::new(2, &points, &[1.0, -1.0, 1.0], &knots),
+            Err(PathError::InvalidBSpline)
+        );
+
+        // Decreasing knots
+        assert_eq!(
+            BSplinePathSegment::from_control_points(2, &points, &[0.0, 0.0, 0.0, 1.0, 0.5, 1.0]),
+            Err(PathError::InvalidBSpline)
+        );
+
+        // All control points at the same position
+        assert_eq!(
+            BSplinePathSegment::from_control_points(2, &[TestCoord3::zeros(); 3], &knots),
+            Err(PathError::InvalidBSpline)
+        );
+
+        assert_eq!(
+            BSplinePathSegment::from_control_points(
+                2,
+                &[
+                    points[0],
+                    TestCoord3::new(std::f64::NAN, 0.0, 0.0),
+                    points[2]
+                ],
+                &knots
+            ),
+            Err(PathError::NonFiniteCoordinate { index: 1 })
+        );
+    }
+
+    #[test]
+    fn bspline_trajectory() {
+        let curve = BSplinePathSegment::from_control_points(
+            3,
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(2.0, 0.0, 0.5),
+                TestCoord3::new(3.0, 1.0, 1.0),
+                TestCoord3::new(4.0, 0.0, 1.0),
+            ],
+            &[0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+        )
+        .unwrap();
+
+        let path = Path::from_segments(vec![PathSegment::BSpline(curve)]);
+        let options = trajectory_options_fixture();
+
+        let trajectory = Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            TestCoord3::new(4.0, 0.0, 1.0)
+        );
+
+        assert_within_limits(&trajectory, &options);
+    }
+}
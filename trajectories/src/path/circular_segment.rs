@@ -1,4 +1,4 @@
-use super::PathItem;
+use super::{ArcDirection, PathError, PathItem, PathTransform};
 use crate::Coord;
 use crate::TRAJECTORY_EPSILON;
 use nalgebra::{
@@ -8,11 +8,23 @@ use nalgebra::{
 };
 use std::f64;
 
+/// Maximum difference between the start and end radius of an arc, relative to its radius
+pub(crate) const ARC_RADIUS_TOLERANCE: f64 = 0.001;
+
 /// Circular path segment
 ///
-/// Used to blend two straight path segments along a circular path. `x` and `y` form a plane on
-/// on which the blend circle lies, with its center at `center`. Radius is radius.
+/// Used to blend two straight path segments along a circular path, or to follow an arc created
+/// with `CircularPathSegment::from_arc`. `x` and `y` form a plane on on which the blend circle
+/// lies, with its center at `center`. Radius is radius.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
 pub struct CircularPathSegment<N>
 where
     N: DimName + Copy,
@@ -157,6 +169,85 @@ where
         }
     }
 
+    /// Create an arc from `start` to `end` around `center` in the plane formed by axes `plane.0`
+    /// and `plane.1`
+    ///
+    /// A full circle is created if `start` and `end` are at the same position. Returns an error if
+    /// the plane axes are invalid, if any coordinate is NaN or infinite, if `start` and `end` are
+    /// different distances from `center`, or if the three points differ in an axis outside the
+    /// plane.
+    pub fn from_arc(
+        start: &Coord<N>,
+        end: &Coord<N>,
+        center: &Coord<N>,
+        plane: (usize, usize),
+        direction: ArcDirection,
+    ) -> Result<Self, PathError> {
+        let (a, b) = plane;
+
+        if a == b || a >= N::dim() || b >= N::dim() {
+            return Err(PathError::InvalidArc);
+        }
+
+        if [start, end, center]
+            .iter()
+            .any(|point| point.iter().any(|c| !c.is_finite()))
+        {
+            return Err(PathError::InvalidArc);
+        }
+
+        let out_of_plane = (0..N::dim()).any(|axis| {
+            axis != a
+                && axis != b
+                && ((start[axis] - center[axis]).abs() > TRAJECTORY_EPSILON
+                    || (end[axis] - center[axis]).abs() > TRAJECTORY_EPSILON)
+        });
+
+        let start_radius = (start - center).norm();
+        let end_radius = (end - center).norm();
+
+        if out_of_plane
+            || start_radius < TRAJECTORY_EPSILON
+            || (start_radius - end_radius).abs() > ARC_RADIUS_TOLERANCE * start_radius.max(1.0)
+        {
+            return Err(PathError::InvalidArc);
+        }
+
+        let radius = start_radius;
+
+        // Unit vector from center to start of arc
+        let x = (start - center) / radius;
+
+        // Direction of travel at the start of the arc, perpendicular to X in the arc plane
+        let mut y = Coord::zeros();
+        y[a] = -x[b];
+        y[b] = x[a];
+
+        if direction == ArcDirection::Clockwise {
+            y = -y;
+        }
+
+        let end = end - center;
+        let mut angle = end.dot(&y).atan2(end.dot(&x));
+
+        if angle <= TRAJECTORY_EPSILON {
+            angle += 2.0 * f64::consts::PI;
+        }
+
+        let arc_length = angle * radius;
+
+        Ok(CircularPathSegment {
+            center: center.clone(),
+            radius,
+            x,
+            y,
+            arc_length,
+            start_offset: 0.0,
+            end_offset: arc_length,
+            empty: false,
+        })
+    }
+
     /// Clone with a start offset
     pub fn with_start_offset(self, start_offset: f64) -> Self {
         Self {
@@ -166,31 +257,148 @@ where
         }
     }
 
+    /// Get the part of this arc between two distances along the path
+    ///
+    /// The trimmed arc starts at `from`.
+    pub fn trim(&self, from: f64, to: f64) -> Self {
+        if self.empty {
+            return self.clone().with_start_offset(from);
+        }
+
+        let angle = (from - self.start_offset) / self.radius;
+        let (angle_s, angle_c) = angle.sin_cos();
+
+        Self {
+            x: &self.x * angle_c + &self.y * angle_s,
+            y: &self.y * angle_c - &self.x * angle_s,
+            arc_length: to - from,
+            start_offset: from,
+            end_offset: to,
+            ..self.clone()
+        }
+    }
+
+    /// Get this arc traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        if self.empty {
+            return self.clone();
+        }
+
+        let angle = self.arc_length / self.radius;
+        let (angle_s, angle_c) = angle.sin_cos();
+
+        Self {
+            x: &self.x * angle_c + &self.y * angle_s,
+            y: &self.x * angle_s - &self.y * angle_c,
+            ..self.clone()
+        }
+    }
+
+    /// Divide each coordinate of this arc by its axis weight, keeping the length measured with the
+    /// weights
+    ///
+    /// The arc becomes elliptical unless every weight is the same.
+    pub(crate) fn unweighted(&self, weights: &Coord<N>) -> Self {
+        Self {
+            center: self.center.component_div(weights),
+            x: self.x.component_div(weights),
+            y: self.y.component_div(weights),
+            ..self.clone()
+        }
+    }
+
+    /// Whether this arc is circular rather than stretched into an ellipse by axis weights
+    pub(crate) fn is_round(&self) -> bool {
+        self.empty
+            || ((self.x.norm() - 1.0).abs() < TRAJECTORY_EPSILON
+                && (self.y.norm() - 1.0).abs() < TRAJECTORY_EPSILON
+                && self.x.dot(&self.y).abs() < TRAJECTORY_EPSILON)
+    }
+
+    /// Get this arc moved by a transform, with the same start offset
+    ///
+    /// Returns `None` if the transform does not keep the arc circular.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Option<Self> {
+        let scale = transform.similarity_scale()?;
+
+        if self.empty {
+            return Some(Self {
+                center: transform.transform_point(&self.center),
+                ..self.clone()
+            });
+        }
+
+        Some(Self {
+            center: transform.transform_point(&self.center),
+            radius: self.radius * scale,
+            x: transform.transform_vector(&self.x) / scale,
+            y: transform.transform_vector(&self.y) / scale,
+            arc_length: self.arc_length * scale,
+            end_offset: self.start_offset + self.arc_length * scale,
+            ..self.clone()
+        })
+    }
+
+    /// Get the distance along the path of the point on this arc closest to `point`
+    ///
+    /// Points off the plane of the arc are projected onto it first. If the projected point lies
+    /// outside the arc, the nearer end of the arc is closest.
+    pub fn project(&self, point: &Coord<N>) -> f64 {
+        if self.empty {
+            return self.start_offset;
+        }
+
+        let offset = point - &self.center;
+        let mut angle = offset.dot(&self.y).atan2(offset.dot(&self.x));
+
+        if angle < 0.0 {
+            angle += 2.0 * f64::consts::PI;
+        }
+
+        if angle * self.radius <= self.arc_length {
+            return self.start_offset + angle * self.radius;
+        }
+
+        let to_start = (self.position(self.start_offset) - point).norm();
+        let to_end = (self.position(self.end_offset) - point).norm();
+
+        if to_start <= to_end {
+            self.start_offset
+        } else {
+            self.end_offset
+        }
+    }
+
     /// Get switching points for circular segment
     ///
     /// A segment can have a switching point for each dimension at various points along its path.
     /// Takes into account the path's start offset
-    // TODO: Trait
     pub fn switching_points(&self) -> Vec<f64> {
         // Loop through each _component_ of unit vectors X and Y
         let mut switching_points = self
             .x
             .iter()
             .zip(self.y.iter())
-            .filter_map(|(x, y)| {
+            .flat_map(|(x, y)| {
                 let mut switching_angle = y.atan2(*x);
 
                 if switching_angle < 0.0 {
                     switching_angle += f64::consts::PI;
                 }
 
-                let switching_point = switching_angle * self.radius;
-
-                if switching_point < self.arc_length {
-                    Some(switching_point)
+                // The tangent of an axis in the arc plane crosses zero every half turn, which
+                // matters for arcs longer than a blend
+                let repeats = if x.abs() < TRAJECTORY_EPSILON && y.abs() < TRAJECTORY_EPSILON {
+                    1
                 } else {
-                    None
-                }
+                    2
+                };
+
+                (0..repeats)
+                    .map(move |half_turn| {
+                        (switching_angle + half_turn as f64 * f64::consts::PI) * self.radius
+                    })
+                    .filter(move |switching_point| *switching_point < self.arc_length)
             })
             .collect::<Vec<f64>>();
 
@@ -294,6 +502,103 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn it_gets_full_circle_switching_points() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+        let center = TestCoord3::new(0.0, 0.0, 0.0);
+
+        let circle = CircularPathSegment::from_arc(
+            &start,
+            &start,
+            &center,
+            (0, 1),
+            ArcDirection::Counterclockwise,
+        )
+        .unwrap();
+
+        assert_near!(circle.len(), 2.0 * f64::consts::PI);
+        assert_near!(
+            circle.position(f64::consts::PI / 2.0),
+            TestCoord3::new(0.0, 1.0, 0.0)
+        );
+
+        // Each in-plane axis reverses every half turn
+        let expected = [0.0, 0.0, 0.5, 1.0, 1.5]
+            .iter()
+            .map(|turns| turns * f64::consts::PI)
+            .collect::<Vec<f64>>();
+
+        for (point, expected) in circle.switching_points().iter().zip(expected.iter()) {
+            assert_near!(point, expected);
+        }
+
+        assert_eq!(circle.switching_points().len(), expected.len());
+    }
+
+    #[test]
+    fn it_rejects_invalid_arcs() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+        let center = TestCoord3::new(0.0, 0.0, 0.0);
+        let direction = ArcDirection::Clockwise;
+
+        // Different start and end radius
+        assert_eq!(
+            CircularPathSegment::from_arc(
+                &start,
+                &TestCoord3::new(0.0, 2.0, 0.0),
+                &center,
+                (0, 1),
+                direction
+            ),
+            Err(PathError::InvalidArc)
+        );
+
+        // End is out of the arc plane
+        assert_eq!(
+            CircularPathSegment::from_arc(
+                &start,
+                &TestCoord3::new(0.0, 0.0, 1.0),
+                &center,
+                (0, 1),
+                direction
+            ),
+            Err(PathError::InvalidArc)
+        );
+
+        // Zero radius
+        assert_eq!(
+            CircularPathSegment::from_arc(&center, &center, &center, (0, 1), direction),
+            Err(PathError::InvalidArc)
+        );
+
+        // Invalid planes
+        for plane in [(0, 0), (0, 3)].iter() {
+            assert_eq!(
+                CircularPathSegment::from_arc(&start, &start, &center, *plane, direction),
+                Err(PathError::InvalidArc)
+            );
+        }
+    }
+
+    #[test]
+    fn it_creates_clockwise_arcs() {
+        let arc = CircularPathSegment::from_arc(
+            &TestCoord3::new(0.0, 0.0, 1.0),
+            &TestCoord3::new(0.0, 1.0, 0.0),
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            (1, 2),
+            ArcDirection::Clockwise,
+        )
+        .unwrap();
+
+        // Clockwise from Z to Y in the YZ plane is the short way around
+        assert_near!(arc.len(), f64::consts::PI / 2.0);
+        assert_near!(
+            arc.position(arc.len() / 2.0),
+            TestCoord3::new(0.0, f64::consts::FRAC_1_SQRT_2, f64::consts::FRAC_1_SQRT_2)
+        );
+    }
+
     #[test]
     fn it_gets_the_position() {
         let before = TestCoord3::new(0.0, 0.0, 0.0);
@@ -0,0 +1,525 @@
+use super::{PathItem, PathTransform};
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::f64;
+
+/// Clothoid path segment
+///
+/// Used to blend two straight path segments with a symmetric pair of clothoids (Euler spirals).
+/// Curvature rises linearly from zero at the start of the blend to its maximum at the middle,
+/// then falls back to zero at the end, so the blend is curvature continuous with the linear
+/// segments on either side. `x` and `y` form the plane the blend lies on.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ClothoidPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Point where the blend leaves the previous segment
+    pub start: Coord<N>,
+
+    /// Direction of the previous segment
+    pub x: Coord<N>,
+
+    /// Unit vector perpendicular to `x`, pointing into the corner
+    pub y: Coord<N>,
+
+    /// Total angle turned through by the blend
+    pub angle: f64,
+
+    /// Length of each of the two clothoids
+    pub half_length: f64,
+
+    /// Distance from the start of the blend to the corner
+    pub corner_distance: f64,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> ClothoidPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a blend segment for two line segments comprised of three points
+    ///
+    /// The blend is as large as possible without deviating from `current` by more than
+    /// `max_deviation` or using more than half of either line segment. The blend has a NaN length
+    /// if the path doubles back on itself.
+    pub fn from_waypoints(
+        previous: &Coord<N>,
+        current: &Coord<N>,
+        next: &Coord<N>,
+        max_deviation: f64,
+    ) -> Self {
+        let previous_length = (current - previous).norm();
+        let next_length = (next - current).norm();
+
+        // A blend of zero length is still required to make the path differentiable
+        let empty = Self {
+            start: current.clone(),
+            x: Coord::zeros(),
+            y: Coord::zeros(),
+            angle: 0.0,
+            half_length: 0.0,
+            corner_distance: 0.0,
+            start_offset: 0.0,
+            end_offset: 0.0,
+        };
+
+        if previous_length < TRAJECTORY_EPSILON || next_length < TRAJECTORY_EPSILON {
+            return empty;
+        }
+
+        let previous_normalised = (current - previous) / previous_length;
+        let next_normalised = (next - current) / next_length;
+
+        if (&previous_normalised - &next_normalised).norm() < TRAJECTORY_EPSILON {
+            return Self {
+                x: previous_normalised,
+                ..empty
+            };
+        }
+
+        let angle = previous_normalised.angle(&next_normalised);
+        let half_angle = angle / 2.0;
+
+        let y = match (&next_normalised
+            - &previous_normalised * previous_normalised.dot(&next_normalised))
+        .try_normalize(TRAJECTORY_EPSILON)
+        {
+            Some(y) => y,
+            None => {
+                return Self {
+                    half_length: f64::NAN,
+                    ..empty
+                }
+            }
+        };
+
+        // End of a single clothoid of unit length, which lies on the bisector of the corner
+        let (unit_x, unit_y) = unit_clothoid(angle, 1.0);
+
+        // Distance from the blend start to the corner, and from the corner to the middle of the
+        // blend, for unit length clothoids
+        let unit_corner_distance = unit_x + unit_y * half_angle.tan();
+        let unit_deviation = unit_y / half_angle.cos();
+
+        let half_length = (max_deviation / unit_deviation)
+            .min(previous_length / 2.0 / unit_corner_distance)
+            .min(next_length / 2.0 / unit_corner_distance);
+
+        let corner_distance = half_length * unit_corner_distance;
+
+        trace!(
+            "RS clothoid_seg (half_len;angle;corner_dist),{},{},{}",
+            half_length,
+            angle,
+            corner_distance
+        );
+
+        Self {
+            start: current - &previous_normalised * corner_distance,
+            x: previous_normalised,
+            y,
+            angle,
+            half_length,
+            corner_distance,
+            start_offset: 0.0,
+            end_offset: 2.0 * half_length,
+        }
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + 2.0 * self.half_length,
+            ..self
+        }
+    }
+
+    /// Get this blend traversed from end to start, with the same start offset
+    ///
+    /// The blend is symmetric about the bisector of its corner, so only its frame changes.
+    pub fn reversed(&self) -> Self {
+        let (angle_s, angle_c) = self.angle.sin_cos();
+
+        Self {
+            start: self.position(self.end_offset),
+            x: -(&self.x * angle_c + &self.y * angle_s),
+            y: &self.y * angle_c - &self.x * angle_s,
+            ..self.clone()
+        }
+    }
+
+    /// Divide each coordinate of this blend by its axis weight, keeping the length measured with
+    /// the weights
+    pub(crate) fn unweighted(&self, weights: &Coord<N>) -> Self {
+        Self {
+            start: self.start.component_div(weights),
+            x: self.x.component_div(weights),
+            y: self.y.component_div(weights),
+            ..self.clone()
+        }
+    }
+
+    /// Get this blend moved by a transform, with the same start offset
+    ///
+    /// Returns `None` if the transform changes the shape of the blend.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Option<Self> {
+        let scale = transform.similarity_scale()?;
+
+        Some(
+            Self {
+                start: transform.transform_point(&self.start),
+                x: transform.transform_vector(&self.x) / scale,
+                y: transform.transform_vector(&self.y) / scale,
+                half_length: self.half_length * scale,
+                corner_distance: self.corner_distance * scale,
+                ..self.clone()
+            }
+            .with_start_offset(self.start_offset),
+        )
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points for this blend
+    ///
+    /// These are the points where a component of the tangent crosses zero, and the middle of the
+    /// blend where curvature is greatest.
+    pub fn switching_points(&self) -> Vec<f64> {
+        if self.half_length <= 0.0 || !self.half_length.is_finite() {
+            return Vec::new();
+        }
+
+        let mut switching_points = self
+            .x
+            .iter()
+            .zip(self.y.iter())
+            .filter(|(x, y)| x.abs() > TRAJECTORY_EPSILON || y.abs() > TRAJECTORY_EPSILON)
+            .filter_map(|(x, y)| {
+                // Heading along the blend where this component of the tangent is zero
+                let mut heading = (-x).atan2(*y);
+
+                if heading < 0.0 {
+                    heading += f64::consts::PI;
+                }
+
+                if heading > 0.0 && heading < self.angle {
+                    Some(self.distance_at_heading(heading))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<f64>>();
+
+        switching_points.push(self.half_length);
+
+        switching_points
+            .sort_unstable_by(|a, b| a.partial_cmp(b).expect("Could not sort switching points"));
+
+        switching_points
+    }
+
+    /// Get the distance from the start of the blend where the tangent has turned through `heading`
+    fn distance_at_heading(&self, heading: f64) -> f64 {
+        if heading <= self.angle / 2.0 {
+            self.half_length * (2.0 * heading / self.angle).sqrt()
+        } else {
+            self.half_length * (2.0 - (2.0 * (self.angle - heading) / self.angle).sqrt())
+        }
+    }
+
+    /// Get position, tangent and curvature in the plane of the blend
+    ///
+    /// The second clothoid is the first one reflected about the bisector of the corner and
+    /// traversed backwards.
+    fn local(&self, distance_along_blend: f64) -> ((f64, f64), (f64, f64), (f64, f64)) {
+        let length = self.half_length;
+
+        if length <= 0.0 {
+            return ((0.0, 0.0), (1.0, 0.0), (0.0, 0.0));
+        }
+
+        let distance = (distance_along_blend - self.start_offset)
+            .max(0.0)
+            .min(2.0 * length);
+
+        let first_half = |distance: f64| {
+            let ratio = distance / length;
+            let (x, y) = unit_clothoid(self.angle, ratio);
+            let heading = self.angle * ratio.powi(2) / 2.0;
+            let curvature = self.angle * ratio / length;
+
+            (
+                (x * length, y * length),
+                (heading.cos(), heading.sin()),
+                (-curvature * heading.sin(), curvature * heading.cos()),
+            )
+        };
+
+        if distance <= length {
+            first_half(distance)
+        } else {
+            let ((x, y), (tx, ty), (cx, cy)) = first_half(2.0 * length - distance);
+
+            // Unit vector along the bisector, and a reflection about it
+            let (bx, by) = (-(self.angle / 2.0).sin(), (self.angle / 2.0).cos());
+            let reflect = |x: f64, y: f64| {
+                let dot = 2.0 * (bx * x + by * y);
+
+                (dot * bx - x, dot * by - y)
+            };
+
+            let (rx, ry) = reflect(x - self.corner_distance, y);
+            let (tx, ty) = reflect(tx, ty);
+
+            ((self.corner_distance + rx, ry), (-tx, -ty), reflect(cx, cy))
+        }
+    }
+
+    /// Convert a vector in the plane of the blend into a path coordinate
+    fn to_coord(&self, (x, y): (f64, f64)) -> Coord<N> {
+        &self.x * x + &self.y * y
+    }
+}
+
+impl<N> PathItem<N> for ClothoidPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the length of both clothoids
+    fn len(&self) -> f64 {
+        2.0 * self.half_length
+    }
+
+    /// Get position along blend
+    fn position(&self, distance_along_blend: f64) -> Coord<N> {
+        let (position, _, _) = self.local(distance_along_blend);
+
+        &self.start + self.to_coord(position)
+    }
+
+    /// Get tangent of point along blend
+    fn tangent(&self, distance_along_blend: f64) -> Coord<N> {
+        let (_, tangent, _) = self.local(distance_along_blend);
+
+        self.to_coord(tangent)
+    }
+
+    /// Get curvature of point along blend
+    fn curvature(&self, distance_along_blend: f64) -> Coord<N> {
+        let (_, _, curvature) = self.local(distance_along_blend);
+
+        self.to_coord(curvature)
+    }
+
+    fn tangent_and_curvature(&self, distance_along_blend: f64) -> (Coord<N>, Coord<N>) {
+        let (_, tangent, curvature) = self.local(distance_along_blend);
+
+        (self.to_coord(tangent), self.to_coord(curvature))
+    }
+}
+
+/// Get the end point of a clothoid of unit length that turns through `angle`, evaluated a
+/// fraction `ratio` of the way along it
+///
+/// This integrates `(cos(angle * t^2 / 2), sin(angle * t^2 / 2))` from `0` to `ratio` using the
+/// power series of the complex exponential, which converges quickly for angles up to a half turn.
+fn unit_clothoid(angle: f64, ratio: f64) -> (f64, f64) {
+    let heading = angle * ratio.powi(2) / 2.0;
+
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut term = ratio;
+
+    for n in 0..40 {
+        let value = term / (2 * n + 1) as f64;
+
+        match n % 4 {
+            0 => x += value,
+            1 => y += value,
+            2 => x -= value,
+            _ => y -= value,
+        }
+
+        term *= heading / (n + 1) as f64;
+
+        if term.abs() < f64::EPSILON * f64::EPSILON {
+            break;
+        }
+    }
+
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn unit_clothoid_matches_integral() {
+        let angle = 2.0;
+        let steps = 10_000;
+
+        let (x, y) = (0..steps).fold((0.0, 0.0), |(x, y), i| {
+            let t = (i as f64 + 0.5) / steps as f64;
+            let heading = angle * t * t / 2.0;
+
+            (
+                x + heading.cos() / steps as f64,
+                y + heading.sin() / steps as f64,
+            )
+        });
+
+        let (series_x, series_y) = unit_clothoid(angle, 1.0);
+
+        assert!((series_x - x).abs() < 1e-8);
+        assert!((series_y - y).abs() < 1e-8);
+    }
+
+    #[test]
+    fn meets_adjacent_segments() {
+        let blend = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 5.0, 0.0),
+            &TestCoord3::new(5.0, 10.0, 0.0),
+            0.1,
+        );
+        let previous = TestCoord3::new(0.0, 1.0, 0.0);
+        let next = TestCoord3::new(1.0, 1.0, 0.0).normalize();
+        let corner = TestCoord3::new(0.0, 5.0, 0.0);
+
+        assert_near!(
+            blend.position(0.0),
+            corner - previous * blend.corner_distance
+        );
+        assert_near!(
+            blend.position(blend.len()),
+            corner + next * blend.corner_distance
+        );
+        assert_near!(blend.tangent(0.0), previous);
+        assert_near!(blend.tangent(blend.len()), next);
+        assert_near!(blend.curvature(0.0), TestCoord3::zeros());
+        assert_near!(blend.curvature(blend.len()), TestCoord3::zeros());
+
+        // Closest point to the corner is the middle of the blend
+        let deviation = (blend.position(blend.half_length) - corner).norm();
+
+        assert_near!(deviation, 0.1);
+    }
+
+    #[test]
+    fn curvature_is_continuous() {
+        let blend = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 5.0, 0.0),
+            &TestCoord3::new(5.0, 10.0, 0.0),
+            0.1,
+        );
+        let h = 1e-6;
+
+        for i in 1..100 {
+            let distance = blend.len() * i as f64 / 100.0;
+
+            let position_derivative =
+                (blend.position(distance + h) - blend.position(distance - h)) / (2.0 * h);
+            let tangent_derivative =
+                (blend.tangent(distance + h) - blend.tangent(distance - h)) / (2.0 * h);
+
+            assert!((position_derivative - blend.tangent(distance)).norm() < 1e-6);
+            assert!((tangent_derivative - blend.curvature(distance)).norm() < 1e-4);
+        }
+
+        assert!(
+            (blend.curvature(blend.half_length - h) - blend.curvature(blend.half_length + h))
+                .norm()
+                < 1e-3
+        );
+    }
+
+    #[test]
+    fn limited_by_segment_length() {
+        let blend = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(1.0, 0.0, 0.0),
+            &TestCoord3::new(1.0, 1.0, 0.0),
+            10.0,
+        );
+
+        assert_near!(blend.corner_distance, 0.5);
+        assert_near!(blend.position(0.0), TestCoord3::new(0.5, 0.0, 0.0));
+        assert_near!(blend.position(blend.len()), TestCoord3::new(1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn switching_points() {
+        let blend = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(1.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 1.0, 0.0),
+            0.1,
+        );
+
+        let points = blend.switching_points();
+
+        // The middle of the blend after turning through 67.5 degrees, then the X tangent crosses
+        // zero after turning through 90 degrees
+        assert_eq!(points.len(), 2);
+        assert_near!(points[0], blend.half_length);
+        assert_near!(blend.tangent(points[1])[0], 0.0);
+    }
+
+    #[test]
+    fn straight_and_degenerate_blends() {
+        let straight = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(1.0, 0.0, 0.0),
+            &TestCoord3::new(2.0, 0.0, 0.0),
+            0.1,
+        );
+
+        assert_eq!(straight.len(), 0.0);
+        assert_near!(straight.position(0.0), TestCoord3::new(1.0, 0.0, 0.0));
+        assert_eq!(straight.switching_points(), Vec::<f64>::new());
+
+        let reversal = ClothoidPathSegment::from_waypoints(
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            &TestCoord3::new(1.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 0.0, 0.0),
+            0.1,
+        );
+
+        assert!(!reversal.len().is_finite());
+    }
+}
@@ -0,0 +1,249 @@
+use super::{PathItem, Segment};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::sync::Arc;
+
+/// User defined path segment
+///
+/// Wraps a `Segment` implementation and gives it a position along a path. The wrapped segment is
+/// shared between clones, and two custom segments are only equal if they wrap the same instance.
+#[derive(Clone, Debug)]
+pub struct CustomPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// User segment geometry
+    pub segment: Arc<dyn Segment<N>>,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> CustomPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a new custom segment from user geometry
+    pub fn new<S>(segment: S) -> Self
+    where
+        S: Segment<N> + 'static,
+    {
+        Self::from_boxed(Box::new(segment))
+    }
+
+    /// Create a new custom segment from boxed user geometry
+    pub fn from_boxed(segment: Box<dyn Segment<N>>) -> Self {
+        let segment: Arc<dyn Segment<N>> = Arc::from(segment);
+        let end_offset = segment.len();
+
+        Self {
+            segment,
+            start_offset: 0.0,
+            end_offset,
+        }
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.segment.len(),
+            ..self
+        }
+    }
+
+    /// Get this segment traversed from end to start, with the same start offset
+    ///
+    /// The user geometry is wrapped, so the reversed segment is not equal to any other segment.
+    pub fn reversed(&self) -> Self {
+        Self::new(ReversedSegment {
+            segment: self.segment.clone(),
+        })
+        .with_start_offset(self.start_offset)
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points for this segment, relative to its start
+    pub fn switching_points(&self) -> Vec<f64> {
+        self.segment.switching_points()
+    }
+}
+
+/// User geometry traversed from end to start
+#[derive(Debug)]
+struct ReversedSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+{
+    segment: Arc<dyn Segment<N>>,
+}
+
+impl<N> Segment<N> for ReversedSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+{
+    fn len(&self) -> f64 {
+        self.segment.len()
+    }
+
+    fn position(&self, distance: f64) -> Coord<N> {
+        self.segment.position(self.len() - distance)
+    }
+
+    fn tangent(&self, distance: f64) -> Coord<N> {
+        -self.segment.tangent(self.len() - distance)
+    }
+
+    fn curvature(&self, distance: f64) -> Coord<N> {
+        self.segment.curvature(self.len() - distance)
+    }
+
+    fn tangent_and_curvature(&self, distance: f64) -> (Coord<N>, Coord<N>) {
+        let (tangent, curvature) = self.segment.tangent_and_curvature(self.len() - distance);
+
+        (-tangent, curvature)
+    }
+
+    fn switching_points(&self) -> Vec<f64> {
+        let mut switching_points = self
+            .segment
+            .switching_points()
+            .into_iter()
+            .map(|point| self.len() - point)
+            .collect::<Vec<_>>();
+
+        switching_points.reverse();
+
+        switching_points
+    }
+}
+
+impl<N> PartialEq for CustomPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // Compare data pointers only, as the same type can have more than one vtable
+        Arc::as_ptr(&self.segment) as *const u8 == Arc::as_ptr(&other.segment) as *const u8
+            && self.start_offset == other.start_offset
+    }
+}
+
+impl<N> PathItem<N> for CustomPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get length of segment
+    fn len(&self) -> f64 {
+        self.segment.len()
+    }
+
+    /// Get position at a point along the segment
+    fn position(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment
+            .position(distance_along_segment - self.start_offset)
+    }
+
+    /// Get first derivative (tangent) at a point
+    fn tangent(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment
+            .tangent(distance_along_segment - self.start_offset)
+    }
+
+    /// Get second derivative (curvature) at a point
+    fn curvature(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment
+            .curvature(distance_along_segment - self.start_offset)
+    }
+
+    fn tangent_and_curvature(&self, distance_along_segment: f64) -> (Coord<N>, Coord<N>) {
+        self.segment
+            .tangent_and_curvature(distance_along_segment - self.start_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{LinearPathSegment, Path, PathSegment, Trajectory};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn custom_segment_offsets() {
+        let segment = CustomPathSegment::new(QuarterCircle).with_start_offset(1.0);
+
+        assert_near!(segment.end_offset(), 1.0 + PI / 2.0);
+        assert_near!(segment.position(1.0), TestCoord3::new(1.0, 0.0, 0.0));
+        assert_near!(
+            segment.tangent(1.0 + PI / 2.0),
+            TestCoord3::new(-1.0, 0.0, 0.0)
+        );
+        assert_near!(
+            segment.tangent_and_curvature(1.0 + PI / 2.0).1,
+            TestCoord3::new(0.0, -1.0, 0.0)
+        );
+
+        assert_eq!(segment.clone(), segment);
+        assert!(CustomPathSegment::new(QuarterCircle) != CustomPathSegment::new(QuarterCircle));
+    }
+
+    #[test]
+    fn custom_segment_path() {
+        let path = Path::from_segments(vec![
+            PathSegment::Linear(LinearPathSegment::from_waypoints(
+                TestCoord3::new(1.0, -1.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 0.0),
+            )),
+            PathSegment::custom(QuarterCircle),
+        ]);
+
+        assert_near!(path.len(), 1.0 + PI / 2.0);
+        assert_near!(
+            path.position(1.0 + PI / 4.0),
+            TestCoord3::new((PI / 4.0).cos(), (PI / 4.0).sin(), 0.0)
+        );
+
+        // The quarter circle has no switching points between its ends
+        assert!(path
+            .switching_points()
+            .iter()
+            .all(|point| point.position <= 1.0 || point.position >= 1.0 + PI / 2.0));
+
+        let options = trajectory_options_fixture();
+
+        let trajectory = Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            TestCoord3::new(0.0, 1.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,514 @@
+use super::{ArcDirection, CircularPathSegment, PathError, PathItem, PathTransform};
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::f64;
+
+/// Helical path segment
+///
+/// Turns around an axis in the plane formed by `x` and `y` while moving along `axis` at a constant
+/// rate. `center` is the point on the axis level with the start of the helix.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct HelixPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Point on the axis level with the start of the helix
+    pub center: Coord<N>,
+
+    /// Unit vector from `center` to the start of the helix
+    pub x: Coord<N>,
+
+    /// Unit vector in the direction of travel at the start of the helix, ignoring movement along
+    /// the axis
+    pub y: Coord<N>,
+
+    /// Unit vector along the axis, or zero if `pitch` is zero
+    pub axis: Coord<N>,
+
+    /// Distance from the axis
+    pub radius: f64,
+
+    /// Distance moved along `axis` in each turn
+    pub pitch: f64,
+
+    /// Number of turns, which may be fractional
+    pub turns: f64,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> HelixPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a helix starting at `start` that turns around the axis through `center`
+    ///
+    /// The helix turns in the plane formed by axes `plane.0` and `plane.1` in `direction`, and
+    /// moves `pitch` along `axis` with each turn. `axis` must be perpendicular to the plane, and
+    /// `start` must be level with `center` along it. Returns an error if the helix is invalid.
+    pub fn new(
+        start: &Coord<N>,
+        center: &Coord<N>,
+        axis: &Coord<N>,
+        plane: (usize, usize),
+        direction: ArcDirection,
+        pitch: f64,
+        turns: f64,
+    ) -> Result<Self, PathError> {
+        let (a, b) = plane;
+
+        if a == b || a >= N::dim() || b >= N::dim() {
+            return Err(PathError::InvalidArc);
+        }
+
+        if [start, center, axis]
+            .iter()
+            .any(|point| point.iter().any(|c| !c.is_finite()))
+            || !pitch.is_finite()
+            || !turns.is_finite()
+            || turns <= 0.0
+        {
+            return Err(PathError::InvalidArc);
+        }
+
+        let out_of_plane = |coord: &Coord<N>| {
+            (0..N::dim()).any(|i| i != a && i != b && coord[i].abs() > TRAJECTORY_EPSILON)
+        };
+
+        let axis = if pitch == 0.0 {
+            Coord::zeros()
+        } else {
+            match axis.try_normalize(TRAJECTORY_EPSILON) {
+                Some(axis)
+                    if axis[a].abs() < TRAJECTORY_EPSILON && axis[b].abs() < TRAJECTORY_EPSILON =>
+                {
+                    axis
+                }
+                _ => return Err(PathError::InvalidArc),
+            }
+        };
+
+        // Move the center along the axis to be level with the start
+        let center = center + &axis * axis.dot(&(start - center));
+        let radial = start - &center;
+
+        if out_of_plane(&radial) {
+            return Err(PathError::InvalidArc);
+        }
+
+        // A full circle gives the plane vectors
+        let circle = CircularPathSegment::from_arc(start, start, &center, plane, direction)?;
+
+        Ok(Self::from_parts(circle, axis, pitch, turns))
+    }
+
+    /// Create a helix from `start` to `end` around `center`, turning in the plane formed by axes
+    /// `plane.0` and `plane.1`
+    ///
+    /// This is an arc like `CircularPathSegment::from_arc` that also moves linearly in every
+    /// axis outside the plane, as produced by `G2` or `G3` with movement outside the arc plane.
+    /// A full turn is made if `start` and `end` are at the same position in the plane. The
+    /// position of `center` outside the plane is ignored.
+    pub fn from_arc(
+        start: &Coord<N>,
+        end: &Coord<N>,
+        center: &Coord<N>,
+        plane: (usize, usize),
+        direction: ArcDirection,
+    ) -> Result<Self, PathError> {
+        let (a, b) = plane;
+
+        if a == b || a >= N::dim() || b >= N::dim() {
+            return Err(PathError::InvalidArc);
+        }
+
+        // Project the end and center onto the plane of the start
+        let flatten = |point: &Coord<N>| {
+            let mut flat = start.clone();
+
+            flat[a] = point[a];
+            flat[b] = point[b];
+
+            flat
+        };
+
+        let flat_end = flatten(end);
+        let circle =
+            CircularPathSegment::from_arc(start, &flat_end, &flatten(center), plane, direction)?;
+
+        let angle = circle.arc_length / circle.radius;
+        let lead = end - &flat_end;
+        let rise = lead.norm();
+        let turns = angle / (2.0 * f64::consts::PI);
+
+        let (axis, pitch) = if rise < TRAJECTORY_EPSILON {
+            (Coord::zeros(), 0.0)
+        } else {
+            (lead / rise, rise / turns)
+        };
+
+        Ok(Self::from_parts(circle, axis, pitch, turns))
+    }
+
+    fn from_parts(circle: CircularPathSegment<N>, axis: Coord<N>, pitch: f64, turns: f64) -> Self {
+        let mut helix = Self {
+            center: circle.center,
+            x: circle.x,
+            y: circle.y,
+            axis,
+            radius: circle.radius,
+            pitch,
+            turns,
+            start_offset: 0.0,
+            end_offset: 0.0,
+        };
+
+        helix.end_offset = helix.len();
+
+        helix
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.len(),
+            ..self
+        }
+    }
+
+    /// Divide each coordinate of this helix by its axis weight, keeping the length measured with
+    /// the weights
+    pub(crate) fn unweighted(&self, weights: &Coord<N>) -> Self {
+        Self {
+            center: self.center.component_div(weights),
+            x: self.x.component_div(weights),
+            y: self.y.component_div(weights),
+            axis: self.axis.component_div(weights),
+            ..self.clone()
+        }
+    }
+
+    /// Get the part of this helix between two distances along the path
+    ///
+    /// The trimmed helix starts at `from`.
+    pub fn trim(&self, from: f64, to: f64) -> Self {
+        let angle = self.angle(from);
+        let (angle_s, angle_c) = angle.sin_cos();
+
+        Self {
+            center: &self.center + &self.axis * (self.lead() * angle),
+            x: &self.x * angle_c + &self.y * angle_s,
+            y: &self.y * angle_c - &self.x * angle_s,
+            turns: (to - from) / self.length_per_radian() / (2.0 * f64::consts::PI),
+            start_offset: from,
+            end_offset: to,
+            ..self.clone()
+        }
+    }
+
+    /// Get this helix traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        let angle = self.total_angle();
+        let (angle_s, angle_c) = angle.sin_cos();
+
+        Self {
+            center: &self.center + &self.axis * (self.lead() * angle),
+            x: &self.x * angle_c + &self.y * angle_s,
+            y: &self.x * angle_s - &self.y * angle_c,
+            axis: -&self.axis,
+            ..self.clone()
+        }
+    }
+
+    /// Get this helix moved by a transform, with the same start offset
+    ///
+    /// Returns `None` if the transform does not keep the helix circular.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Option<Self> {
+        let scale = transform.similarity_scale()?;
+
+        Some(
+            Self {
+                center: transform.transform_point(&self.center),
+                x: transform.transform_vector(&self.x) / scale,
+                y: transform.transform_vector(&self.y) / scale,
+                axis: transform.transform_vector(&self.axis) / scale,
+                radius: self.radius * scale,
+                pitch: self.pitch * scale,
+                ..self.clone()
+            }
+            .with_start_offset(self.start_offset),
+        )
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points for this helix
+    ///
+    /// Like a circle, the tangent of each axis in the plane of the helix crosses zero every half
+    /// turn. The tangent along the axis is constant.
+    pub fn switching_points(&self) -> Vec<f64> {
+        let total_angle = self.total_angle();
+        let scale = self.length_per_radian();
+
+        let mut switching_points = self
+            .x
+            .iter()
+            .zip(self.y.iter())
+            .filter(|(x, y)| x.abs() > TRAJECTORY_EPSILON || y.abs() > TRAJECTORY_EPSILON)
+            .flat_map(|(x, y)| {
+                let mut switching_angle = y.atan2(*x);
+
+                if switching_angle < 0.0 {
+                    switching_angle += f64::consts::PI;
+                }
+
+                (0..)
+                    .map(move |half_turn| switching_angle + half_turn as f64 * f64::consts::PI)
+                    .take_while(move |angle| *angle < total_angle)
+                    .map(move |angle| angle * scale)
+            })
+            .collect::<Vec<f64>>();
+
+        switching_points
+            .sort_unstable_by(|a, b| a.partial_cmp(b).expect("Could not sort switching points"));
+
+        switching_points
+    }
+
+    /// Get the total angle turned through, in radians
+    fn total_angle(&self) -> f64 {
+        self.turns * 2.0 * f64::consts::PI
+    }
+
+    /// Distance moved along the axis for each radian turned
+    fn lead(&self) -> f64 {
+        self.pitch / (2.0 * f64::consts::PI)
+    }
+
+    /// Distance along the helix for each radian turned
+    fn length_per_radian(&self) -> f64 {
+        self.radius.hypot(self.lead())
+    }
+
+    /// Get the angle turned through at a distance along the path
+    fn angle(&self, distance_along_helix: f64) -> f64 {
+        (distance_along_helix - self.start_offset) / self.length_per_radian()
+    }
+}
+
+impl<N> PathItem<N> for HelixPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the length of this helix
+    fn len(&self) -> f64 {
+        self.total_angle() * self.length_per_radian()
+    }
+
+    /// Get position along helix
+    fn position(&self, distance_along_helix: f64) -> Coord<N> {
+        let angle = self.angle(distance_along_helix);
+
+        &self.center
+            + (&self.x * angle.cos() + &self.y * angle.sin()) * self.radius
+            + &self.axis * (self.lead() * angle)
+    }
+
+    /// Get tangent of point along helix
+    fn tangent(&self, distance_along_helix: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_helix).0
+    }
+
+    /// Get curvature of point along helix, which always points towards the axis
+    fn curvature(&self, distance_along_helix: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_helix).1
+    }
+
+    fn tangent_and_curvature(&self, distance_along_helix: f64) -> (Coord<N>, Coord<N>) {
+        let angle = self.angle(distance_along_helix);
+        let (angle_s, angle_c) = angle.sin_cos();
+        let scale = self.length_per_radian();
+
+        (
+            ((&self.y * angle_c - &self.x * angle_s) * self.radius + &self.axis * self.lead())
+                / scale,
+            (&self.x * angle_c + &self.y * angle_s) * (-self.radius / scale.powi(2)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn thread_geometry() {
+        let helix = HelixPathSegment::new(
+            &TestCoord3::new(5.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 0.0, 3.0),
+            &TestCoord3::new(0.0, 0.0, -1.0),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+            1.5,
+            2.5,
+        )
+        .unwrap();
+
+        assert_near!(helix.len(), 2.5 * (10.0 * PI).hypot(1.5));
+        assert_near!(helix.position(0.0), TestCoord3::new(5.0, 0.0, 0.0));
+        assert_near!(
+            helix.position(helix.len() / 10.0),
+            TestCoord3::new(0.0, 5.0, -0.375)
+        );
+        assert_near!(
+            helix.position(helix.len()),
+            TestCoord3::new(-5.0, 0.0, -3.75)
+        );
+    }
+
+    #[test]
+    fn analytic_derivatives() {
+        let helix = HelixPathSegment::new(
+            &TestCoord3::new(5.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 0.0, 3.0),
+            &TestCoord3::new(0.0, 0.0, -1.0),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+            1.5,
+            2.5,
+        )
+        .unwrap()
+        .with_start_offset(1.0);
+        let h = 1e-6;
+
+        for i in 0..=50 {
+            let distance = 1.0 + helix.len() * i as f64 / 50.0;
+            let (tangent, curvature) = helix.tangent_and_curvature(distance);
+
+            assert_near!(tangent.norm(), 1.0);
+
+            let position_derivative =
+                (helix.position(distance + h) - helix.position(distance - h)) / (2.0 * h);
+            let tangent_derivative =
+                (helix.tangent(distance + h) - helix.tangent(distance - h)) / (2.0 * h);
+
+            assert!((position_derivative - tangent).norm() < 1e-6);
+            assert!((tangent_derivative - curvature).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn switching_points() {
+        let helix = HelixPathSegment::new(
+            &TestCoord3::new(5.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 0.0, 3.0),
+            &TestCoord3::new(0.0, 0.0, -1.0),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+            1.5,
+            2.5,
+        )
+        .unwrap();
+        let quarter = helix.len() / 10.0;
+
+        // X and Y tangents cross zero every quarter turn
+        let expected = (0..10).map(|i| i as f64 * quarter).collect::<Vec<f64>>();
+        let points = helix.switching_points();
+
+        assert_eq!(points.len(), expected.len());
+
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            assert_near!(point, expected);
+        }
+    }
+
+    #[test]
+    fn from_arc() {
+        let helix = HelixPathSegment::from_arc(
+            &TestCoord3::new(10.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 10.0, -2.0),
+            &TestCoord3::new(0.0, 0.0, 5.0),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+        )
+        .unwrap();
+
+        assert_near!(helix.turns, 0.25);
+        assert_near!(helix.pitch, 8.0);
+        assert_near!(
+            helix.position(helix.len()),
+            TestCoord3::new(0.0, 10.0, -2.0)
+        );
+        assert_near!(
+            helix.position(helix.len() / 2.0),
+            TestCoord3::new(10.0 * (PI / 4.0).cos(), 10.0 * (PI / 4.0).sin(), -1.0)
+        );
+    }
+
+    #[test]
+    fn invalid_helix() {
+        let start = TestCoord3::new(5.0, 0.0, 0.0);
+        let center = TestCoord3::zeros();
+        let direction = ArcDirection::Clockwise;
+
+        // Axis in the plane of the helix
+        assert_eq!(
+            HelixPathSegment::new(
+                &start,
+                &center,
+                &TestCoord3::new(1.0, 0.0, 0.0),
+                (0, 1),
+                direction,
+                1.0,
+                1.0
+            ),
+            Err(PathError::InvalidArc)
+        );
+
+        // No turns
+        assert_eq!(
+            HelixPathSegment::new(
+                &start,
+                &center,
+                &TestCoord3::new(0.0, 0.0, 1.0),
+                (0, 1),
+                direction,
+                1.0,
+                0.0
+            ),
+            Err(PathError::InvalidArc)
+        );
+    }
+}
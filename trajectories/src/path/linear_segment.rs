@@ -1,15 +1,23 @@
-use super::PathItem;
+use super::{PathItem, PathTransform};
 use crate::Coord;
 use nalgebra::allocator::Allocator;
 use nalgebra::allocator::SameShapeVectorAllocator;
+use nalgebra::storage::Owned;
 use nalgebra::DefaultAllocator;
 use nalgebra::DimName;
 
-/// Circular path segment
+/// Linear path segment
 ///
-/// Used to blend two straight path segments along a circular path. `x` and `y` form a plane on
-/// on which the blend circle lies, with its center at `center`. Radius is radius.
+/// A straight line from `start` to `end`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
 pub struct LinearPathSegment<N>
 where
     N: DimName + Copy,
@@ -41,6 +49,7 @@ where
     DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
 {
+    /// Create a straight line from `start` to `end`
     pub fn from_waypoints(start: Coord<N>, end: Coord<N>) -> Self {
         let length = (&end - &start).norm();
         let tangent = (&end - &start).normalize();
@@ -64,11 +73,84 @@ where
         }
     }
 
+    /// Get the part of this line between two distances along the path
+    ///
+    /// The trimmed line starts at `from`.
+    pub fn trim(&self, from: f64, to: f64) -> Self {
+        Self {
+            start: self.position(from),
+            end: self.position(to),
+            length: to - from,
+            start_offset: from,
+            end_offset: to,
+            ..self.clone()
+        }
+    }
+
+    /// Get this line traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        Self {
+            start: self.end.clone(),
+            end: self.start.clone(),
+            tangent: -&self.tangent,
+            ..self.clone()
+        }
+    }
+
+    /// Divide each coordinate of this line by its axis weight, keeping the length measured with
+    /// the weights
+    pub(crate) fn unweighted(&self, weights: &Coord<N>) -> Self {
+        Self {
+            start: self.start.component_div(weights),
+            end: self.end.component_div(weights),
+            tangent: self.tangent.component_div(weights),
+            ..self.clone()
+        }
+    }
+
+    /// Get this line moved by a transform, with the same start offset
+    ///
+    /// If the transform keeps the shape of the line, its length is scaled along with it, so lines
+    /// measured with axis weights keep being measured the same way.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Self
+    where
+        Owned<f64, N>: Copy,
+    {
+        match transform.similarity_scale() {
+            Some(scale) => Self {
+                start: transform.transform_point(&self.start),
+                end: transform.transform_point(&self.end),
+                length: self.length * scale,
+                end_offset: self.start_offset + self.length * scale,
+                tangent: transform.transform_vector(&self.tangent) / scale,
+                ..self.clone()
+            },
+            None => Self::from_waypoints(
+                transform.transform_point(&self.start),
+                transform.transform_point(&self.end),
+            )
+            .with_start_offset(self.start_offset),
+        }
+    }
+
+    /// Get the distance along the path of the point on this line closest to `point`
+    pub fn project(&self, point: &Coord<N>) -> f64 {
+        let direction = &self.end - &self.start;
+        let length_squared = direction.norm_squared();
+
+        if length_squared <= 0.0 {
+            return self.start_offset;
+        }
+
+        let ratio = (point - &self.start).dot(&direction) / length_squared;
+
+        self.start_offset + ratio.max(0.0).min(1.0) * self.length
+    }
+
     /// Get switching points for linear segment
     ///
     /// There are no switching points for a linear segment, so this method will always return an
     /// empty list.
-    // TODO: Trait
     pub fn switching_points(&self) -> Vec<f64> {
         Vec::new()
     }
@@ -127,7 +209,7 @@ mod tests {
             TestCoord3::new(1.0, 0.0, 0.0),
         );
 
-        assert_eq!(line.switching_points(), Vec::new());
+        assert_eq!(line.switching_points(), Vec::<f64>::new());
     }
 
     #[test]
@@ -1,17 +1,56 @@
+mod arc_direction;
+mod arc_length;
+mod blend;
+mod bspline_segment;
 mod circular_segment;
+mod clothoid_segment;
+mod custom_segment;
+mod helix_segment;
 mod linear_segment;
+mod path_builder;
+mod path_edit;
+mod path_error;
 mod path_item;
 mod path_options;
+mod path_periodic;
+mod path_project;
+mod path_reverse;
+#[cfg(feature = "serde")]
+mod path_serde;
+mod path_slice;
 mod path_switching_point;
+mod path_transform;
 mod segment;
-
+mod segment_index;
+mod spline_segment;
+mod transformed_segment;
+mod trimmed_segment;
+mod user_segment;
+
+pub use self::arc_direction::ArcDirection;
+pub(crate) use self::arc_length::{ArcLengthTable, ParametricCurve};
+pub use self::blend::Blend;
+pub use self::bspline_segment::BSplinePathSegment;
 pub use self::circular_segment::CircularPathSegment;
+pub(crate) use self::circular_segment::ARC_RADIUS_TOLERANCE;
+pub use self::clothoid_segment::ClothoidPathSegment;
+pub use self::custom_segment::CustomPathSegment;
+pub use self::helix_segment::HelixPathSegment;
 pub use self::linear_segment::LinearPathSegment;
+pub use self::path_builder::PathBuilder;
+pub use self::path_error::PathError;
 pub use self::path_item::PathItem;
 pub use self::path_options::PathOptions;
 pub use self::path_switching_point::PathSwitchingPoint;
+pub use self::path_transform::PathTransform;
 pub use self::segment::PathSegment;
+use self::segment_index::SegmentIndexCache;
+pub use self::spline_segment::SplinePathSegment;
+pub use self::transformed_segment::TransformedPathSegment;
+pub use self::trimmed_segment::TrimmedPathSegment;
+pub use self::user_segment::Segment;
 use crate::Coord;
+use crate::{MAX_DEVIATION, TRAJECTORY_EPSILON};
 use nalgebra::allocator::Allocator;
 use nalgebra::allocator::SameShapeVectorAllocator;
 use nalgebra::storage::Owned;
@@ -21,6 +60,7 @@ use std::time::Instant;
 
 /// Continuity flag
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Continuity {
     /// The path at a point is discontinuous (`true` in C++ code)
     Discontinuous,
@@ -46,6 +86,16 @@ where
 
     /// Switching points. Bool denotes whether point is discontinuous (`true`) or not (`false`)
     switching_points: Vec<PathSwitchingPoint>,
+
+    /// Waypoints the path was blended from, or empty if it was created from segments
+    waypoints: Vec<Coord<N>>,
+
+    /// Options used to blend the corner at each waypoint
+    options: PathOptions<N>,
+
+    /// Bounding box tree over the segments, built the first time a point is projected onto the
+    /// path
+    index: SegmentIndexCache<N>,
 }
 
 impl<N> Path<N>
@@ -58,144 +108,379 @@ where
     /// Create a blended path from a set of waypoints
     ///
     /// The path must be differentiable, so small blends are added between linear segments
-    pub fn from_waypoints(waypoints: &[Coord<N>], options: PathOptions) -> Self {
-        let PathOptions { max_deviation } = options;
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than two waypoints are given. Use `Path::try_from_waypoints` to validate
+    /// waypoints and get an error instead.
+    pub fn from_waypoints(waypoints: &[Coord<N>], options: PathOptions<N>) -> Self {
+        Self::blend_waypoints(waypoints, options).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        let mut start_offset = 0.0;
-        let mut switching_points = Vec::with_capacity((waypoints.len() as f32 * 2.5) as usize);
+    /// Create a blended path from a set of waypoints, returning an error if the waypoints cannot
+    /// form a valid path
+    ///
+    /// Unlike `Path::from_waypoints`, this method rejects waypoints with NaN or infinite
+    /// coordinates, consecutive duplicate waypoints and corners where the path doubles back on
+    /// itself.
+    pub fn try_from_waypoints(
+        waypoints: &[Coord<N>],
+        options: PathOptions<N>,
+    ) -> Result<Self, PathError> {
+        if waypoints.len() < 2 {
+            return Err(PathError::TooFewWaypoints {
+                count: waypoints.len(),
+            });
+        }
 
-        let start = Instant::now();
+        Self::check_periodic_axes(&options)?;
+        Self::check_waypoints(&Self::unwrap_waypoints(waypoints, &options), 0)?;
 
-        let segments = match waypoints.len() {
-            0 | 1 => panic!("Path must contain at least two waypoints"),
-            2 => vec![PathSegment::Linear(LinearPathSegment::from_waypoints(
-                waypoints[0].clone(),
-                waypoints[1].clone(),
-            ))],
-            _ => waypoints.windows(3).fold(
-                Vec::with_capacity(waypoints.len() * 3),
-                |mut segments, parts| match parts {
-                    [prev, curr, next] => {
-                        let blend_segment =
-                            CircularPathSegment::from_waypoints(&prev, &curr, &next, max_deviation);
-
-                        let blend_start = blend_segment.position(0.0);
-                        let blend_end = blend_segment.position(blend_segment.len());
-
-                        // Update previous segment with new end point, or create a new one if we're
-                        // at the beginning of the path
-                        let prev_segment = segments
-                            .pop()
-                            .map(|segment| match segment {
-                                PathSegment::Linear(s) => {
-                                    LinearPathSegment::from_waypoints(s.start, blend_start.clone())
-                                        .with_start_offset(s.start_offset)
-                                }
-                                _ => panic!("Invalid path: expected last segment to be linear"),
-                            })
-                            .unwrap_or_else(|| {
-                                LinearPathSegment::from_waypoints(prev.clone(), blend_start)
-                                    .with_start_offset(start_offset)
-                            });
-
-                        start_offset += prev_segment.len();
-
-                        // Switching point where linear segment touches blend (discontinuous)
-                        // TODO: Get actual list of switching points when support for non-linear
-                        // path segments (that aren't blends) is added.
-                        switching_points.push(PathSwitchingPoint::new(
-                            start_offset,
-                            Continuity::Discontinuous,
-                        ));
-
-                        let blend_segment = blend_segment.with_start_offset(start_offset);
-                        let blend_switching_points = blend_segment.switching_points();
-                        let blend_end_offset = blend_segment.start_offset + blend_segment.len();
-
-                        // Get switching points over the duration of the blend segment
-                        switching_points.append(
-                            &mut blend_switching_points
-                                .iter()
-                                .filter_map(|p| {
-                                    let p_offset = p + blend_segment.start_offset;
-
-                                    if p_offset < blend_end_offset {
-                                        Some(PathSwitchingPoint::new(
-                                            p_offset,
-                                            Continuity::Continuous,
-                                        ))
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect(),
-                        );
-
-                        // Add blend segment length to path length total
-                        start_offset = blend_end_offset;
-
-                        let next_segment =
-                            LinearPathSegment::from_waypoints(blend_end, next.clone())
-                                .with_start_offset(start_offset);
-
-                        // Switching point where linear segment touches blend
-                        // TODO: Get actual list of switching points when support for non-linear
-                        // path segments (that aren't blends) is added.
-                        switching_points.push(PathSwitchingPoint::new(
-                            start_offset,
-                            Continuity::Discontinuous,
-                        ));
-
-                        // Add both linear segments with blend in between to overall path
-                        segments.append(&mut vec![
-                            PathSegment::Linear(prev_segment),
-                            PathSegment::Circular(blend_segment),
-                            PathSegment::Linear(next_segment),
-                        ]);
-
-                        segments
-                    }
-                    _ => panic!("Linear segments"),
-                },
-            ),
+        Self::blend_waypoints(waypoints, options)
+    }
+
+    /// Check a run of waypoints for NaN or infinite coordinates, consecutive duplicates and
+    /// corners where the path doubles back on itself
+    ///
+    /// `first_index` is the index of the first waypoint in the run, used for errors.
+    fn check_waypoints(waypoints: &[Coord<N>], first_index: usize) -> Result<(), PathError> {
+        if let Some(index) = waypoints
+            .iter()
+            .position(|waypoint| waypoint.iter().any(|c| !c.is_finite()))
+        {
+            return Err(PathError::NonFiniteCoordinate {
+                index: first_index + index,
+            });
+        }
+
+        if let Some(index) = waypoints
+            .windows(2)
+            .position(|parts| (&parts[1] - &parts[0]).norm() < TRAJECTORY_EPSILON)
+        {
+            return Err(PathError::ZeroLengthSegment {
+                index: first_index + index,
+            });
+        }
+
+        // A corner where the next segment points back along the previous one cannot be blended
+        if let Some(index) = waypoints.windows(3).position(|parts| {
+            let previous_normalised = (&parts[1] - &parts[0]).normalize();
+            let next_normalised = (&parts[2] - &parts[1]).normalize();
+
+            (previous_normalised + next_normalised).norm() < TRAJECTORY_EPSILON
+        }) {
+            return Err(PathError::DegenerateBlend {
+                index: first_index + index + 1,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Create linear segments between each waypoint and the circular blends that join them
+    fn blend_waypoints(waypoints: &[Coord<N>], options: PathOptions<N>) -> Result<Self, PathError> {
+        if waypoints.len() < 2 {
+            return Err(PathError::TooFewWaypoints {
+                count: waypoints.len(),
+            });
+        }
+
+        Self::check_axis_weights(&options)?;
+        Self::check_periodic_axes(&options)?;
+
+        let start = Instant::now();
+        let waypoints = &Self::unwrap_waypoints(waypoints, &options);
+
+        let mut path = Self {
+            segments: Vec::with_capacity(waypoints.len() * 3),
+            length: 0.0,
+            switching_points: Vec::with_capacity((waypoints.len() as f32 * 2.5) as usize),
+            waypoints: Vec::new(),
+            options,
+            index: SegmentIndexCache::default(),
         };
 
-        for p in switching_points.iter() {
+        let first_segment = path.line(waypoints[0].clone(), waypoints[1].clone());
+
+        path.length = first_segment.end_offset();
+        path.segments.push(PathSegment::Linear(first_segment));
+
+        for (index, parts) in waypoints.windows(3).enumerate() {
+            path.push_corner(&parts[0], &parts[1], &parts[2], index + 1)?;
+        }
+
+        path.waypoints = waypoints.to_vec();
+
+        for p in path.switching_points.iter() {
             // trace!("RS switching_point (pos;1.0),{},1.0", p.position);
             instrument!("switching_point", (p.position, 1.0));
         }
 
-        let length = start_offset
-            + segments
-                .last()
-                .map(|l| l.len())
-                .expect("Cannot get length of empty path");
-
         info!(
             "Created path {} long with {} segments and {} switching points in {} ms",
-            length,
-            segments.len(),
-            switching_points.len(),
+            path.length,
+            path.segments.len(),
+            path.switching_points.len(),
             start.elapsed().as_millis()
         );
 
+        Ok(path)
+    }
+
+    /// Check each axis weight is positive and finite, if weights are given
+    fn check_axis_weights(options: &PathOptions<N>) -> Result<(), PathError> {
+        let invalid = options.axis_weights.and_then(|weights| {
+            weights
+                .iter()
+                .position(|weight| !weight.is_finite() || *weight <= 0.0)
+        });
+
+        match invalid {
+            Some(axis) => Err(PathError::InvalidAxisWeights { axis }),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the weight of each axis from the path options
+    fn axis_weights(&self) -> Option<Coord<N>> {
+        self.options.axis_weights
+    }
+
+    /// Create a line between two points, with its length measured using the path's axis weights
+    pub(crate) fn line(&self, start: Coord<N>, end: Coord<N>) -> LinearPathSegment<N> {
+        match self.axis_weights() {
+            Some(weights) => LinearPathSegment::from_waypoints(
+                start.component_mul(&weights),
+                end.component_mul(&weights),
+            )
+            .unweighted(&weights),
+            None => LinearPathSegment::from_waypoints(start, end),
+        }
+    }
+
+    /// Blend the corner at `curr` and add a linear segment from the blend to `next`
+    ///
+    /// `curr` must be the end of the path, which must end in a linear segment from `prev`. Only
+    /// that linear segment is rebuilt; earlier segments and switching points are left untouched.
+    /// The blend is created using the path's options. `index` is the index of `curr` in the
+    /// original waypoints, used for errors. The path is left unchanged if an error is returned.
+    pub(crate) fn push_corner(
+        &mut self,
+        prev: &Coord<N>,
+        curr: &Coord<N>,
+        next: &Coord<N>,
+        index: usize,
+    ) -> Result<(), PathError> {
+        let PathOptions {
+            max_deviation,
+            blend,
+            ..
+        } = self.options;
+
+        // With axis weights, blend in the weighted space the path length is measured in
+        let weights = self.axis_weights();
+
+        let (prev_weighted, curr_weighted, next_weighted) = match &weights {
+            Some(weights) => (
+                prev.component_mul(weights),
+                curr.component_mul(weights),
+                next.component_mul(weights),
+            ),
+            None => (prev.clone(), curr.clone(), next.clone()),
+        };
+
+        // Clothoid blends have zero curvature at each end, so they join the linear segments on
+        // either side smoothly
+        let blend_continuity = match blend {
+            Blend::Circular => Continuity::Discontinuous,
+            Blend::Clothoid => Continuity::Continuous,
+        };
+
+        let blend_segment = match blend {
+            Blend::Circular => PathSegment::Circular(CircularPathSegment::from_waypoints(
+                &prev_weighted,
+                &curr_weighted,
+                &next_weighted,
+                max_deviation,
+            )),
+            Blend::Clothoid => PathSegment::Clothoid(ClothoidPathSegment::from_waypoints(
+                &prev_weighted,
+                &curr_weighted,
+                &next_weighted,
+                max_deviation,
+            )),
+        };
+
+        let blend_segment = match (&weights, blend_segment) {
+            (Some(weights), PathSegment::Circular(s)) => {
+                PathSegment::Circular(s.unweighted(weights))
+            }
+            (Some(weights), PathSegment::Clothoid(s)) => {
+                PathSegment::Clothoid(s.unweighted(weights))
+            }
+            (_, s) => s,
+        };
+
+        if !blend_segment.len().is_finite() {
+            return Err(PathError::DegenerateBlend { index });
+        }
+
+        let blend_start = blend_segment.position(0.0);
+        let blend_end = blend_segment.position(blend_segment.len());
+
+        // Update previous segment with new end point
+        let prev_segment = match self.segments.last() {
+            Some(PathSegment::Linear(s)) => self
+                .line(s.start.clone(), blend_start)
+                .with_start_offset(s.start_offset),
+            _ => return Err(PathError::DegenerateBlend { index }),
+        };
+
+        let _ = self.segments.pop();
+
+        let mut start_offset = prev_segment.start_offset + prev_segment.len();
+
+        // Switching point where linear segment touches blend
+        // TODO: Get actual list of switching points when support for non-linear
+        // path segments (that aren't blends) is added.
+        self.switching_points.push(PathSwitchingPoint::new(
+            start_offset,
+            blend_continuity.clone(),
+        ));
+
+        let blend_segment = blend_segment.with_start_offset(start_offset);
+        let blend_end_offset = blend_segment.start_offset() + blend_segment.len();
+
+        // Get switching points over the duration of the blend segment
+        self.switching_points.extend(
+            blend_segment
+                .switching_points()
+                .iter()
+                .map(|p| p + blend_segment.start_offset())
+                .filter(|p_offset| *p_offset < blend_end_offset)
+                .map(|p_offset| PathSwitchingPoint::new(p_offset, Continuity::Continuous)),
+        );
+
+        // Add blend segment length to path length total
+        start_offset = blend_end_offset;
+
+        let next_segment = self
+            .line(blend_end, next.clone())
+            .with_start_offset(start_offset);
+
+        // Switching point where linear segment touches blend
+        // TODO: Get actual list of switching points when support for non-linear
+        // path segments (that aren't blends) is added.
+        self.switching_points
+            .push(PathSwitchingPoint::new(start_offset, blend_continuity));
+
+        self.length = next_segment.end_offset();
+        self.index = SegmentIndexCache::default();
+
+        // Add both linear segments with blend in between to overall path
+        self.segments.push(PathSegment::Linear(prev_segment));
+        self.segments.push(blend_segment);
+        self.segments.push(PathSegment::Linear(next_segment));
+
+        Ok(())
+    }
+
+    /// Create a path from segments that join end to end
+    ///
+    /// Segment start offsets are recalculated. Each junction between two segments is added as a
+    /// discontinuous switching point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no segments are given. Use `Path::try_from_segments` to validate segments and get
+    /// an error instead.
+    pub fn from_segments(segments: Vec<PathSegment<N>>) -> Self {
+        assert!(!segments.is_empty(), "{}", PathError::NoSegments);
+
+        let count = segments.len();
+        let mut start_offset = 0.0;
+        let mut switching_points = Vec::new();
+
+        let segments = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                let segment = segment.with_start_offset(start_offset);
+
+                switching_points.extend(segment.switching_points().into_iter().map(|p| {
+                    PathSwitchingPoint::new(segment.start_offset() + p, Continuity::Continuous)
+                }));
+
+                start_offset = segment.end_offset();
+
+                if index + 1 < count {
+                    switching_points.push(PathSwitchingPoint::new(
+                        start_offset,
+                        Continuity::Discontinuous,
+                    ));
+                }
+
+                segment
+            })
+            .collect();
+
         Self {
-            switching_points,
             segments,
-            length,
+            length: start_offset,
+            switching_points,
+            waypoints: Vec::new(),
+            options: PathOptions::default(),
+            index: SegmentIndexCache::default(),
         }
     }
 
-    // TODO: Keep a hashmap of segment positions to segments
+    /// Create a path from segments, returning an error if they do not join end to end
+    ///
+    /// The end of each segment must be within `MAX_DEVIATION` of the start of the next.
+    pub fn try_from_segments(segments: Vec<PathSegment<N>>) -> Result<Self, PathError> {
+        if segments.is_empty() {
+            return Err(PathError::NoSegments);
+        }
+
+        if let Some(index) = segments.windows(2).position(|parts| {
+            let end = parts[0].position(parts[0].end_offset());
+            let start = parts[1].position(parts[1].start_offset());
+
+            (end - start).norm() > MAX_DEVIATION
+        }) {
+            return Err(PathError::DisconnectedSegments { index });
+        }
+
+        Ok(Self::from_segments(segments))
+    }
+
     /// Get a path segment for a position along the entire path
     ///
     /// It will return the last segment in the path if a position greater than the total path length
     /// is given.
     pub fn segment_at_position(&self, position_along_path: f64) -> &PathSegment<N> {
-        self.segments
-            .iter()
-            .find(|segment| segment.end_offset() > position_along_path)
-            .unwrap_or_else(|| &self.segments.last().unwrap())
+        &self.segments[self.segment_index_at_position(position_along_path)]
+    }
+
+    /// Get the index of the path segment for a position along the entire path
+    ///
+    /// Returns the index of the last segment if a position greater than the total path length is
+    /// given.
+    pub(crate) fn segment_index_at_position(&self, position_along_path: f64) -> usize {
+        // Segments are sorted by offset, so binary search for the first segment that ends after
+        // the given position
+        let index = self
+            .segments
+            .partition_point(|segment| segment.end_offset() <= position_along_path);
+
+        index.min(self.segments.len() - 1)
+    }
+
+    /// Get the waypoints this path was blended from
+    ///
+    /// Returns an empty slice if the path was created from segments.
+    pub fn waypoints(&self) -> &[Coord<N>] {
+        &self.waypoints
     }
 
     /// Get all switching points along this path
@@ -203,17 +488,31 @@ where
         &self.switching_points
     }
 
+    /// Get all switching points that lie after a position along the path
+    pub fn switching_points_after(&self, position_along_path: f64) -> &[PathSwitchingPoint] {
+        let index = self
+            .switching_points
+            .partition_point(|sp| sp.position <= position_along_path);
+
+        &self.switching_points[index..]
+    }
+
     /// Get position of next switching point after a position along the path
     ///
     /// Returns the end of the path as position if no switching point could be found
     pub fn next_switching_point(&self, position_along_path: f64) -> Option<&PathSwitchingPoint> {
-        self.switching_points
-            .iter()
-            .find(|sp| sp.position > position_along_path)
+        self.switching_points_after(position_along_path).first()
         // TODO: Test a load of different paths to see if commenting this out makes a difference
         // .or_else(|| self.switching_points.last())
     }
 
+    /// Get the next discontinuous switching point after a position along the path
+    pub fn next_discontinuity(&self, position_along_path: f64) -> Option<&PathSwitchingPoint> {
+        self.switching_points_after(position_along_path)
+            .iter()
+            .find(|sp| sp.continuity == Continuity::Discontinuous)
+    }
+
     /// Get an iterator of path switching points
     pub fn switching_points_iter(&self) -> impl Iterator<Item = &PathSwitchingPoint> {
         self.switching_points.iter()
@@ -273,6 +572,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.01,
+                ..PathOptions::default()
             },
         );
     }
@@ -286,14 +586,220 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.01,
+                ..PathOptions::default()
             },
         );
         let _p2 = Path::from_waypoints(
             &Vec::<TestCoord3>::new(),
             PathOptions {
                 max_deviation: 0.01,
+                ..PathOptions::default()
+            },
+        );
+    }
+
+    #[test]
+    fn try_path_too_short() {
+        assert_eq!(
+            Path::try_from_waypoints(
+                &vec![TestCoord3::new(1.0, 0.0, 0.0)],
+                PathOptions::default()
+            ),
+            Err(PathError::TooFewWaypoints { count: 1 })
+        );
+        assert_eq!(
+            Path::try_from_waypoints(&Vec::<TestCoord3>::new(), PathOptions::default()),
+            Err(PathError::TooFewWaypoints { count: 0 })
+        );
+    }
+
+    #[test]
+    fn try_path_non_finite() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.0),
+            TestCoord3::new(1.0, std::f64::NAN, 0.0),
+        ];
+
+        assert_eq!(
+            Path::try_from_waypoints(&waypoints, PathOptions::default()),
+            Err(PathError::NonFiniteCoordinate { index: 2 })
+        );
+    }
+
+    #[test]
+    fn try_path_zero_length_segment() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        assert_eq!(
+            Path::try_from_waypoints(&waypoints, PathOptions::default()),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+    }
+
+    #[test]
+    fn try_path_degenerate_blend() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.0),
+        ];
+
+        assert_eq!(
+            Path::try_from_waypoints(&waypoints, PathOptions::default()),
+            Err(PathError::DegenerateBlend { index: 2 })
+        );
+    }
+
+    #[test]
+    fn try_path_matches_from_waypoints() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 2.0, 0.0),
+        ];
+
+        assert_eq!(
+            Path::try_from_waypoints(&waypoints, PathOptions::default()),
+            Ok(Path::from_waypoints(&waypoints, PathOptions::default()))
+        );
+    }
+
+    #[test]
+    fn path_from_arc_segments() {
+        let start = TestCoord3::new(0.0, 0.0, 0.0);
+        let circle_start = TestCoord3::new(10.0, 0.0, 0.0);
+        let end = TestCoord3::new(20.0, 0.0, 0.0);
+
+        let circle = CircularPathSegment::from_arc(
+            &circle_start,
+            &circle_start,
+            &TestCoord3::new(10.0, 5.0, 0.0),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+        )
+        .unwrap();
+
+        let path = Path::try_from_segments(vec![
+            PathSegment::Linear(LinearPathSegment::from_waypoints(start, circle_start)),
+            PathSegment::Circular(circle),
+            PathSegment::Linear(LinearPathSegment::from_waypoints(circle_start, end)),
+        ])
+        .unwrap();
+
+        let circumference = 2.0 * std::f64::consts::PI * 5.0;
+
+        assert_near!(path.len(), 20.0 + circumference);
+        assert_near!(
+            path.position(10.0 + circumference / 2.0),
+            TestCoord3::new(10.0, 10.0, 0.0)
+        );
+        assert_near!(
+            path.tangent(10.0 + circumference / 4.0),
+            TestCoord3::new(0.0, 1.0, 0.0)
+        );
+        assert_near!(path.position(path.len()), end);
+
+        let discontinuities = path
+            .switching_points()
+            .iter()
+            .filter(|point| point.continuity == Continuity::Discontinuous)
+            .map(|point| point.position)
+            .collect::<Vec<f64>>();
+
+        assert_eq!(discontinuities, vec![10.0, 10.0 + circumference]);
+
+        // The X and Y tangents of the circle reverse every half turn
+        let continuous = path
+            .switching_points()
+            .iter()
+            .filter(|point| {
+                point.continuity == Continuity::Continuous
+                    && point.position > 10.0 + TRAJECTORY_EPSILON
+            })
+            .count();
+
+        assert_eq!(continuous, 3);
+    }
+
+    #[test]
+    fn try_path_from_invalid_segments() {
+        let line = |start, end| PathSegment::Linear(LinearPathSegment::from_waypoints(start, end));
+
+        assert_eq!(
+            Path::<nalgebra::U3>::try_from_segments(Vec::new()),
+            Err(PathError::NoSegments)
+        );
+        assert_eq!(
+            Path::try_from_segments(vec![
+                line(
+                    TestCoord3::new(0.0, 0.0, 0.0),
+                    TestCoord3::new(1.0, 0.0, 0.0)
+                ),
+                line(
+                    TestCoord3::new(1.0, 0.0, 0.0),
+                    TestCoord3::new(1.0, 1.0, 0.0)
+                ),
+                line(
+                    TestCoord3::new(1.0, 2.0, 0.0),
+                    TestCoord3::new(1.0, 3.0, 0.0)
+                ),
+            ]),
+            Err(PathError::DisconnectedSegments { index: 1 })
+        );
+    }
+
+    #[test]
+    fn clothoid_blends() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.01,
+                blend: Blend::Clothoid,
+                ..PathOptions::default()
             },
         );
+
+        assert_eq!(path.segments.len(), 5);
+        assert!(path
+            .switching_points()
+            .iter()
+            .all(|point| point.continuity == Continuity::Continuous));
+
+        // Curvature is continuous where each blend meets a linear segment
+        for segment in path.segments.iter() {
+            if let PathSegment::Clothoid(blend) = segment {
+                for offset in [blend.start_offset, blend.end_offset].iter() {
+                    assert!(path.curvature(offset - 1e-9).norm() < 1e-4);
+                    assert!(path.curvature(offset + 1e-9).norm() < 1e-4);
+                }
+            }
+        }
+
+        let trajectory = crate::Trajectory::new(
+            &path,
+            crate::TrajectoryOptions {
+                timestep: 0.01,
+                ..crate::TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_near!(trajectory.position(trajectory.duration()), waypoints[3]);
     }
 
     #[test]
@@ -308,6 +814,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.01,
+                ..PathOptions::default()
             },
         );
 
@@ -502,6 +1009,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_next_discontinuity() {
+        // Data from Example.cpp in C++ example code
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 0.2, 1.0),
+            TestCoord3::new(0.0, 3.0, 0.5),
+            TestCoord3::new(1.1, 2.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(0.0, 0.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        assert_eq!(
+            path.next_discontinuity(1.0173539279271488),
+            Some(&PathSwitchingPoint::new(
+                1.0212310438858092,
+                Continuity::Discontinuous
+            ))
+        );
+        assert_eq!(path.switching_points_after(8.845047598681882), &[]);
+        assert_eq!(path.next_discontinuity(8.845047598681882), None);
+    }
+
     #[test]
     fn length_limit_blend_size() {
         let waypoints = vec![
@@ -511,7 +1044,13 @@ mod tests {
             TestCoord3::new(1.0, 2.0, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 1.5 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 1.5,
+                ..PathOptions::default()
+            },
+        );
 
         debug_path("length_limit_blend_size", &path, &waypoints);
 
@@ -587,6 +1126,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.05,
+                ..PathOptions::default()
             },
         );
 
@@ -635,7 +1175,16 @@ mod tests {
                         assert_near!(*point, *expected);
                     }
                 }
-                PathSegment::Linear(s) => assert_eq!(s.switching_points(), Vec::new()),
+                PathSegment::Linear(s) => assert_eq!(s.switching_points(), Vec::<f64>::new()),
+                PathSegment::Clothoid(_)
+                | PathSegment::Spline(_)
+                | PathSegment::Helix(_)
+                | PathSegment::BSpline(_)
+                | PathSegment::Trimmed(_)
+                | PathSegment::Transformed(_)
+                | PathSegment::Custom(_) => {
+                    unreachable!()
+                }
             }
         }
     }
@@ -652,7 +1201,13 @@ mod tests {
             TestCoord3::new(4.0, 4.0, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 0.1 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
 
         debug_path("path_with_blends", &path, &waypoints);
 
@@ -668,7 +1223,13 @@ mod tests {
             TestCoord3::new(2.0, 2.0, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 0.1 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
         let pos = path.position(0.5);
 
         debug_path_point("get_pos_in_first_segment", &path, &waypoints, &pos);
@@ -686,7 +1247,13 @@ mod tests {
             TestCoord3::new(2.0, 2.0, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 0.1 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
         let pos = path.position(path.len() - 0.70710678118);
 
         debug_path_point("get_pos_in_last_segment", &path, &waypoints, &pos);
@@ -704,7 +1271,13 @@ mod tests {
             TestCoord3::new(2.5, 0.5, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 0.1 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
         let pos = path.position(path.len() - 0.2);
 
         debug_path_point("get_pos_in_last_segment_other", &path, &waypoints, &pos);
@@ -725,7 +1298,13 @@ mod tests {
             TestCoord3::new(2.0, 2.0, 0.0),
         ];
 
-        let path = Path::from_waypoints(&waypoints, PathOptions { max_deviation: 0.1 });
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
         let pos = path.position(path.len());
 
         debug_path_point("get_pos_in_last_segment", &path, &waypoints, &pos);
@@ -733,4 +1312,199 @@ mod tests {
         assert_near!(path.len(), 3.2586540784544042);
         assert_near!(pos, TestCoord3::new(2.0, 2.0, 0.0));
     }
+
+    fn weighted_waypoints() -> Vec<TestCoord3> {
+        // Third axis is rotary, in degrees
+        vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(3.0, 0.0, 0.0),
+            TestCoord3::new(3.0, 1.0, 40.0),
+            TestCoord3::new(5.0, 3.0, 10.0),
+        ]
+    }
+
+    fn weighted_options(blend: Blend) -> PathOptions<nalgebra::U3> {
+        PathOptions {
+            max_deviation: 0.1,
+            blend,
+            axis_weights: Some(TestCoord3::new(1.0, 1.0, 0.1)),
+            ..PathOptions::default()
+        }
+    }
+
+    #[test]
+    fn weighted_path() {
+        let weights = TestCoord3::new(1.0, 1.0, 0.1);
+
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let path = Path::from_waypoints(&weighted_waypoints(), weighted_options(*blend));
+
+            assert_near!(path.position(0.0), weighted_waypoints()[0]);
+            assert_near!(path.position(path.len()), weighted_waypoints()[3]);
+
+            // Length is measured with the weights, so it is shorter than the weighted polyline
+            let polyline = weighted_waypoints()
+                .windows(2)
+                .map(|pair| (&pair[1] - &pair[0]).component_mul(&weights).norm())
+                .sum::<f64>();
+
+            assert!(path.len() < polyline);
+            assert!(path.len() > polyline - 1.0);
+
+            let step = 0.000_01;
+
+            for i in 1..200 {
+                let distance = path.len() * i as f64 / 200.0;
+                let (tangent, curvature) = path.tangent_and_curvature(distance);
+
+                // Tangents are unit length in the weighted space
+                assert_near!(tangent.component_mul(&weights).norm(), 1.0);
+
+                // Tangent and curvature are still derivatives with respect to distance
+                let difference = (path.position(distance + step) - path.position(distance - step))
+                    / (2.0 * step);
+
+                assert!((difference - tangent).norm() < 0.000_01);
+
+                let segment = path.segment_at_position(distance);
+
+                if distance - step > segment.start_offset()
+                    && distance + step < segment.end_offset()
+                {
+                    let difference = (path.tangent(distance + step)
+                        - path.tangent(distance - step))
+                        / (2.0 * step);
+
+                    assert!((difference - curvature).norm() < 0.000_1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_blend_deviation() {
+        let weights = TestCoord3::new(1.0, 1.0, 0.1);
+        let waypoints = weighted_waypoints();
+        let path = Path::from_waypoints(&waypoints, weighted_options(Blend::Circular));
+
+        // The closest point of each blend to its corner is `max_deviation` away in the weighted
+        // space
+        for (corner, blend) in waypoints[1..3]
+            .iter()
+            .zip(path.segments.iter().skip(1).step_by(2))
+        {
+            let closest = (0..=1000)
+                .map(|i| {
+                    let distance = blend.start_offset() + blend.len() * i as f64 / 1000.0;
+
+                    (blend.position(distance) - corner)
+                        .component_mul(&weights)
+                        .norm()
+                })
+                .fold(std::f64::INFINITY, f64::min);
+
+            assert!((closest - 0.1).abs() < 0.000_1);
+        }
+    }
+
+    #[test]
+    fn uniform_weights_scale_length() {
+        let waypoints = weighted_waypoints();
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let scaled = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.002,
+                axis_weights: Some(TestCoord3::new(2.0, 2.0, 2.0)),
+                ..PathOptions::default()
+            },
+        );
+
+        assert_near!(scaled.len(), path.len() * 2.0);
+        assert_eq!(
+            scaled.switching_points().len(),
+            path.switching_points().len()
+        );
+
+        for i in 0..=100 {
+            let distance = path.len() * i as f64 / 100.0;
+
+            assert_near!(scaled.position(distance * 2.0), path.position(distance));
+            assert_near!(scaled.tangent(distance * 2.0), path.tangent(distance) / 2.0);
+        }
+    }
+
+    #[test]
+    fn edit_weighted_path() {
+        let mut path =
+            Path::from_waypoints(&weighted_waypoints(), weighted_options(Blend::Clothoid));
+
+        let _ = path
+            .move_waypoint(2, TestCoord3::new(3.5, 1.0, 60.0))
+            .unwrap();
+
+        let rebuilt = Path::from_waypoints(path.waypoints(), weighted_options(Blend::Clothoid));
+
+        assert_near!(path.len(), rebuilt.len());
+
+        for i in 0..=100 {
+            let distance = path.len() * i as f64 / 100.0;
+
+            assert_near!(path.position(distance), rebuilt.position(distance));
+        }
+    }
+
+    #[test]
+    fn plan_weighted_path() {
+        let path = Path::from_waypoints(&weighted_waypoints(), weighted_options(Blend::Circular));
+
+        let options = crate::TrajectoryOptions {
+            velocity_limit: TestCoord3::new(1.0, 1.0, 30.0),
+            acceleration_limit: TestCoord3::new(2.0, 2.0, 60.0),
+            timestep: 0.01,
+            ..crate::TrajectoryOptions::default()
+        };
+
+        let trajectory = crate::Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            weighted_waypoints()[3]
+        );
+
+        // Limits apply in each axis's own units
+        for sample in trajectory.sample(0.01).unwrap() {
+            let velocity = sample.velocity;
+
+            assert!(velocity.x.abs() < 1.0 + 0.01);
+            assert!(velocity.y.abs() < 1.0 + 0.01);
+            assert!(velocity.z.abs() < 30.0 + 0.3);
+        }
+    }
+
+    #[test]
+    fn invalid_axis_weights() {
+        let waypoints = weighted_waypoints();
+
+        for (weights, axis) in [
+            (TestCoord3::new(1.0, 0.0, 1.0), 1),
+            (TestCoord3::new(1.0, -1.0, 1.0), 1),
+            (TestCoord3::new(1.0, std::f64::NAN, 1.0), 1),
+            (TestCoord3::new(1.0, 1.0, std::f64::INFINITY), 2),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                Path::try_from_waypoints(
+                    &waypoints,
+                    PathOptions {
+                        axis_weights: Some(*weights),
+                        ..PathOptions::default()
+                    }
+                ),
+                Err(PathError::InvalidAxisWeights { axis: *axis })
+            );
+        }
+    }
 }
@@ -0,0 +1,222 @@
+use super::{Path, PathError, PathOptions};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::iter::Extend;
+
+/// Build a blended path one waypoint at a time
+///
+/// Each new waypoint is added with `Path::push_waypoint`, which only rebuilds the last linear
+/// segment of the path and adds the blend around the previous waypoint, so waypoints can be
+/// streamed in as they arrive. The resulting path is the same as one created by
+/// `Path::try_from_waypoints` with the same waypoints and options.
+#[derive(Debug, Clone)]
+pub struct PathBuilder<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Options used to blend each corner
+    options: PathOptions<N>,
+
+    /// The first waypoint, until a second one is pushed
+    start: Option<Coord<N>>,
+
+    /// The path built so far, once at least two waypoints have been pushed
+    path: Option<Path<N>>,
+}
+
+impl<N> PathBuilder<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a new, empty builder
+    pub fn new(options: PathOptions<N>) -> Self {
+        Self {
+            options,
+            start: None,
+            path: None,
+        }
+    }
+
+    /// Add a waypoint to the end of the path
+    ///
+    /// Returns an error if the waypoint has a NaN or infinite coordinate, is at the same position
+    /// as the previous waypoint, or makes the path double back on itself. The builder is left
+    /// unchanged if an error is returned.
+    pub fn push_waypoint(&mut self, waypoint: Coord<N>) -> Result<(), PathError> {
+        match (self.path.as_mut(), self.start.as_ref()) {
+            (Some(path), _) => path.push_waypoint(waypoint)?,
+            (None, Some(start)) => {
+                self.path = Some(Path::try_from_waypoints(
+                    &[start.clone(), waypoint],
+                    self.options,
+                )?);
+                self.start = None;
+            }
+            (None, None) => {
+                if waypoint.iter().any(|c| !c.is_finite()) {
+                    return Err(PathError::NonFiniteCoordinate { index: 0 });
+                }
+
+                self.start = Some(waypoint);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add each waypoint in turn to the end of the path, stopping at the first error
+    pub fn extend_waypoints<I>(&mut self, waypoints: I) -> Result<(), PathError>
+    where
+        I: IntoIterator<Item = Coord<N>>,
+    {
+        for waypoint in waypoints {
+            self.push_waypoint(waypoint)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the number of waypoints pushed so far
+    pub fn waypoint_count(&self) -> usize {
+        match (&self.path, &self.start) {
+            (Some(path), _) => path.waypoints().len(),
+            (None, Some(_)) => 1,
+            (None, None) => 0,
+        }
+    }
+
+    /// Get the path built so far, or `None` if fewer than two waypoints have been pushed
+    pub fn path(&self) -> Option<&Path<N>> {
+        self.path.as_ref()
+    }
+
+    /// Finish building and return the path
+    pub fn build(self) -> Result<Path<N>, PathError> {
+        let count = self.waypoint_count();
+
+        self.path.ok_or(PathError::TooFewWaypoints { count })
+    }
+}
+
+impl<N> Extend<Coord<N>> for PathBuilder<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Add each waypoint in turn to the end of the path
+    ///
+    /// # Panics
+    ///
+    /// Panics if a waypoint is invalid. Use `PathBuilder::extend_waypoints` to get an error
+    /// instead.
+    fn extend<I>(&mut self, waypoints: I)
+    where
+        I: IntoIterator<Item = Coord<N>>,
+    {
+        self.extend_waypoints(waypoints)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::Blend;
+
+    #[test]
+    fn matches_batch_path() {
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let options = PathOptions {
+                max_deviation: 0.1,
+                blend: *blend,
+                ..PathOptions::default()
+            };
+
+            let mut builder = PathBuilder::new(options);
+
+            for (index, waypoint) in waypoints_fixture().into_iter().enumerate() {
+                builder.push_waypoint(waypoint).unwrap();
+
+                // Every intermediate path is the same as a batch path over the same waypoints
+                if index > 0 {
+                    assert_eq!(
+                        builder.path(),
+                        Some(&Path::from_waypoints(
+                            &waypoints_fixture()[0..=index],
+                            options
+                        ))
+                    );
+                }
+            }
+
+            assert_eq!(
+                builder.build().unwrap(),
+                Path::from_waypoints(&waypoints_fixture(), options)
+            );
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let mut builder = PathBuilder::new(PathOptions::default());
+
+        builder.extend(waypoints_fixture().into_iter().take(3));
+        builder
+            .extend_waypoints(waypoints_fixture().into_iter().skip(3))
+            .unwrap();
+
+        assert_eq!(builder.waypoint_count(), 6);
+        assert_eq!(
+            builder.build().unwrap(),
+            Path::from_waypoints(&waypoints_fixture(), PathOptions::default())
+        );
+    }
+
+    #[test]
+    fn invalid_waypoints() {
+        let mut builder = PathBuilder::new(PathOptions::default());
+
+        assert_eq!(builder.path(), None);
+
+        builder.push_waypoint(TestCoord3::zeros()).unwrap();
+        builder
+            .push_waypoint(TestCoord3::new(1.0, 0.0, 0.0))
+            .unwrap();
+
+        let path = builder.path().cloned();
+
+        assert_eq!(
+            builder.push_waypoint(TestCoord3::new(1.0, 0.0, 0.0)),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+        assert_eq!(
+            builder.push_waypoint(TestCoord3::new(0.5, 0.0, 0.0)),
+            Err(PathError::DegenerateBlend { index: 1 })
+        );
+        assert_eq!(
+            builder.push_waypoint(TestCoord3::new(std::f64::NAN, 0.0, 0.0)),
+            Err(PathError::NonFiniteCoordinate { index: 2 })
+        );
+
+        // Failed pushes leave the path unchanged
+        assert_eq!(builder.path().cloned(), path);
+
+        assert_eq!(
+            PathBuilder::<nalgebra::U3>::new(PathOptions::default()).build(),
+            Err(PathError::TooFewWaypoints { count: 0 })
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Editing the waypoints of a blended path in place
+
+use super::{Path, PathError, PathSegment, PathTransform, SegmentIndexCache};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Add a waypoint to the end of the path
+    ///
+    /// Only the last linear segment is rebuilt, and a blend is added around the previous last
+    /// waypoint. Returns an error if the path was not created from waypoints, or if the new
+    /// waypoint is invalid as described in `Path::try_from_waypoints`. The path is left unchanged
+    /// if an error is returned.
+    pub fn push_waypoint(&mut self, waypoint: Coord<N>) -> Result<(), PathError> {
+        self.insert_waypoint(self.waypoints.len(), waypoint)
+    }
+
+    /// Insert a waypoint before the waypoint at `index`, or at the end of the path if `index` is
+    /// the number of waypoints
+    ///
+    /// Only the segments next to the new waypoint are rebuilt. Later segments and switching points
+    /// are moved along the path by the change in length. Returns an error if the path was not
+    /// created from waypoints, if `index` is out of range, or if the new waypoint is invalid as
+    /// described in `Path::try_from_waypoints`. The path is left unchanged if an error is returned.
+    ///
+    /// Along periodic axes, the new waypoint is moved by whole periods to be the shortest rotation
+    /// from the waypoint before it, and later waypoints are moved to follow it.
+    pub fn insert_waypoint(&mut self, index: usize, waypoint: Coord<N>) -> Result<(), PathError> {
+        self.check_index(index, self.waypoints.len() + 1)?;
+
+        let waypoint = self.unwrap_waypoint(waypoint, index.saturating_sub(1));
+
+        self.waypoints.insert(index, waypoint);
+
+        let unwrapped_tail = self.unwrap_tail(index + 1);
+        let start = index.saturating_sub(2);
+        let end = (index + 2).min(self.waypoints.len() - 1);
+
+        match self.rebuild_waypoints(start, end - 1, end, &unwrapped_tail) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.restore_tail(index + 1, unwrapped_tail);
+
+                let _ = self.waypoints.remove(index);
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Remove the waypoint at `index`, returning it
+    ///
+    /// Only the segments next to the removed waypoint are rebuilt. Later segments and switching
+    /// points are moved along the path by the change in length. Returns an error if the path was
+    /// not created from waypoints, if `index` is out of range, if fewer than two waypoints would be
+    /// left, or if the remaining waypoints are invalid as described in `Path::try_from_waypoints`.
+    /// The path is left unchanged if an error is returned.
+    ///
+    /// Along periodic axes, the waypoints after the removed one are moved by whole periods if
+    /// needed, so they follow the shortest rotation from the waypoint before them.
+    pub fn remove_waypoint(&mut self, index: usize) -> Result<Coord<N>, PathError> {
+        self.check_index(index, self.waypoints.len())?;
+
+        if self.waypoints.len() <= 2 {
+            return Err(PathError::TooFewWaypoints {
+                count: self.waypoints.len() - 1,
+            });
+        }
+
+        let start = index.saturating_sub(2);
+        let end = (index + 2).min(self.waypoints.len() - 1);
+
+        let waypoint = self.waypoints.remove(index);
+        let unwrapped_tail = self.unwrap_tail(index);
+
+        match self.rebuild_waypoints(start, end, end - 1, &unwrapped_tail) {
+            Ok(()) => Ok(waypoint),
+            Err(e) => {
+                self.restore_tail(index, unwrapped_tail);
+                self.waypoints.insert(index, waypoint);
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Move the waypoint at `index` to a new position, returning its old position
+    ///
+    /// Only the segments next to the moved waypoint are rebuilt. Later segments and switching
+    /// points are moved along the path by the change in length. Returns an error if the path was
+    /// not created from waypoints, if `index` is out of range, or if the moved waypoint is invalid
+    /// as described in `Path::try_from_waypoints`. The path is left unchanged if an error is
+    /// returned.
+    ///
+    /// Along periodic axes, the moved waypoint is unwrapped from its neighbours in the same way as
+    /// `Path::insert_waypoint`.
+    pub fn move_waypoint(
+        &mut self,
+        index: usize,
+        waypoint: Coord<N>,
+    ) -> Result<Coord<N>, PathError> {
+        self.check_index(index, self.waypoints.len())?;
+
+        // The first waypoint has no waypoint before it, so is unwrapped from the one after it
+        let waypoint = self.unwrap_waypoint(waypoint, if index > 0 { index - 1 } else { 1 });
+        let previous = std::mem::replace(&mut self.waypoints[index], waypoint);
+
+        let unwrapped_tail = self.unwrap_tail(index + 1);
+        let start = index.saturating_sub(2);
+        let end = (index + 2).min(self.waypoints.len() - 1);
+
+        match self.rebuild_waypoints(start, end, end, &unwrapped_tail) {
+            Ok(()) => Ok(previous),
+            Err(e) => {
+                self.restore_tail(index + 1, unwrapped_tail);
+                self.waypoints[index] = previous;
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Check that this path can be edited, and that an index is less than `count`
+    fn check_index(&self, index: usize, count: usize) -> Result<(), PathError> {
+        if self.waypoints.is_empty() {
+            return Err(PathError::NoWaypoints);
+        }
+
+        if index >= count {
+            return Err(PathError::WaypointOutOfRange {
+                index,
+                count: self.waypoints.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Put back the waypoints from `index` onwards if `Path::unwrap_tail` moved them
+    fn restore_tail(&mut self, index: usize, unwrapped_tail: Option<(Coord<N>, Vec<Coord<N>>)>) {
+        if let Some((_, previous)) = unwrapped_tail {
+            self.waypoints.truncate(index);
+            self.waypoints.extend(previous);
+        }
+    }
+
+    /// Rebuild the segments between the waypoints at `start` and `end`
+    ///
+    /// `self.waypoints` must already be edited. The waypoint at `end` was at `old_end` before the
+    /// edit. The blends around `start` and `end` are kept as they are, and every segment between
+    /// them is replaced. If `Path::unwrap_tail` moved the waypoints after `start`, the segments
+    /// from the blend around `end` onwards are moved with them.
+    fn rebuild_waypoints(
+        &mut self,
+        start: usize,
+        old_end: usize,
+        end: usize,
+        unwrapped_tail: &Option<(Coord<N>, Vec<Coord<N>>)>,
+    ) -> Result<(), PathError> {
+        let shift = unwrapped_tail
+            .as_ref()
+            .map(|(shift, _)| PathTransform::translation(shift));
+        let waypoints = &self.waypoints;
+        let last = waypoints.len() - 1;
+
+        Self::check_waypoints(&waypoints[start..=end], start)?;
+
+        // Linear segment `i` joins waypoints `i` and `i + 1`, with a blend in between each pair
+        let first_segment = 2 * start;
+        let last_segment = 2 * (old_end - 1);
+
+        let (window_start, start_offset) = match &self.segments[first_segment] {
+            PathSegment::Linear(s) if start > 0 => (s.start.clone(), s.start_offset),
+            _ => (waypoints[0].clone(), 0.0),
+        };
+
+        let (window_end, old_end_offset) = match &self.segments[last_segment] {
+            PathSegment::Linear(s) if end < last => match &shift {
+                Some(shift) => (shift.transform_point(&s.end), s.end_offset()),
+                None => (s.end.clone(), s.end_offset()),
+            },
+            s => (waypoints[last].clone(), s.end_offset()),
+        };
+
+        // Blend each corner in between into a temporary path, starting at the right offset
+        let mut window = Self {
+            segments: Vec::new(),
+            length: 0.0,
+            switching_points: Vec::new(),
+            waypoints: Vec::new(),
+            options: self.options,
+            index: SegmentIndexCache::default(),
+        };
+
+        let window_start = window
+            .line(window_start, waypoints[start + 1].clone())
+            .with_start_offset(start_offset);
+
+        window.segments.push(PathSegment::Linear(window_start));
+
+        for corner in start + 1..end {
+            window.push_corner(
+                &waypoints[corner - 1],
+                &waypoints[corner],
+                &waypoints[corner + 1],
+                corner,
+            )?;
+        }
+
+        // End at the start of the blend around `end`, if there is one
+        let end_segment = match window.segments.pop() {
+            Some(PathSegment::Linear(s)) => window
+                .line(s.start, window_end)
+                .with_start_offset(s.start_offset),
+            _ => unreachable!("Window must end in a linear segment"),
+        };
+
+        let delta = end_segment.end_offset() - old_end_offset;
+
+        window.segments.push(PathSegment::Linear(end_segment));
+
+        // Find switching points of the replaced blends before changing any segments
+        let first_switching_point = self.corner_switching_point_index(start + 1);
+        let last_switching_point = self.corner_switching_point_index(old_end);
+
+        let tail = self
+            .segments
+            .drain(last_segment + 1..)
+            .map(|segment| {
+                let offset = segment.start_offset() + delta;
+
+                match &shift {
+                    Some(shift) => segment.transformed(shift).with_start_offset(offset),
+                    None => segment.with_start_offset(offset),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.segments.truncate(first_segment);
+        self.segments.append(&mut window.segments);
+        self.segments.extend(tail);
+
+        for point in self.switching_points[last_switching_point..].iter_mut() {
+            point.position += delta;
+        }
+
+        let _ = self
+            .switching_points
+            .splice(
+                first_switching_point..last_switching_point,
+                window.switching_points,
+            )
+            .count();
+
+        self.length = self
+            .segments
+            .last()
+            .map(|segment| segment.end_offset())
+            .unwrap_or(0.0);
+        self.index = SegmentIndexCache::default();
+
+        Ok(())
+    }
+
+    /// Get the index of the first switching point of the blend around a waypoint
+    ///
+    /// Each blend adds its switching points in order, so this is the index just after the
+    /// switching points of the previous blend. Returns the number of switching points if there is
+    /// no blend around the waypoint.
+    fn corner_switching_point_index(&self, corner: usize) -> usize {
+        if corner + 1 >= self.waypoints_before_edit() {
+            return self.switching_points.len();
+        }
+
+        let before = &self.segments[2 * corner - 2];
+        let blend_start = self.segments[2 * corner - 1].start_offset();
+
+        let index = self
+            .switching_points
+            .partition_point(|point| point.position < blend_start);
+
+        // If the linear segment before the blend has no length, the previous blend's last
+        // switching point is at the same position
+        if corner > 1 && before.start_offset() >= blend_start {
+            index + 1
+        } else {
+            index
+        }
+    }
+
+    /// Get the number of waypoints the current segments were built from
+    fn waypoints_before_edit(&self) -> usize {
+        (self.segments.len() + 3) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{Blend, PathItem, PathOptions};
+
+    fn waypoints() -> Vec<TestCoord3> {
+        (0..12)
+            .map(|i| {
+                let i = i as f64;
+
+                TestCoord3::new(i, (i * 1.3).sin() * 2.0, (i * 0.7).cos())
+            })
+            .collect()
+    }
+
+    /// Check an edited path matches a path created from scratch with the same waypoints
+    fn assert_matches_rebuilt(path: &Path<nalgebra::U3>) {
+        let rebuilt = Path::from_waypoints(path.waypoints(), path.options);
+
+        assert_eq!(path.segments.len(), rebuilt.segments.len());
+        assert_near!(path.len(), rebuilt.len());
+
+        for (a, b) in path.segments.iter().zip(rebuilt.segments.iter()) {
+            assert_near!(a.start_offset(), b.start_offset());
+            assert_near!(a.end_offset(), b.end_offset());
+        }
+
+        assert_eq!(
+            path.switching_points().len(),
+            rebuilt.switching_points().len()
+        );
+
+        for (a, b) in path
+            .switching_points()
+            .iter()
+            .zip(rebuilt.switching_points())
+        {
+            assert_near!(a.position, b.position);
+            assert_eq!(a.continuity, b.continuity);
+        }
+
+        for i in 0..=200 {
+            let position = path.len() * i as f64 / 200.0;
+
+            assert_near!(path.position(position), rebuilt.position(position));
+        }
+    }
+
+    fn options() -> Vec<PathOptions<nalgebra::U3>> {
+        vec![
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+            PathOptions {
+                max_deviation: 0.1,
+                blend: Blend::Clothoid,
+                ..PathOptions::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn move_waypoints() {
+        for options in options() {
+            for index in 0..waypoints().len() {
+                let mut path = Path::from_waypoints(&waypoints(), options);
+
+                let previous = path
+                    .move_waypoint(index, waypoints()[index] + TestCoord3::new(0.3, -0.5, 0.2))
+                    .unwrap();
+
+                assert_eq!(previous, waypoints()[index]);
+                assert_matches_rebuilt(&path);
+            }
+        }
+    }
+
+    #[test]
+    fn insert_waypoints() {
+        for options in options() {
+            for index in 0..=waypoints().len() {
+                let mut path = Path::from_waypoints(&waypoints(), options);
+
+                path.insert_waypoint(index, TestCoord3::new(index as f64 - 0.5, 3.0, 0.0))
+                    .unwrap();
+
+                assert_eq!(path.waypoints().len(), waypoints().len() + 1);
+                assert_matches_rebuilt(&path);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_waypoints() {
+        for options in options() {
+            for index in 0..waypoints().len() {
+                let mut path = Path::from_waypoints(&waypoints(), options);
+
+                assert_eq!(path.remove_waypoint(index), Ok(waypoints()[index]));
+                assert_matches_rebuilt(&path);
+            }
+        }
+
+        // Remove waypoints down to a single segment
+        let mut path = Path::from_waypoints(&waypoints()[0..3], PathOptions::default());
+
+        let _ = path.remove_waypoint(1).unwrap();
+
+        assert_matches_rebuilt(&path);
+        assert_eq!(
+            path.remove_waypoint(0),
+            Err(PathError::TooFewWaypoints { count: 1 })
+        );
+    }
+
+    #[test]
+    fn push_waypoints() {
+        let mut path = Path::from_waypoints(&waypoints()[0..2], PathOptions::default());
+
+        for waypoint in waypoints().into_iter().skip(2) {
+            path.push_waypoint(waypoint).unwrap();
+        }
+
+        assert_eq!(
+            path,
+            Path::from_waypoints(&waypoints(), PathOptions::default())
+        );
+    }
+
+    #[test]
+    fn invalid_edits() {
+        let original = Path::from_waypoints(&waypoints(), PathOptions::default());
+        let mut path = original.clone();
+
+        assert_eq!(
+            path.move_waypoint(3, waypoints()[2]),
+            Err(PathError::ZeroLengthSegment { index: 2 })
+        );
+        assert_eq!(
+            path.insert_waypoint(13, TestCoord3::zeros()),
+            Err(PathError::WaypointOutOfRange {
+                index: 13,
+                count: 12
+            })
+        );
+        assert_eq!(
+            path.move_waypoint(3, TestCoord3::new(std::f64::NAN, 0.0, 0.0)),
+            Err(PathError::NonFiniteCoordinate { index: 3 })
+        );
+
+        // Doubling back from waypoint 4 to a point between waypoints 3 and 4
+        let midpoint = (waypoints()[3] + waypoints()[4]) / 2.0;
+
+        assert_eq!(
+            path.insert_waypoint(5, midpoint),
+            Err(PathError::DegenerateBlend { index: 4 })
+        );
+
+        // Failed edits leave the path unchanged
+        assert_eq!(path, original);
+
+        let mut path = Path::from_segments(original.segments.clone());
+
+        assert_eq!(
+            path.push_waypoint(TestCoord3::zeros()),
+            Err(PathError::NoWaypoints)
+        );
+    }
+}
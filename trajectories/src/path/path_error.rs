@@ -0,0 +1,142 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors that can occur when creating a path
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    /// A path must be created from at least two waypoints
+    TooFewWaypoints {
+        /// Number of waypoints that were given
+        count: usize,
+    },
+
+    /// A waypoint contains a NaN or infinite coordinate
+    NonFiniteCoordinate {
+        /// Index of the offending waypoint
+        index: usize,
+    },
+
+    /// Two consecutive waypoints are at the same position, producing a segment with no length
+    ZeroLengthSegment {
+        /// Index of the waypoint at the start of the zero length segment
+        index: usize,
+    },
+
+    /// No valid blend could be created around a waypoint, for example because the path doubles
+    /// back on itself
+    DegenerateBlend {
+        /// Index of the waypoint the blend was created around
+        index: usize,
+    },
+
+    /// An arc cannot be created, for example because its start and end points are different
+    /// distances from its center or do not lie in the arc plane
+    InvalidArc,
+
+    /// A B-spline cannot be created, for example because its knot vector or weights do not match
+    /// its control points
+    InvalidBSpline,
+
+    /// A path must be created from at least one segment
+    NoSegments,
+
+    /// A segment does not end where the next segment starts
+    DisconnectedSegments {
+        /// Index of the segment whose end does not meet the next segment
+        index: usize,
+    },
+
+    /// A path created from segments has no waypoints to edit
+    NoWaypoints,
+
+    /// A waypoint index is past the end of the path's waypoints
+    WaypointOutOfRange {
+        /// Index that was given
+        index: usize,
+        /// Number of waypoints in the path
+        count: usize,
+    },
+
+    /// A transform is not invertible or contains a NaN or infinite value
+    InvalidTransform,
+
+    /// A slice does not lie within the path, or ends before it starts
+    InvalidSlice {
+        /// Distance along the path the slice starts at
+        start: f64,
+        /// Distance along the path the slice ends at
+        end: f64,
+    },
+
+    /// Every axis weight must be positive and finite
+    InvalidAxisWeights {
+        /// Index of the first axis with an invalid weight
+        axis: usize,
+    },
+
+    /// Every axis period must be zero or a positive, finite number
+    InvalidPeriodicAxis {
+        /// Index of the offending axis
+        axis: usize,
+    },
+
+    /// The weight of rotation in a pose path must be a positive, finite number
+    InvalidRotationWeight {
+        /// Weight that was given
+        weight: f64,
+    },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::TooFewWaypoints { count } => {
+                write!(f, "Path must contain at least two waypoints, got {}", count)
+            }
+            PathError::NonFiniteCoordinate { index } => write!(
+                f,
+                "Waypoint {} contains a NaN or infinite coordinate",
+                index
+            ),
+            PathError::ZeroLengthSegment { index } => write!(
+                f,
+                "Waypoints {} and {} are at the same position",
+                index,
+                index + 1
+            ),
+            PathError::DegenerateBlend { index } => {
+                write!(f, "Could not create a blend around waypoint {}", index)
+            }
+            PathError::InvalidArc => write!(f, "Invalid arc"),
+            PathError::InvalidBSpline => write!(f, "Invalid B-spline"),
+            PathError::NoSegments => write!(f, "Path must contain at least one segment"),
+            PathError::DisconnectedSegments { index } => write!(
+                f,
+                "Segment {} does not end at the start of segment {}",
+                index,
+                index + 1
+            ),
+            PathError::NoWaypoints => write!(f, "Path was not created from waypoints"),
+            PathError::WaypointOutOfRange { index, count } => write!(
+                f,
+                "Waypoint index {} is out of range for a path with {} waypoints",
+                index, count
+            ),
+            PathError::InvalidTransform => write!(f, "Invalid transform"),
+            PathError::InvalidSlice { start, end } => {
+                write!(f, "Cannot slice path from {} to {}", start, end)
+            }
+            PathError::InvalidAxisWeights { axis } => {
+                write!(f, "Invalid weight for axis {}", axis)
+            }
+            PathError::InvalidPeriodicAxis { axis } => {
+                write!(f, "Invalid period for axis {}", axis)
+            }
+            PathError::InvalidRotationWeight { weight } => {
+                write!(f, "Invalid rotation weight {}", weight)
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
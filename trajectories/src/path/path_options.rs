@@ -1,14 +1,61 @@
+use super::Blend;
+use crate::Coord;
+use nalgebra::{allocator::SameShapeVectorAllocator, storage::Owned, DefaultAllocator, DimName};
+
 /// Path creation options
-#[derive(Debug, Copy, Clone)]
-pub struct PathOptions {
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct PathOptions<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
     /// Maximum deviation from true ideal path
     pub max_deviation: f64,
+
+    /// Type of curve used to blend corners
+    pub blend: Blend,
+
+    /// Weight of each axis when measuring distance along the path, or `None` to weight every axis
+    /// equally
+    ///
+    /// Path length, blend deviation and tangents are measured after multiplying each coordinate
+    /// by its axis weight, so a path can mix axes in different units, like millimetres and
+    /// degrees. Trajectory limits still apply in each axis's own units. Every weight must be
+    /// positive.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub axis_weights: Option<Coord<N>>,
+
+    /// Period each axis wraps around at, or zero for axes that do not wrap, like rotary axes
+    /// without hard stops
+    ///
+    /// Each waypoint is moved by a whole number of periods along these axes so the path takes the
+    /// shortest rotation from the waypoint before it, so going from 359 to 1 degrees turns by 2
+    /// degrees. Positions along the path are not wrapped; use `Path::wrap` to wrap them.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub axis_periods: Option<Coord<N>>,
 }
 
-impl Default for PathOptions {
+impl<N> Default for PathOptions<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
     fn default() -> Self {
         Self {
             max_deviation: 0.001,
+            blend: Blend::Circular,
+            axis_weights: None,
+            axis_periods: None,
         }
     }
 }
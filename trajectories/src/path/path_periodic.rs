@@ -0,0 +1,421 @@
+//! Axes that wrap around
+
+use super::{Path, PathError, PathOptions};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Wrap each periodic axis of a position into the range from zero up to its period
+    ///
+    /// Other axes are left as they are. Use this to report positions along a path with periodic
+    /// axes in the range the machine expects.
+    pub fn wrap(&self, position: &Coord<N>) -> Coord<N> {
+        let mut wrapped = position.clone();
+
+        for (axis, period) in periodic_axes(&self.options) {
+            let value = position[axis].rem_euclid(period);
+
+            // Tiny negative values can round up to the period itself
+            wrapped[axis] = if value < period { value } else { 0.0 };
+        }
+
+        wrapped
+    }
+
+    /// Check each axis period is zero or a positive, finite number
+    pub(crate) fn check_periodic_axes(options: &PathOptions<N>) -> Result<(), PathError> {
+        let invalid = options.axis_periods.and_then(|periods| {
+            periods
+                .iter()
+                .position(|period| !period.is_finite() || *period < 0.0)
+        });
+
+        match invalid {
+            Some(axis) => Err(PathError::InvalidPeriodicAxis { axis }),
+            None => Ok(()),
+        }
+    }
+
+    /// Move a waypoint by whole periods along each periodic axis so it is the shortest rotation
+    /// away from `reference`
+    fn unwrap_from(
+        periodic_axes: &[(usize, f64)],
+        waypoint: &Coord<N>,
+        reference: &Coord<N>,
+    ) -> Coord<N> {
+        let mut unwrapped = waypoint.clone();
+
+        for (axis, period) in periodic_axes {
+            let turns = ((waypoint[*axis] - reference[*axis]) / period + 0.5).floor();
+
+            unwrapped[*axis] = waypoint[*axis] - turns * period;
+        }
+
+        unwrapped
+    }
+
+    /// Unwrap each waypoint from the one before it, keeping the first waypoint where it is
+    pub(crate) fn unwrap_waypoints(
+        waypoints: &[Coord<N>],
+        options: &PathOptions<N>,
+    ) -> Vec<Coord<N>> {
+        let periodic_axes = periodic_axes(options);
+        let mut unwrapped: Vec<Coord<N>> = Vec::with_capacity(waypoints.len());
+
+        for waypoint in waypoints {
+            let waypoint = match unwrapped.last() {
+                Some(previous) => Self::unwrap_from(&periodic_axes, waypoint, previous),
+                None => waypoint.clone(),
+            };
+
+            unwrapped.push(waypoint);
+        }
+
+        unwrapped
+    }
+
+    /// Unwrap a new or moved waypoint from the waypoint at `reference`
+    pub(crate) fn unwrap_waypoint(&self, waypoint: Coord<N>, reference: usize) -> Coord<N> {
+        Self::unwrap_from(
+            &periodic_axes(&self.options),
+            &waypoint,
+            &self.waypoints[reference],
+        )
+    }
+
+    /// Move the waypoints from `index` onwards by the same whole number of periods, so the
+    /// waypoint at `index` is the shortest rotation away from the one before it
+    ///
+    /// Returns how far the waypoints were moved and their previous positions, or `None` if they
+    /// did not need to move.
+    pub(crate) fn unwrap_tail(&mut self, index: usize) -> Option<(Coord<N>, Vec<Coord<N>>)> {
+        if index == 0 || index >= self.waypoints.len() {
+            return None;
+        }
+
+        let shift = Self::unwrap_from(
+            &periodic_axes(&self.options),
+            &self.waypoints[index],
+            &self.waypoints[index - 1],
+        ) - &self.waypoints[index];
+
+        if shift.iter().all(|component| *component == 0.0) {
+            return None;
+        }
+
+        let previous = self.waypoints[index..].to_vec();
+
+        for waypoint in self.waypoints[index..].iter_mut() {
+            *waypoint += &shift;
+        }
+
+        Some((shift, previous))
+    }
+}
+
+/// Get the index and period of each axis that wraps around
+fn periodic_axes<N>(options: &PathOptions<N>) -> Vec<(usize, f64)>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    options
+        .axis_periods
+        .map(|periods| {
+            periods
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(_, period)| *period > 0.0)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{PathItem, Trajectory, TrajectoryOptions};
+    use nalgebra::U3;
+
+    fn options() -> PathOptions<U3> {
+        PathOptions {
+            max_deviation: 0.1,
+            axis_periods: Some(TestCoord3::new(0.0, 0.0, 360.0)),
+            ..PathOptions::default()
+        }
+    }
+
+    #[test]
+    fn shortest_rotation() {
+        let path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 350.0),
+                TestCoord3::new(1.0, 0.0, 10.0),
+                TestCoord3::new(2.0, 1.0, 330.0),
+                TestCoord3::new(3.0, 1.0, -170.0),
+            ],
+            options(),
+        );
+
+        assert_eq!(
+            path.waypoints(),
+            &[
+                TestCoord3::new(0.0, 0.0, 350.0),
+                TestCoord3::new(1.0, 0.0, 370.0),
+                TestCoord3::new(2.0, 1.0, 330.0),
+                TestCoord3::new(3.0, 1.0, 190.0),
+            ]
+        );
+
+        assert_near!(path.position(0.0), TestCoord3::new(0.0, 0.0, 350.0));
+        assert_near!(path.position(path.len()), TestCoord3::new(3.0, 1.0, 190.0));
+
+        // Positions along the path are continuous, and wrap back into range
+        let middle = TestCoord3::new(1.0, 0.0, 20.0).norm() * 0.75;
+
+        assert_near!(path.position(middle).z, 365.0);
+        assert_near!(path.wrap(&path.position(middle)).z, 5.0);
+
+        // Half a turn either way is unwrapped the same way every time
+        let half = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 180.0),
+            ],
+            options(),
+        );
+
+        assert_eq!(half.waypoints()[1], TestCoord3::new(1.0, 0.0, -180.0));
+        assert_eq!(
+            Path::from_waypoints(half.waypoints(), options()).waypoints(),
+            half.waypoints()
+        );
+    }
+
+    #[test]
+    fn wrap_positions() {
+        let path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 10.0),
+            ],
+            options(),
+        );
+
+        assert_eq!(
+            path.wrap(&TestCoord3::new(-5.0, 725.0, -90.0)),
+            TestCoord3::new(-5.0, 725.0, 270.0)
+        );
+        assert_eq!(
+            path.wrap(&TestCoord3::new(0.0, 0.0, -0.000_000_000_000_000_01))
+                .z,
+            0.0
+        );
+        assert_eq!(
+            path.wrap(&TestCoord3::new(0.0, 0.0, 720.0)),
+            TestCoord3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn duplicate_after_unwrapping() {
+        let waypoints = [
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 90.0),
+            TestCoord3::new(1.0, 0.0, 450.0),
+        ];
+
+        assert_eq!(
+            Path::try_from_waypoints(&waypoints, options()),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+    }
+
+    #[test]
+    fn edit_periodic_path() {
+        let waypoints = vec![
+            TestCoord3::new(0.0, 0.0, 340.0),
+            TestCoord3::new(1.0, 0.0, 350.0),
+            TestCoord3::new(2.0, 1.0, 10.0),
+            TestCoord3::new(3.0, 1.0, 30.0),
+            TestCoord3::new(4.0, 2.0, 50.0),
+            TestCoord3::new(5.0, 2.0, 60.0),
+        ];
+
+        let assert_matches_rebuilt = |path: &Path<U3>| {
+            let rebuilt = Path::from_waypoints(path.waypoints(), options());
+
+            assert_eq!(path.waypoints(), rebuilt.waypoints());
+            assert_near!(path.len(), rebuilt.len());
+
+            for i in 0..=100 {
+                let distance = path.len() * i as f64 / 100.0;
+
+                assert_near!(path.position(distance), rebuilt.position(distance));
+            }
+        };
+
+        // Moving a waypoint the other way around moves every later waypoint by a whole turn
+        let mut path = Path::from_waypoints(&waypoints, options());
+
+        assert_eq!(path.waypoints()[3].z, 390.0);
+
+        let _ = path
+            .move_waypoint(2, TestCoord3::new(2.0, 1.0, 200.0))
+            .unwrap();
+
+        assert_eq!(path.waypoints()[2].z, 200.0);
+        assert_eq!(path.waypoints()[3].z, 30.0);
+        assert_eq!(path.waypoints()[5].z, 60.0);
+        assert_matches_rebuilt(&path);
+
+        // New waypoints are unwrapped from the one before them
+        let mut path = Path::from_waypoints(&waypoints, options());
+
+        path.insert_waypoint(2, TestCoord3::new(1.5, 0.5, 5.0))
+            .unwrap();
+        path.push_waypoint(TestCoord3::new(6.0, 2.0, -60.0))
+            .unwrap();
+
+        assert_eq!(path.waypoints()[2].z, 365.0);
+        assert_eq!(path.waypoints()[7].z, 300.0);
+        assert_matches_rebuilt(&path);
+
+        // Removing a waypoint can leave its neighbours more than half a turn apart
+        let mut path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 120.0),
+                TestCoord3::new(2.0, 1.0, 240.0),
+                TestCoord3::new(3.0, 1.0, 250.0),
+            ],
+            options(),
+        );
+
+        let _ = path.remove_waypoint(1).unwrap();
+
+        assert_eq!(path.waypoints()[1].z, -120.0);
+        assert_eq!(path.waypoints()[2].z, -110.0);
+        assert_matches_rebuilt(&path);
+
+        // A failed edit leaves the path as it was
+        let mut path = Path::from_waypoints(&waypoints, options());
+        let original = path.clone();
+
+        let _ = path
+            .move_waypoint(2, TestCoord3::new(2.0, 1.0, 200.5 + 1080.0))
+            .unwrap();
+
+        assert_eq!(path.waypoints()[2].z, 200.5);
+
+        let edited = path.clone();
+
+        assert_eq!(
+            path.move_waypoint(3, TestCoord3::new(2.0, 1.0, 200.5 + 360.0)),
+            Err(PathError::ZeroLengthSegment { index: 2 })
+        );
+        assert_eq!(path, edited);
+
+        // Later waypoints are put back too, after being moved by a turn
+        assert_eq!(
+            path.move_waypoint(2, TestCoord3::new(1.0, 0.0, 350.0)),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+        assert_eq!(path, edited);
+
+        let _ = path
+            .move_waypoint(2, TestCoord3::new(2.0, 1.0, 10.0))
+            .unwrap();
+
+        assert_eq!(path.waypoints(), original.waypoints());
+        assert_matches_rebuilt(&path);
+    }
+
+    #[test]
+    fn invalid_periodic_axes() {
+        let waypoints = [TestCoord3::zeros(), TestCoord3::x()];
+
+        for (axis_periods, axis) in [
+            (TestCoord3::new(0.0, -1.0, 360.0), 1),
+            (TestCoord3::new(0.0, std::f64::INFINITY, 0.0), 1),
+            (TestCoord3::new(0.0, 0.0, std::f64::NAN), 2),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                Path::try_from_waypoints(
+                    &waypoints,
+                    PathOptions {
+                        axis_periods: Some(*axis_periods),
+                        ..PathOptions::default()
+                    }
+                ),
+                Err(PathError::InvalidPeriodicAxis { axis: *axis })
+            );
+        }
+    }
+
+    #[test]
+    fn plan_periodic_path() {
+        let path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 355.0),
+                TestCoord3::new(1.0, 0.0, 5.0),
+                TestCoord3::new(1.0, 1.0, 15.0),
+            ],
+            PathOptions {
+                axis_weights: Some(TestCoord3::new(1.0, 1.0, 0.1)),
+                ..options()
+            },
+        );
+
+        let options = TrajectoryOptions {
+            velocity_limit: TestCoord3::new(1.0, 1.0, 30.0),
+            acceleration_limit: TestCoord3::new(2.0, 2.0, 60.0),
+            timestep: 0.01,
+            ..TrajectoryOptions::default()
+        };
+
+        let trajectory = Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            TestCoord3::new(1.0, 1.0, 375.0)
+        );
+        assert_near!(
+            trajectory.wrapped_position(trajectory.duration()),
+            TestCoord3::new(1.0, 1.0, 15.0)
+        );
+
+        // The rotary axis only turns the short way
+        for sample in trajectory.sample(0.01).unwrap() {
+            assert!(sample.position.z >= 355.0 - 0.000_1 && sample.position.z <= 375.0 + 0.000_1);
+        }
+
+        // Wrapped positions stay within one period everywhere they can be queried
+        let mut sampler = trajectory.sampler().with_wrapped_positions();
+
+        for sample in trajectory.sample(0.01).unwrap().with_wrapped_positions() {
+            let expected = path.wrap(&trajectory.position(sample.time));
+
+            assert!(sample.position.z >= 0.0 && sample.position.z < 360.0);
+            assert_near!(sample.position, expected);
+            assert_near!(sampler.position(sample.time), expected);
+            assert_near!(sampler.state(sample.time).position, expected);
+            assert_near!(trajectory.wrapped_state(sample.time).position, expected);
+        }
+    }
+}
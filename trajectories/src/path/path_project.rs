@@ -0,0 +1,392 @@
+//! Finding the closest point on a path
+
+use super::{Path, PathItem};
+use crate::{Coord, TRAJECTORY_EPSILON};
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Find the point on this path closest to `point`
+    ///
+    /// Returns the distance along the path of the closest point, its position, and its distance
+    /// from `point`. Each segment is projected onto as described in `PathSegment::project`. If
+    /// several points are equally close, the one nearest the start of the path is returned.
+    ///
+    /// The first call builds a bounding box tree over the segments, so later calls only check the
+    /// segments near `point`. Clones of the path share the tree once it is built.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path has no segments.
+    pub fn project(&self, point: &Coord<N>) -> (f64, Coord<N>, f64) {
+        let index = self.index.get(&self.segments);
+        let mut closest: Option<(f64, Coord<N>, f64)> = None;
+
+        index.nearest(point, |segment| {
+            let segment = &self.segments[segment];
+            let distance = segment.project(point);
+            let position = segment.position(distance);
+            let error = (&position - point).norm();
+
+            let is_closer = match &closest {
+                Some((closest_distance, _, closest_error)) => {
+                    error < closest_error - TRAJECTORY_EPSILON
+                        || (error <= closest_error + TRAJECTORY_EPSILON
+                            && distance < *closest_distance)
+                }
+                None => true,
+            };
+
+            if is_closer {
+                closest = Some((distance, position, error));
+            }
+
+            error
+        });
+
+        closest.expect("Path has no segments")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{
+        ArcDirection, BSplinePathSegment, Blend, CircularPathSegment, HelixPathSegment,
+        LinearPathSegment, PathOptions, PathSegment, SplinePathSegment,
+    };
+    use nalgebra::U3;
+    use std::f64::consts::PI;
+
+    /// Find the closest point by projecting onto every segment
+    fn project_everywhere(path: &Path<U3>, point: &TestCoord3) -> (f64, f64) {
+        path.segments
+            .iter()
+            .map(|segment| {
+                let distance = segment.project(point);
+
+                (distance, (segment.position(distance) - point).norm())
+            })
+            .fold((0.0, std::f64::INFINITY), |closest, candidate| {
+                if candidate.1 < closest.1 {
+                    candidate
+                } else {
+                    closest
+                }
+            })
+    }
+
+    /// Check points on a path project back onto themselves
+    fn assert_projects_onto_itself(path: &Path<U3>) {
+        for i in 0..=200 {
+            let distance = path.len() * i as f64 / 200.0;
+            let (projected, position, error) = path.project(&path.position(distance));
+
+            assert_near!(projected, distance);
+            assert_near!(position, path.position(distance));
+            assert_near!(error, 0.0);
+        }
+    }
+
+    fn line(start: TestCoord3, end: TestCoord3) -> PathSegment<U3> {
+        PathSegment::Linear(LinearPathSegment::from_waypoints(start, end))
+    }
+
+    #[test]
+    fn project_onto_lines() {
+        let path = Path::from_segments(vec![
+            line(TestCoord3::zeros(), TestCoord3::new(2.0, 0.0, 0.0)),
+            line(
+                TestCoord3::new(2.0, 0.0, 0.0),
+                TestCoord3::new(2.0, 2.0, 0.0),
+            ),
+        ]);
+
+        let (distance, position, error) = path.project(&TestCoord3::new(1.0, -0.5, 0.5));
+
+        assert_near!(distance, 1.0);
+        assert_near!(position, TestCoord3::new(1.0, 0.0, 0.0));
+        assert_near!(error, 0.5f64.sqrt());
+
+        let (distance, position, error) = path.project(&TestCoord3::new(3.0, 1.5, 0.0));
+
+        assert_near!(distance, 3.5);
+        assert_near!(position, TestCoord3::new(2.0, 1.5, 0.0));
+        assert_near!(error, 1.0);
+
+        // Points beyond either end project onto the end
+        let (distance, _, error) = path.project(&TestCoord3::new(-1.0, 0.0, 0.0));
+
+        assert_near!(distance, 0.0);
+        assert_near!(error, 1.0);
+
+        let (distance, _, error) = path.project(&TestCoord3::new(2.0, 3.0, 0.0));
+
+        assert_near!(distance, 4.0);
+        assert_near!(error, 1.0);
+    }
+
+    #[test]
+    fn project_onto_arc() {
+        // Half of a unit circle around the origin, from +X through +Y to -X
+        let arc = CircularPathSegment::from_arc(
+            &TestCoord3::x(),
+            &-TestCoord3::x(),
+            &TestCoord3::zeros(),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+        )
+        .unwrap();
+
+        let path = Path::from_segments(vec![PathSegment::Circular(arc)]);
+
+        // Outside the circle, above the plane of the arc
+        let (distance, position, error) = path.project(&TestCoord3::new(
+            2.0 * (PI / 3.0).cos(),
+            2.0 * (PI / 3.0).sin(),
+            1.0,
+        ));
+
+        assert_near!(distance, PI / 3.0);
+        assert_near!(
+            position,
+            TestCoord3::new((PI / 3.0).cos(), (PI / 3.0).sin(), 0.0)
+        );
+        assert_near!(error, 2.0f64.sqrt());
+
+        // Inside the circle
+        let (distance, _, error) = path.project(&TestCoord3::new(0.0, 0.25, 0.0));
+
+        assert_near!(distance, PI / 2.0);
+        assert_near!(error, 0.75);
+
+        // Beyond the arc, nearer each end
+        let (distance, position, _) = path.project(&TestCoord3::new(0.5, -1.0, 0.0));
+
+        assert_near!(distance, 0.0);
+        assert_near!(position, TestCoord3::x());
+
+        let (distance, position, _) = path.project(&TestCoord3::new(-0.5, -1.0, 0.0));
+
+        assert_near!(distance, PI);
+        assert_near!(position, -TestCoord3::x());
+
+        assert_projects_onto_itself(&path);
+    }
+
+    #[test]
+    fn project_onto_curves() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+
+        let segments = vec![
+            PathSegment::Helix(
+                HelixPathSegment::new(
+                    &start,
+                    &TestCoord3::zeros(),
+                    &TestCoord3::z(),
+                    (0, 1),
+                    ArcDirection::Counterclockwise,
+                    0.5,
+                    2.5,
+                )
+                .unwrap(),
+            ),
+            PathSegment::Spline(
+                SplinePathSegment::from_waypoints(&[
+                    start,
+                    TestCoord3::new(0.0, 1.0, 0.5),
+                    TestCoord3::new(-1.0, 2.0, 1.0),
+                    TestCoord3::new(-2.0, 2.5, 1.0),
+                ])
+                .unwrap(),
+            ),
+            PathSegment::BSpline(
+                BSplinePathSegment::new(
+                    2,
+                    &[
+                        start,
+                        TestCoord3::new(1.0, 1.0, 0.0),
+                        TestCoord3::new(0.0, 1.0, 0.5),
+                        TestCoord3::new(0.0, 2.0, 1.0),
+                    ],
+                    &[1.0, 2.0, 0.5, 1.0],
+                    &[0.0, 0.0, 0.0, 0.3, 1.0, 1.0, 1.0],
+                )
+                .unwrap(),
+            ),
+            PathSegment::custom(QuarterCircle),
+        ];
+
+        for segment in segments {
+            assert_projects_onto_itself(&Path::from_segments(vec![segment]));
+        }
+
+        // Closest point on the circle around a helix turn, checked from off its axis
+        let helix = Path::from_segments(vec![PathSegment::Helix(
+            HelixPathSegment::new(
+                &start,
+                &TestCoord3::zeros(),
+                &TestCoord3::z(),
+                (0, 1),
+                ArcDirection::Counterclockwise,
+                0.5,
+                2.5,
+            )
+            .unwrap(),
+        )]);
+
+        let on_helix = helix.position(helix.len() * 0.6);
+        let outside = TestCoord3::new(on_helix.x * 2.0, on_helix.y * 2.0, on_helix.z);
+        let (distance, _, error) = helix.project(&outside);
+
+        assert_near!(error, project_everywhere(&helix, &outside).1);
+        assert!((distance - helix.len() * 0.6).abs() < 0.01);
+    }
+
+    #[test]
+    fn project_onto_blended_path() {
+        let waypoints = waypoints_fixture();
+
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let path = Path::from_waypoints(
+                &waypoints,
+                PathOptions {
+                    max_deviation: 0.1,
+                    blend: *blend,
+                    ..PathOptions::default()
+                },
+            );
+
+            assert_projects_onto_itself(&path);
+
+            // Points around the path find the same distance as checking every segment
+            for x in -2..=12 {
+                for y in -2..=14 {
+                    let point = TestCoord3::new(x as f64 * 0.5, y as f64 * 0.5, 0.5);
+                    let (_, position, error) = path.project(&point);
+                    let (expected_distance, expected_error) = project_everywhere(&path, &point);
+
+                    assert_near!(error, expected_error);
+                    assert_near!(position, path.position(expected_distance));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn project_onto_long_path() {
+        let waypoints = (0..2000)
+            .map(|i| {
+                let angle = i as f64 * 0.1;
+
+                TestCoord3::new(
+                    angle.cos() * (1.0 + angle),
+                    angle.sin() * (1.0 + angle),
+                    angle,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        for i in 0..100 {
+            let point = TestCoord3::new(
+                (i as f64 * 0.7).cos() * i as f64 * 2.0,
+                (i as f64 * 1.3).sin() * i as f64 * 2.0,
+                i as f64 * 2.0,
+            );
+
+            assert_near!(path.project(&point).2, project_everywhere(&path, &point).1);
+        }
+
+        // A clone shares the index and finds the same points
+        let clone = path.clone();
+        let point = TestCoord3::new(10.0, -5.0, 20.0);
+
+        assert_eq!(clone.project(&point), path.project(&point));
+    }
+
+    #[test]
+    fn equally_close_points() {
+        let path = Path::from_segments(vec![
+            line(TestCoord3::zeros(), TestCoord3::x()),
+            line(TestCoord3::x(), TestCoord3::new(1.0, 1.0, 0.0)),
+            line(TestCoord3::new(1.0, 1.0, 0.0), TestCoord3::y()),
+        ]);
+
+        // Half way between the first and last segments, so the start of the path is chosen
+        let (distance, position, error) = path.project(&TestCoord3::new(0.0, 0.5, 0.0));
+
+        assert_near!(distance, 0.0);
+        assert_near!(position, TestCoord3::zeros());
+        assert_near!(error, 0.5);
+    }
+
+    #[test]
+    fn project_after_edit() {
+        let mut path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 2.0, 0.0),
+                TestCoord3::new(3.0, 2.0, 1.0),
+            ],
+            PathOptions::default(),
+        );
+
+        let point = TestCoord3::new(5.0, 2.0, 1.0);
+
+        assert_near!(path.project(&point).2, 2.0);
+
+        // The index is rebuilt after the path changes
+        path.push_waypoint(TestCoord3::new(5.0, 3.0, 1.0)).unwrap();
+
+        assert_near!(path.project(&point).2, project_everywhere(&path, &point).1);
+        assert!(path.project(&point).2 < 1.0);
+    }
+
+    #[test]
+    fn project_after_editing_segments() {
+        let mut path = Path::from_segments(vec![
+            line(TestCoord3::zeros(), TestCoord3::x()),
+            line(TestCoord3::x(), TestCoord3::new(1.0, 1.0, 0.0)),
+        ]);
+
+        let point = TestCoord3::new(1.0, 3.0, 0.0);
+
+        assert_near!(path.project(&point).2, 2.0);
+
+        // Segments edited directly, without going through a method that resets the index
+        path.segments.push(
+            line(
+                TestCoord3::new(1.0, 1.0, 0.0),
+                TestCoord3::new(1.0, 3.0, 0.0),
+            )
+            .with_start_offset(2.0),
+        );
+
+        let (distance, position, error) = path.project(&point);
+
+        assert_near!(distance, 4.0);
+        assert_near!(position, point);
+        assert_near!(error, 0.0);
+
+        // Same number of segments, but the last one is shorter
+        path.segments[2] = line(
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 2.0, 0.0),
+        )
+        .with_start_offset(2.0);
+
+        assert_near!(path.project(&point).2, 1.0);
+    }
+}
@@ -0,0 +1,237 @@
+//! Traversing a path backwards
+
+use super::{Path, PathSwitchingPoint, SegmentIndexCache};
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get this path traversed from end to start
+    ///
+    /// Segments are reversed in place as described in `PathSegment::reversed`, so blends are the
+    /// same as the original path's instead of being recreated. A switching point at `s` along this
+    /// path is at `length - s` along the reversed path, with the same continuity. Waypoints are
+    /// kept in reverse order, so the reversed path can still be edited.
+    pub fn reversed(&self) -> Self {
+        let length = self.length;
+
+        let segments = self
+            .segments
+            .iter()
+            .rev()
+            .map(|segment| {
+                segment
+                    .reversed()
+                    .with_start_offset(length - segment.end_offset())
+            })
+            .collect();
+
+        let switching_points = self
+            .switching_points
+            .iter()
+            .rev()
+            .map(|point| PathSwitchingPoint::new(length - point.position, point.continuity.clone()))
+            .collect();
+
+        let mut waypoints = self.waypoints.clone();
+
+        waypoints.reverse();
+
+        Self {
+            segments,
+            length,
+            switching_points,
+            waypoints,
+            options: self.options,
+            index: SegmentIndexCache::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{
+        ArcDirection, BSplinePathSegment, Blend, CircularPathSegment, HelixPathSegment,
+        LinearPathSegment, PathItem, PathOptions, PathSegment, SplinePathSegment, Trajectory,
+    };
+
+    /// Check a reversed path follows the original path backwards
+    fn assert_reversed(path: &Path<nalgebra::U3>) {
+        let reversed = path.reversed();
+        let length = path.len();
+
+        assert_near!(reversed.len(), length);
+        assert_near!(reversed.segments[0].start_offset(), 0.0);
+
+        for pair in reversed.segments.windows(2) {
+            assert_near!(pair[0].end_offset(), pair[1].start_offset());
+        }
+
+        for i in 0..=200 {
+            let distance = length * i as f64 / 200.0;
+
+            assert_near!(
+                reversed.position(distance),
+                path.position(length - distance)
+            );
+            assert_near!(reversed.tangent(distance), -path.tangent(length - distance));
+
+            // Curvature can jump at either end of a path segment
+            if i > 0 && i < 200 {
+                assert_near!(
+                    reversed.curvature(distance),
+                    path.curvature(length - distance)
+                );
+            }
+        }
+
+        assert_eq!(
+            reversed.switching_points().len(),
+            path.switching_points().len()
+        );
+
+        for (a, b) in reversed
+            .switching_points()
+            .iter()
+            .zip(path.switching_points().iter().rev())
+        {
+            assert_near!(a.position, length - b.position);
+            assert_eq!(a.continuity, b.continuity);
+        }
+    }
+
+    #[test]
+    fn reverse_segments() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+
+        let segments = vec![
+            PathSegment::Linear(LinearPathSegment::from_waypoints(
+                start,
+                TestCoord3::new(2.0, 3.0, -1.0),
+            )),
+            PathSegment::Circular(
+                CircularPathSegment::from_arc(
+                    &start,
+                    &TestCoord3::new(0.0, -1.0, 0.0),
+                    &TestCoord3::zeros(),
+                    (0, 1),
+                    ArcDirection::Counterclockwise,
+                )
+                .unwrap(),
+            ),
+            PathSegment::Helix(
+                HelixPathSegment::new(
+                    &start,
+                    &TestCoord3::zeros(),
+                    &TestCoord3::z(),
+                    (0, 1),
+                    ArcDirection::Clockwise,
+                    0.5,
+                    1.25,
+                )
+                .unwrap(),
+            ),
+            PathSegment::Spline(
+                SplinePathSegment::from_waypoints(&[
+                    start,
+                    TestCoord3::new(0.0, 1.0, 0.5),
+                    TestCoord3::new(-1.0, 2.0, 1.0),
+                    TestCoord3::new(-2.0, 2.5, 1.0),
+                ])
+                .unwrap(),
+            ),
+            PathSegment::BSpline(
+                BSplinePathSegment::new(
+                    2,
+                    &[
+                        start,
+                        TestCoord3::new(1.0, 1.0, 0.0),
+                        TestCoord3::new(0.0, 1.0, 0.5),
+                        TestCoord3::new(0.0, 2.0, 1.0),
+                    ],
+                    &[1.0, 2.0, 0.5, 1.0],
+                    &[0.0, 0.0, 0.0, 0.3, 1.0, 1.0, 1.0],
+                )
+                .unwrap(),
+            ),
+            PathSegment::custom(QuarterCircle),
+        ];
+
+        for segment in segments {
+            assert_reversed(&Path::from_segments(vec![segment]));
+        }
+    }
+
+    #[test]
+    fn reverse_blended_path() {
+        let waypoints = waypoints_fixture();
+
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let options = PathOptions {
+                max_deviation: 0.1,
+                blend: *blend,
+                ..PathOptions::default()
+            };
+
+            let path = Path::from_waypoints(&waypoints, options);
+
+            assert_reversed(&path);
+
+            // Reversing twice gives the original path back
+            let twice = path.reversed().reversed();
+
+            assert_eq!(twice.waypoints(), path.waypoints());
+
+            for i in 0..=100 {
+                let distance = path.len() * i as f64 / 100.0;
+
+                assert_near!(twice.position(distance), path.position(distance));
+            }
+
+            // Blends are the same as those of a path created from the reversed waypoints
+            let reversed = path.reversed();
+            let rebuilt = Path::from_waypoints(reversed.waypoints(), options);
+
+            assert_near!(rebuilt.len(), path.len());
+
+            for i in 0..=100 {
+                let distance = path.len() * i as f64 / 100.0;
+
+                assert_near!(rebuilt.position(distance), reversed.position(distance));
+            }
+        }
+    }
+
+    #[test]
+    fn plan_reversed_path() {
+        let path = Path::from_waypoints(
+            &[
+                TestCoord3::new(0.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 2.0, 0.0),
+                TestCoord3::new(3.0, 2.0, 1.0),
+            ],
+            PathOptions::default(),
+        );
+
+        let options = trajectory_options_fixture();
+
+        let reversed = path.reversed();
+        let trajectory = Trajectory::new(&reversed, options).unwrap();
+
+        assert_near!(trajectory.position(0.0), TestCoord3::new(3.0, 2.0, 1.0));
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            TestCoord3::zeros()
+        );
+    }
+}
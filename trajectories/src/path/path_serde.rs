@@ -0,0 +1,288 @@
+//! Serde support for `Path`
+
+use super::{Path, PathOptions, PathSegment, PathSwitchingPoint, SegmentIndexCache};
+use crate::envelope;
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Borrowed serialized representation of a path
+#[derive(Serialize)]
+#[serde(rename = "Path", bound(serialize = "Coord<N>: serde::Serialize"))]
+struct PathRef<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    segments: &'a [PathSegment<N>],
+    length: f64,
+    switching_points: &'a [PathSwitchingPoint],
+    waypoints: &'a [Coord<N>],
+    options: &'a PathOptions<N>,
+}
+
+/// Owned serialized representation of a path
+#[derive(Deserialize)]
+#[serde(
+    rename = "Path",
+    bound(deserialize = "Coord<N>: serde::Deserialize<'de>")
+)]
+struct PathData<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    segments: Vec<PathSegment<N>>,
+    length: f64,
+    switching_points: Vec<PathSwitchingPoint>,
+    waypoints: Vec<Coord<N>>,
+    options: PathOptions<N>,
+}
+
+impl<N> Serialize for Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+    Coord<N>: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        envelope::serialize(
+            &PathRef {
+                segments: &self.segments,
+                length: self.length,
+                switching_points: &self.switching_points,
+                waypoints: &self.waypoints,
+                options: &self.options,
+            },
+            serializer,
+        )
+    }
+}
+
+impl<'de, N> Deserialize<'de> for Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+    Coord<N>: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let PathData {
+            segments,
+            length,
+            switching_points,
+            waypoints,
+            options,
+        } = envelope::deserialize(deserializer)?;
+
+        Ok(Self {
+            segments,
+            length,
+            switching_points,
+            waypoints,
+            options,
+            index: SegmentIndexCache::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{Blend, PathItem, PathOptions, PathSegment, PathTransform};
+
+    #[test]
+    fn json_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let json = serde_json::to_string(&path).unwrap();
+        let deserialized: Path<nalgebra::U3> = serde_json::from_str(&json).unwrap();
+
+        // JSON doesn't round trip floats exactly
+        assert_eq!(deserialized.segments.len(), path.segments.len());
+        assert_near!(deserialized.len(), path.len());
+
+        for (a, b) in deserialized
+            .switching_points()
+            .iter()
+            .zip(path.switching_points())
+        {
+            assert_near!(a.position, b.position);
+            assert_eq!(a.continuity, b.continuity);
+        }
+
+        for i in 0..=100 {
+            let position = path.len() * i as f64 / 100.0;
+
+            assert_near!(deserialized.position(position), path.position(position));
+            assert_near!(deserialized.tangent(position), path.tangent(position));
+        }
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let bytes = bincode::serialize(&path).unwrap();
+        let deserialized: Path<nalgebra::U3> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(deserialized, path);
+    }
+
+    #[test]
+    fn sliced_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                blend: Blend::Clothoid,
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
+
+        // Both ends of the slice lie in clothoid blends
+        let slice = path.slice(
+            path.segments[1].start_offset() + path.segments[1].len() / 2.0,
+            path.segments[3].start_offset() + path.segments[3].len() / 2.0,
+        );
+
+        let bytes = bincode::serialize(&slice).unwrap();
+        let deserialized: Path<nalgebra::U3> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(deserialized, slice);
+    }
+
+    #[test]
+    fn sheared_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let transform = PathTransform::from_columns(
+            &[
+                TestCoord3::x(),
+                TestCoord3::new(0.5, 1.0, 0.0),
+                TestCoord3::z(),
+            ],
+            &TestCoord3::zeros(),
+        )
+        .unwrap();
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default()).transformed(&transform);
+
+        assert!(path.segments.iter().any(|segment| match segment {
+            PathSegment::Transformed(_) => true,
+            _ => false,
+        }));
+
+        let bytes = bincode::serialize(&path).unwrap();
+        let deserialized: Path<nalgebra::U3> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(deserialized, path);
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let mut json: serde_json::Value = serde_json::to_value(&path).unwrap();
+
+        assert_eq!(json["version"], envelope::FORMAT_VERSION);
+
+        json["version"] = (envelope::FORMAT_VERSION + 1).into();
+
+        let error = serde_json::from_value::<Path<nalgebra::U3>>(json).unwrap_err();
+
+        assert!(error.to_string().contains("unsupported format version"));
+
+        let mut bytes = bincode::serialize(&path).unwrap();
+
+        // Version is the first field of the envelope
+        bytes[0] += 1;
+
+        assert!(bincode::deserialize::<Path<nalgebra::U3>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn older_version() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                axis_weights: Some(TestCoord3::new(1.0, 1.0, 0.1)),
+                axis_periods: Some(TestCoord3::new(0.0, 0.0, 360.0)),
+                ..PathOptions::default()
+            },
+        );
+
+        // Version 2 stored path options without axis weights or periods
+        let mut json: serde_json::Value = serde_json::to_value(&path).unwrap();
+
+        json["version"] = 2.into();
+
+        let error = serde_json::from_value::<Path<nalgebra::U3>>(json).unwrap_err();
+
+        assert!(error.to_string().contains("unsupported format version 2"));
+
+        let mut bytes = bincode::serialize(&path).unwrap();
+
+        bytes[0] = 2;
+
+        assert!(bincode::deserialize::<Path<nalgebra::U3>>(&bytes).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Extracting part of a path
+
+use super::{Path, PathError, PathOptions, PathSwitchingPoint, SegmentIndexCache};
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the part of this path between two distances along it
+    ///
+    /// Segments at either end are trimmed as described in `PathSegment::trim`, and segments in
+    /// between are kept as they are. The new path starts at zero, and keeps the switching points
+    /// that lie between the two distances. It has no waypoints, so cannot be edited, but keeps the
+    /// periodic axes used by `Path::wrap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice does not lie within the path or ends before it starts. Use
+    /// `Path::try_slice` to get an error instead.
+    pub fn slice(&self, start: f64, end: f64) -> Self {
+        self.try_slice(start, end)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Get the part of this path between two distances along it, returning an error if the slice
+    /// does not lie within the path or ends before it starts
+    pub fn try_slice(&self, start: f64, end: f64) -> Result<Self, PathError> {
+        if !(start >= 0.0 && start < end && end <= self.length) {
+            return Err(PathError::InvalidSlice { start, end });
+        }
+
+        let segments = self
+            .segments
+            .iter()
+            .filter(|segment| segment.end_offset() > start && segment.start_offset() < end)
+            .map(|segment| {
+                let from = start.max(segment.start_offset());
+                let to = end.min(segment.end_offset());
+
+                let segment = if from > segment.start_offset() || to < segment.end_offset() {
+                    segment.trim(from, to)
+                } else {
+                    segment.clone()
+                };
+
+                segment.with_start_offset(from - start)
+            })
+            .collect::<Vec<_>>();
+
+        let switching_points = self
+            .switching_points
+            .iter()
+            .filter(|point| point.position > start && point.position < end)
+            .map(|point| PathSwitchingPoint::new(point.position - start, point.continuity.clone()))
+            .collect();
+
+        let length = segments
+            .last()
+            .map(|segment| segment.end_offset())
+            .unwrap_or(0.0);
+
+        Ok(Self {
+            segments,
+            length,
+            switching_points,
+            waypoints: Vec::new(),
+            options: PathOptions {
+                axis_periods: self.options.axis_periods,
+                ..PathOptions::default()
+            },
+            index: SegmentIndexCache::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{
+        ArcDirection, Blend, HelixPathSegment, LinearPathSegment, PathItem, PathSegment,
+        PathTransform, SplinePathSegment, Trajectory,
+    };
+
+    /// Check a slice follows the original path between two distances
+    fn assert_matches_original(path: &Path<nalgebra::U3>, start: f64, end: f64) {
+        let slice = path.slice(start, end);
+
+        assert_near!(slice.len(), end - start);
+        assert_near!(slice.segments[0].start_offset(), 0.0);
+
+        for pair in slice.segments.windows(2) {
+            assert_near!(pair[0].end_offset(), pair[1].start_offset());
+            assert_near!(
+                pair[0].position(pair[0].end_offset()),
+                pair[1].position(pair[1].start_offset())
+            );
+        }
+
+        for i in 0..=100 {
+            let distance = (end - start) * i as f64 / 100.0;
+
+            assert_near!(slice.position(distance), path.position(start + distance));
+            assert_near!(slice.tangent(distance), path.tangent(start + distance));
+
+            // Curvature can jump where a slice ends on a junction, so only check inside it
+            if i > 0 && i < 100 {
+                assert_near!(slice.curvature(distance), path.curvature(start + distance));
+            }
+        }
+
+        let expected = path
+            .switching_points()
+            .iter()
+            .filter(|point| point.position > start && point.position < end)
+            .collect::<Vec<_>>();
+
+        assert_eq!(slice.switching_points().len(), expected.len());
+
+        for (a, b) in slice.switching_points().iter().zip(expected) {
+            assert_near!(a.position, b.position - start);
+            assert_eq!(a.continuity, b.continuity);
+        }
+    }
+
+    #[test]
+    fn slice_blended_path() {
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let path = Path::from_waypoints(
+                &waypoints_fixture(),
+                PathOptions {
+                    max_deviation: 0.1,
+                    blend: *blend,
+                    ..PathOptions::default()
+                },
+            );
+
+            // Boundaries in linear segments and in blends
+            for segment in 1..path.segments.len() - 1 {
+                let start = path.segments[segment - 1].start_offset()
+                    + path.segments[segment - 1].len() / 3.0;
+                let end = path.segments[segment + 1].start_offset()
+                    + path.segments[segment + 1].len() / 2.0;
+
+                assert_matches_original(&path, start, end);
+            }
+
+            // Within a single segment
+            let blend = &path.segments[3];
+
+            assert_matches_original(
+                &path,
+                blend.start_offset() + blend.len() * 0.25,
+                blend.start_offset() + blend.len() * 0.75,
+            );
+
+            // The whole path
+            assert_eq!(path.slice(0.0, path.len()).segments, path.segments);
+        }
+    }
+
+    #[test]
+    fn slice_curves() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+
+        let path = Path::from_segments(vec![
+            PathSegment::Helix(
+                HelixPathSegment::new(
+                    &start,
+                    &TestCoord3::zeros(),
+                    &TestCoord3::z(),
+                    (0, 1),
+                    ArcDirection::Counterclockwise,
+                    0.5,
+                    1.25,
+                )
+                .unwrap(),
+            ),
+            PathSegment::Spline(
+                SplinePathSegment::from_waypoints(&[
+                    TestCoord3::new(0.0, 1.0, 0.625),
+                    TestCoord3::new(-1.0, 2.0, 1.0),
+                    TestCoord3::new(-2.0, 2.5, 1.0),
+                ])
+                .unwrap(),
+            ),
+        ]);
+
+        let start = path.segments[0].len() * 0.3;
+        let end = path.segments[1].start_offset() + path.segments[1].len() * 0.6;
+
+        assert_matches_original(&path, start, end);
+
+        let slice = path.slice(start, end);
+
+        match &slice.segments[..] {
+            [PathSegment::Helix(_), PathSegment::Trimmed(_)] => (),
+            segments => panic!("Unexpected segments {:?}", segments),
+        }
+    }
+
+    #[test]
+    fn slice_trimmed_segments() {
+        let path = Path::from_waypoints(
+            &waypoints_fixture(),
+            PathOptions {
+                max_deviation: 0.1,
+                blend: Blend::Clothoid,
+                ..PathOptions::default()
+            },
+        );
+
+        let blend = &path.segments[3];
+        let start = blend.start_offset() + blend.len() * 0.25;
+        let slice = path.slice(start, blend.start_offset() + blend.len() * 0.75);
+
+        match &slice.segments[..] {
+            [PathSegment::Trimmed(trimmed)] => {
+                assert_eq!(*trimmed.segment, *blend);
+            }
+            segments => panic!("Unexpected segments {:?}", segments),
+        }
+
+        // Slicing a slice trims the original segment again
+        let inner = slice.slice(slice.len() * 0.2, slice.len() * 0.6);
+
+        match &inner.segments[..] {
+            [PathSegment::Trimmed(trimmed)] => assert_eq!(*trimmed.segment, *blend),
+            segments => panic!("Unexpected segments {:?}", segments),
+        }
+
+        for i in 0..=10 {
+            let distance = inner.len() * i as f64 / 10.0;
+
+            assert_near!(
+                inner.position(distance),
+                path.position(start + slice.len() * 0.2 + distance)
+            );
+        }
+
+        // Reversing and transforming keep the trimmed part
+        let reversed = slice.reversed();
+        let transform = PathTransform::rotation((0, 1), 0.5).then(&PathTransform::scaling(2.0));
+        let transformed = slice.transformed(&transform);
+
+        assert_near!(transformed.len(), slice.len() * 2.0);
+
+        for i in 0..=10 {
+            let distance = slice.len() * i as f64 / 10.0;
+
+            assert_near!(
+                reversed.position(slice.len() - distance),
+                slice.position(distance)
+            );
+            assert_near!(
+                transformed.position(distance * 2.0),
+                transform.transform_point(&slice.position(distance))
+            );
+        }
+    }
+
+    #[test]
+    fn plan_slice() {
+        let path = Path::from_waypoints(&waypoints_fixture(), PathOptions::default());
+        let slice = path.slice(1.0, path.len() - 1.0);
+
+        let options = trajectory_options_fixture();
+
+        let trajectory = Trajectory::new(&slice, options).unwrap();
+
+        assert_near!(trajectory.position(0.0), path.position(1.0));
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            path.position(path.len() - 1.0)
+        );
+    }
+
+    #[test]
+    fn invalid_slices() {
+        let path = Path::from_segments(vec![PathSegment::Linear(
+            LinearPathSegment::from_waypoints(TestCoord3::zeros(), TestCoord3::x()),
+        )]);
+
+        assert_eq!(
+            path.try_slice(0.5, 0.5),
+            Err(PathError::InvalidSlice {
+                start: 0.5,
+                end: 0.5
+            })
+        );
+        assert_eq!(
+            path.try_slice(-0.1, 0.5),
+            Err(PathError::InvalidSlice {
+                start: -0.1,
+                end: 0.5
+            })
+        );
+        assert_eq!(
+            path.try_slice(0.5, 1.5),
+            Err(PathError::InvalidSlice {
+                start: 0.5,
+                end: 1.5
+            })
+        );
+        assert!(path.try_slice(0.0, std::f64::NAN).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use super::Continuity;
 
 /// A switching point
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PathSwitchingPoint {
     /// Position along the path at which this switching point occurs
     pub position: f64,
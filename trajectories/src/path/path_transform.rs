@@ -0,0 +1,784 @@
+use super::{
+    Continuity, Path, PathError, PathItem, PathOptions, PathSegment, PathSwitchingPoint,
+    SegmentIndexCache,
+};
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName, Isometry2, Isometry3, MatrixN, U2, U3,
+};
+
+/// An affine transform that can be applied to a path
+///
+/// A point `p` is moved to `linear * p + translation`. The linear part must be invertible, and may
+/// rotate, scale, mirror or shear the path.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct PathTransform<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Each column of the linear part, which is where each axis unit vector is moved to
+    columns: Vec<Coord<N>>,
+
+    /// Translation applied after the linear part
+    translation: Coord<N>,
+}
+
+impl<N> PathTransform<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a transform from its linear part and a translation
+    ///
+    /// Returns `PathError::InvalidTransform` if the linear part is not invertible or either part
+    /// contains a NaN or infinite value.
+    pub fn new(linear: &MatrixN<f64, N>, translation: &Coord<N>) -> Result<Self, PathError>
+    where
+        DefaultAllocator: Allocator<f64, N, N>,
+    {
+        let columns = (0..N::dim())
+            .map(|axis| linear.column(axis).into_owned())
+            .collect::<Vec<_>>();
+
+        Self::from_columns(&columns, translation)
+    }
+
+    /// Create a transform from the columns of its linear part and a translation
+    ///
+    /// Each column is where the unit vector along that axis is moved to. Returns
+    /// `PathError::InvalidTransform` if there is not one column for each axis, the columns are not
+    /// linearly independent or any value is NaN or infinite.
+    pub fn from_columns(columns: &[Coord<N>], translation: &Coord<N>) -> Result<Self, PathError> {
+        if columns.len() != N::dim()
+            || columns
+                .iter()
+                .chain(Some(translation))
+                .any(|column| column.iter().any(|c| !c.is_finite()))
+        {
+            return Err(PathError::InvalidTransform);
+        }
+
+        // Gram-Schmidt: each column must have some part left over once the parts along every
+        // previous column are removed
+        let mut basis: Vec<Coord<N>> = Vec::with_capacity(columns.len());
+
+        for column in columns {
+            let residual = basis.iter().fold(column.clone(), |residual, unit| {
+                &residual - unit * unit.dot(&residual)
+            });
+
+            match residual.try_normalize(TRAJECTORY_EPSILON * column.norm().max(1.0)) {
+                Some(unit) => basis.push(unit),
+                None => return Err(PathError::InvalidTransform),
+            }
+        }
+
+        Ok(Self::from_parts(columns.to_vec(), translation.clone()))
+    }
+
+    fn from_parts(columns: Vec<Coord<N>>, translation: Coord<N>) -> Self {
+        Self {
+            columns,
+            translation,
+        }
+    }
+
+    /// Create a transform that leaves every point where it is
+    pub fn identity() -> Self {
+        Self::from_parts(
+            (0..N::dim())
+                .map(|axis| {
+                    let mut column = Coord::zeros();
+                    column[axis] = 1.0;
+
+                    column
+                })
+                .collect(),
+            Coord::zeros(),
+        )
+    }
+
+    /// Create a transform that moves every point by `offset`
+    pub fn translation(offset: &Coord<N>) -> Self {
+        Self {
+            translation: offset.clone(),
+            ..Self::identity()
+        }
+    }
+
+    /// Create a transform that scales every point away from the origin by `factor`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is zero, NaN or infinite.
+    pub fn scaling(factor: f64) -> Self {
+        assert!(
+            factor.is_finite() && factor != 0.0,
+            "{}",
+            PathError::InvalidTransform
+        );
+
+        let identity = Self::identity();
+
+        Self::from_parts(
+            identity
+                .columns
+                .iter()
+                .map(|column| column * factor)
+                .collect(),
+            identity.translation,
+        )
+    }
+
+    /// Create a transform that rotates by `angle` radians around the origin, in the plane formed
+    /// by axes `plane.0` and `plane.1`
+    ///
+    /// A positive angle rotates `plane.0` towards `plane.1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the axes are the same or either is out of range.
+    pub fn rotation(plane: (usize, usize), angle: f64) -> Self {
+        let (a, b) = plane;
+
+        assert!(
+            a != b && a < N::dim() && b < N::dim(),
+            "{}",
+            PathError::InvalidTransform
+        );
+
+        let (angle_s, angle_c) = angle.sin_cos();
+        let mut transform = Self::identity();
+
+        transform.columns[a][a] = angle_c;
+        transform.columns[a][b] = angle_s;
+        transform.columns[b][a] = -angle_s;
+        transform.columns[b][b] = angle_c;
+
+        transform
+    }
+
+    /// Create a transform that mirrors every point in the plane through the origin normal to
+    /// `axis`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is out of range.
+    pub fn mirror(axis: usize) -> Self {
+        assert!(axis < N::dim(), "{}", PathError::InvalidTransform);
+
+        let mut transform = Self::identity();
+
+        transform.columns[axis][axis] = -1.0;
+
+        transform
+    }
+
+    /// Create a transform that applies this transform followed by `next`
+    pub fn then(&self, next: &Self) -> Self {
+        Self::from_parts(
+            self.columns
+                .iter()
+                .map(|column| next.transform_vector(column))
+                .collect(),
+            next.transform_point(&self.translation),
+        )
+    }
+
+    /// Apply this transform to a point
+    pub fn transform_point(&self, point: &Coord<N>) -> Coord<N> {
+        self.transform_vector(point) + &self.translation
+    }
+
+    /// Apply the linear part of this transform to a vector
+    pub fn transform_vector(&self, vector: &Coord<N>) -> Coord<N> {
+        self.columns
+            .iter()
+            .zip(vector.iter())
+            .fold(Coord::zeros(), |result, (column, component)| {
+                result + column * *component
+            })
+    }
+
+    /// Get the factor every length is scaled by, or `None` if lengths are scaled by different
+    /// amounts in different directions
+    ///
+    /// Rotations, translations, mirrors and uniform scaling keep the shape of a path, so arcs stay
+    /// arcs.
+    pub fn similarity_scale(&self) -> Option<f64> {
+        let scale = self.columns[0].norm();
+        let tolerance = TRAJECTORY_EPSILON * scale.powi(2);
+
+        let similar = self.columns.iter().enumerate().all(|(index, column)| {
+            self.columns[index..]
+                .iter()
+                .enumerate()
+                .all(|(offset, other)| {
+                    let expected = if offset == 0 { scale.powi(2) } else { 0.0 };
+
+                    (column.dot(other) - expected).abs() <= tolerance
+                })
+        });
+
+        if similar {
+            Some(scale)
+        } else {
+            None
+        }
+    }
+
+    /// Whether each axis is only scaled or mirrored, staying on itself
+    fn is_diagonal(&self) -> bool {
+        self.columns.iter().enumerate().all(|(axis, column)| {
+            column
+                .iter()
+                .enumerate()
+                .all(|(other, c)| other == axis || c.abs() <= TRAJECTORY_EPSILON * column.norm())
+        })
+    }
+
+    /// Whether each axis is moved onto a single axis, so a component of a vector is zero after the
+    /// transform exactly when a component was zero before it
+    fn preserves_axes(&self) -> bool {
+        self.columns.iter().all(|column| {
+            column
+                .iter()
+                .filter(|c| c.abs() > TRAJECTORY_EPSILON * column.norm())
+                .count()
+                == 1
+        })
+    }
+}
+
+impl From<Isometry2<f64>> for PathTransform<U2> {
+    fn from(isometry: Isometry2<f64>) -> Self {
+        let rotation = isometry.rotation.to_rotation_matrix();
+
+        Self::from_parts(
+            (0..2)
+                .map(|axis| rotation.matrix().column(axis).into_owned())
+                .collect(),
+            isometry.translation.vector,
+        )
+    }
+}
+
+impl From<Isometry3<f64>> for PathTransform<U3> {
+    fn from(isometry: Isometry3<f64>) -> Self {
+        let rotation = isometry.rotation.to_rotation_matrix();
+
+        Self::from_parts(
+            (0..3)
+                .map(|axis| rotation.matrix().column(axis).into_owned())
+                .collect(),
+            isometry.translation.vector,
+        )
+    }
+}
+
+impl<N> Path<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get this path moved by an affine transform
+    ///
+    /// Segments are transformed in place as described in `PathSegment::transformed`, so blends
+    /// keep their shape instead of being recreated with an absolute `max_deviation`. Switching
+    /// points at junctions between segments are kept. Switching points inside segments are
+    /// scaled along with the path if the transform keeps each axis on a single axis, and are
+    /// recomputed from the transformed segments otherwise.
+    ///
+    /// Waypoints are transformed and `max_deviation` is scaled if the transform keeps the shape of
+    /// the path, and keeps each axis on itself if the path has axis weights or periodic axes.
+    /// Periods are scaled along with the path. Otherwise, the transformed path has no waypoints and
+    /// cannot be edited.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Self {
+        let scale = transform.similarity_scale();
+
+        // Keep offsets exactly in proportion when every length is scaled by the same amount
+        let mut start_offset = 0.0;
+
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let offset = match scale {
+                    Some(scale) => segment.start_offset() * scale,
+                    None => start_offset,
+                };
+
+                let segment = segment.transformed(transform).with_start_offset(offset);
+
+                start_offset = segment.end_offset();
+
+                segment
+            })
+            .collect::<Vec<_>>();
+
+        let switching_points = match scale {
+            Some(scale) if transform.preserves_axes() => self
+                .switching_points
+                .iter()
+                .map(|point| {
+                    PathSwitchingPoint::new(point.position * scale, point.continuity.clone())
+                })
+                .collect(),
+            _ => self.transformed_switching_points(&segments),
+        };
+
+        // Axis weights and periodic axes only apply to the transformed path the same way if no
+        // axes are swapped or rotated into each other
+        let uses_axes = self.options.axis_weights.is_some() || self.options.axis_periods.is_some();
+
+        let (waypoints, options) = match scale {
+            Some(scale) if !uses_axes || transform.is_diagonal() => (
+                self.waypoints
+                    .iter()
+                    .map(|waypoint| transform.transform_point(waypoint))
+                    .collect(),
+                PathOptions {
+                    max_deviation: self.options.max_deviation * scale,
+                    axis_periods: self.options.axis_periods.map(|periods| periods * scale),
+                    ..self.options
+                },
+            ),
+            _ => (Vec::new(), PathOptions::default()),
+        };
+
+        Self {
+            length: start_offset,
+            segments,
+            switching_points,
+            waypoints,
+            options,
+            index: SegmentIndexCache::default(),
+        }
+    }
+
+    /// Move switching points at junctions onto the transformed segments, and recompute the
+    /// switching points inside each segment
+    fn transformed_switching_points(&self, segments: &[PathSegment<N>]) -> Vec<PathSwitchingPoint> {
+        let mut switching_points = self
+            .switching_points
+            .iter()
+            .filter_map(|point| {
+                let index = self.segment_index_at_position(point.position);
+                let (before, after) = (&self.segments[index], &segments[index]);
+
+                if (point.position - before.start_offset()).abs() < TRAJECTORY_EPSILON {
+                    Some(PathSwitchingPoint::new(
+                        after.start_offset(),
+                        point.continuity.clone(),
+                    ))
+                } else if (point.position - before.end_offset()).abs() < TRAJECTORY_EPSILON {
+                    Some(PathSwitchingPoint::new(
+                        after.end_offset(),
+                        point.continuity.clone(),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Points within rounding error of a junction are already covered by the junction
+        for segment in segments {
+            switching_points.extend(
+                segment
+                    .switching_points()
+                    .into_iter()
+                    .filter(|point| {
+                        *point > TRAJECTORY_EPSILON && *point < segment.len() - TRAJECTORY_EPSILON
+                    })
+                    .map(|point| {
+                        PathSwitchingPoint::new(
+                            segment.start_offset() + point,
+                            Continuity::Continuous,
+                        )
+                    }),
+            );
+        }
+
+        switching_points.sort_by(|a, b| {
+            a.position
+                .partial_cmp(&b.position)
+                .expect("Could not sort switching points")
+        });
+
+        switching_points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{
+        ArcDirection, BSplinePathSegment, Blend, CircularPathSegment, HelixPathSegment,
+        LinearPathSegment, SplinePathSegment, Trajectory,
+    };
+    use nalgebra::{Matrix3, Translation3, UnitQuaternion, Vector3};
+    use std::f64::consts::PI;
+
+    fn options(blend: Blend) -> PathOptions<U3> {
+        PathOptions {
+            max_deviation: 0.1,
+            blend,
+            ..PathOptions::default()
+        }
+    }
+
+    /// Check a transformed path follows the original path, for a transform that keeps its shape
+    fn assert_transformed(path: &Path<U3>, transform: &PathTransform<U3>) {
+        let transformed = path.transformed(transform);
+        let scale = transform.similarity_scale().unwrap();
+
+        assert_near!(transformed.len(), path.len() * scale);
+
+        for i in 0..=200 {
+            let distance = path.len() * i as f64 / 200.0;
+
+            assert_near!(
+                transformed.position(distance * scale),
+                transform.transform_point(&path.position(distance))
+            );
+            assert_near!(
+                transformed.tangent(distance * scale),
+                transform.transform_vector(&path.tangent(distance)) / scale
+            );
+        }
+    }
+
+    /// Merge switching points at the same position, which is discontinuous if any of them are
+    fn distinct(points: &[PathSwitchingPoint]) -> Vec<PathSwitchingPoint> {
+        let mut merged: Vec<PathSwitchingPoint> = Vec::new();
+
+        for point in points {
+            match merged.last_mut() {
+                Some(last) if (last.position - point.position).abs() < TRAJECTORY_EPSILON => {
+                    if point.continuity == Continuity::Discontinuous {
+                        last.continuity = Continuity::Discontinuous;
+                    }
+                }
+                _ => merged.push(point.clone()),
+            }
+        }
+
+        merged
+    }
+
+    #[test]
+    fn constructors() {
+        let point = TestCoord3::new(1.0, 2.0, 3.0);
+
+        assert_near!(
+            PathTransform::translation(&TestCoord3::new(1.0, 0.0, -1.0)).transform_point(&point),
+            TestCoord3::new(2.0, 2.0, 2.0)
+        );
+        assert_near!(
+            PathTransform::scaling(2.0).transform_point(&point),
+            TestCoord3::new(2.0, 4.0, 6.0)
+        );
+        assert_near!(
+            PathTransform::rotation((0, 1), PI / 2.0).transform_point(&point),
+            TestCoord3::new(-2.0, 1.0, 3.0)
+        );
+        assert_near!(
+            PathTransform::mirror(2).transform_point(&point),
+            TestCoord3::new(1.0, 2.0, -3.0)
+        );
+
+        // Scale, then move
+        let combined =
+            PathTransform::scaling(2.0).then(&PathTransform::translation(&TestCoord3::x()));
+
+        assert_near!(
+            combined.transform_point(&point),
+            TestCoord3::new(3.0, 4.0, 6.0)
+        );
+
+        let isometry = Isometry3::from_parts(
+            Translation3::new(1.0, 2.0, 3.0),
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI / 2.0),
+        );
+
+        assert_near!(
+            PathTransform::from(isometry).transform_point(&point),
+            TestCoord3::new(-1.0, 3.0, 6.0)
+        );
+
+        assert_eq!(
+            PathTransform::new(
+                &Matrix3::new(1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0),
+                &point
+            )
+            .unwrap()
+            .transform_point(&point),
+            TestCoord3::new(2.0, 6.0, 12.0)
+        );
+    }
+
+    #[test]
+    fn similarity_scale() {
+        assert_eq!(
+            PathTransform::<U3>::identity().similarity_scale(),
+            Some(1.0)
+        );
+        assert_near!(
+            PathTransform::<U3>::rotation((0, 2), 0.3)
+                .then(&PathTransform::scaling(-3.0))
+                .then(&PathTransform::mirror(1))
+                .similarity_scale()
+                .unwrap(),
+            3.0
+        );
+        assert_eq!(
+            PathTransform::from_columns(
+                &[
+                    TestCoord3::x(),
+                    TestCoord3::new(1.0, 1.0, 0.0),
+                    TestCoord3::z()
+                ],
+                &TestCoord3::zeros()
+            )
+            .unwrap()
+            .similarity_scale(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_transforms() {
+        assert_eq!(
+            PathTransform::new(
+                &Matrix3::new(1.0, 2.0, 0.0, 2.0, 4.0, 0.0, 0.0, 0.0, 1.0),
+                &TestCoord3::zeros()
+            ),
+            Err(PathError::InvalidTransform)
+        );
+        assert_eq!(
+            PathTransform::from_columns(&[TestCoord3::x(), TestCoord3::y()], &TestCoord3::zeros()),
+            Err(PathError::InvalidTransform)
+        );
+        assert_eq!(
+            PathTransform::new(
+                &Matrix3::identity(),
+                &TestCoord3::new(std::f64::NAN, 0.0, 0.0)
+            ),
+            Err(PathError::InvalidTransform)
+        );
+    }
+
+    #[test]
+    fn rigid_motions_match_rebuilt_paths() {
+        let transforms = vec![
+            PathTransform::translation(&TestCoord3::new(10.0, -5.0, 2.0)),
+            PathTransform::rotation((0, 1), 0.7)
+                .then(&PathTransform::translation(&TestCoord3::new(1.0, 2.0, 3.0))),
+            PathTransform::mirror(0),
+            PathTransform::rotation((1, 2), PI / 2.0),
+        ];
+
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let path = Path::from_waypoints(&waypoints_fixture(), options(*blend));
+
+            for transform in transforms.iter() {
+                assert_transformed(&path, transform);
+
+                // Moving a rigid path gives the same blends and switching points as rebuilding it
+                let transformed = path.transformed(transform);
+                let rebuilt = Path::from_waypoints(transformed.waypoints(), options(*blend));
+
+                assert_near!(transformed.len(), rebuilt.len());
+                assert_eq!(
+                    distinct(transformed.switching_points()).len(),
+                    distinct(rebuilt.switching_points()).len()
+                );
+
+                for (a, b) in distinct(transformed.switching_points())
+                    .iter()
+                    .zip(distinct(rebuilt.switching_points()))
+                {
+                    assert_near!(a.position, b.position);
+                    assert_eq!(a.continuity, b.continuity);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scaling_keeps_blend_shape() {
+        let path = Path::from_waypoints(&waypoints_fixture(), options(Blend::Circular));
+        let transform = PathTransform::scaling(2.5);
+        let transformed = path.transformed(&transform);
+
+        assert_transformed(&path, &transform);
+
+        for (a, b) in transformed
+            .switching_points()
+            .iter()
+            .zip(path.switching_points())
+        {
+            assert_near!(a.position, b.position * 2.5);
+        }
+
+        // Blends are scaled instead of recreated, and so is the deviation used for later edits
+        match (&transformed.segments[1], &path.segments[1]) {
+            (PathSegment::Circular(a), PathSegment::Circular(b)) => {
+                assert_near!(a.radius, b.radius * 2.5);
+            }
+            segments => panic!("Unexpected segments {:?}", segments),
+        }
+
+        assert_near!(transformed.options.max_deviation, 0.25);
+    }
+
+    #[test]
+    fn transform_curves() {
+        let start = TestCoord3::new(1.0, 0.0, 0.0);
+
+        let segments = vec![
+            PathSegment::Helix(
+                HelixPathSegment::new(
+                    &start,
+                    &TestCoord3::zeros(),
+                    &TestCoord3::z(),
+                    (0, 1),
+                    ArcDirection::Clockwise,
+                    0.5,
+                    1.25,
+                )
+                .unwrap(),
+            ),
+            PathSegment::Spline(
+                SplinePathSegment::from_waypoints(&[
+                    start,
+                    TestCoord3::new(0.0, 1.0, 0.5),
+                    TestCoord3::new(-1.0, 2.0, 1.0),
+                ])
+                .unwrap(),
+            ),
+            PathSegment::BSpline(
+                BSplinePathSegment::from_control_points(
+                    2,
+                    &[
+                        start,
+                        TestCoord3::new(1.0, 1.0, 0.0),
+                        TestCoord3::new(0.0, 1.0, 0.5),
+                    ],
+                    &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+                )
+                .unwrap(),
+            ),
+        ];
+
+        let transform = PathTransform::rotation((0, 2), 0.4)
+            .then(&PathTransform::scaling(1.5))
+            .then(&PathTransform::mirror(1));
+
+        for segment in segments {
+            let path = Path::from_segments(vec![segment]);
+
+            assert_transformed(&path, &transform);
+
+            // Transformed curves keep their kind
+            assert!(match path.transformed(&transform).segments[0] {
+                PathSegment::Transformed(_) => false,
+                _ => true,
+            });
+        }
+    }
+
+    #[test]
+    fn non_uniform_scaling() {
+        // A quarter circle stretched along X becomes a quarter of an ellipse
+        let arc = CircularPathSegment::from_arc(
+            &TestCoord3::new(1.0, 0.0, 0.0),
+            &TestCoord3::new(0.0, 1.0, 0.0),
+            &TestCoord3::zeros(),
+            (0, 1),
+            ArcDirection::Counterclockwise,
+        )
+        .unwrap();
+
+        let path = Path::from_segments(vec![
+            PathSegment::Linear(LinearPathSegment::from_waypoints(
+                TestCoord3::new(1.0, -1.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 0.0),
+            )),
+            PathSegment::Circular(arc),
+        ]);
+
+        let transform = PathTransform::from_columns(
+            &[
+                TestCoord3::new(2.0, 0.0, 0.0),
+                TestCoord3::y(),
+                TestCoord3::z(),
+            ],
+            &TestCoord3::zeros(),
+        )
+        .unwrap();
+
+        let transformed = path.transformed(&transform);
+
+        // Quarter of the perimeter of an ellipse with semi-axes 2 and 1
+        assert_near!(transformed.len(), 1.0 + 2.422_112_055_136_373);
+        assert_eq!(transformed.waypoints(), &[]);
+
+        for i in 0..=100 {
+            let distance = 1.0 + (transformed.len() - 1.0) * i as f64 / 100.0;
+            let point = transformed.position(distance);
+
+            assert_near!((point.x / 2.0).hypot(point.y), 1.0);
+            assert_near!(transformed.tangent(distance).norm(), 1.0);
+        }
+
+        assert_near!(
+            transformed.position(transformed.len()),
+            TestCoord3::new(0.0, 1.0, 0.0)
+        );
+
+        // The junction is kept, and is the only switching point as the ellipse's tangent only
+        // lines up with an axis at either end
+        assert_eq!(
+            distinct(transformed.switching_points()),
+            vec![PathSwitchingPoint::new(1.0, Continuity::Discontinuous)]
+        );
+    }
+
+    #[test]
+    fn plan_transformed_path() {
+        let path = Path::from_waypoints(&waypoints_fixture(), PathOptions::default());
+        let transform = PathTransform::rotation((0, 1), 0.3)
+            .then(&PathTransform::translation(&TestCoord3::new(5.0, 5.0, 0.0)));
+        let transformed = path.transformed(&transform);
+
+        let options = trajectory_options_fixture();
+
+        let trajectory = Trajectory::new(&transformed, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            transform.transform_point(&waypoints_fixture()[5])
+        );
+    }
+}
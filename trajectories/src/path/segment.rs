@@ -1,5 +1,9 @@
-use crate::path::{CircularPathSegment, LinearPathSegment, PathItem};
-use crate::Coord;
+use crate::path::{
+    BSplinePathSegment, CircularPathSegment, ClothoidPathSegment, CustomPathSegment,
+    HelixPathSegment, LinearPathSegment, PathItem, PathTransform, Segment, SplinePathSegment,
+    TransformedPathSegment, TrimmedPathSegment,
+};
+use crate::{Coord, TRAJECTORY_EPSILON};
 use core::cmp::Ordering;
 use nalgebra::{
     allocator::{Allocator, SameShapeVectorAllocator},
@@ -7,7 +11,25 @@ use nalgebra::{
     DefaultAllocator, DimName,
 };
 
+/// Number of evenly spaced samples used to find the closest point on a segment numerically
+const PROJECTION_SAMPLES: usize = 32;
+
+/// Number of golden section iterations used to refine the closest sample
+const PROJECTION_ITERATIONS: usize = 64;
+
+/// Number of evenly spaced samples used to bound segments without a convex hull
+const BOUNDS_SAMPLES: usize = 64;
+
+/// A segment of a path
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
 pub enum PathSegment<N>
 where
     N: DimName + Copy,
@@ -15,8 +37,33 @@ where
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
     Owned<f64, N>: Copy,
 {
+    /// Straight line between two points
     Linear(LinearPathSegment<N>),
+
+    /// Circular arc, either a blend between two linear segments or an explicit arc
     Circular(CircularPathSegment<N>),
+
+    /// Clothoid blend between two linear segments
+    Clothoid(ClothoidPathSegment<N>),
+
+    /// Cubic spline through a list of waypoints
+    Spline(SplinePathSegment<N>),
+
+    /// Helix around an axis
+    Helix(HelixPathSegment<N>),
+
+    /// B-spline or NURBS curve
+    BSpline(BSplinePathSegment<N>),
+
+    /// Part of another segment, left by trimming it to slice a path
+    Trimmed(TrimmedPathSegment<N>),
+
+    /// Another segment moved by a transform that changes its shape
+    Transformed(TransformedPathSegment<N>),
+
+    /// User defined geometry, which cannot be serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(CustomPathSegment<N>),
 }
 
 impl<N> PartialOrd for PathSegment<N>
@@ -52,6 +99,13 @@ where
         match self {
             PathSegment::Linear(s) => s.len(),
             PathSegment::Circular(s) => s.len(),
+            PathSegment::Clothoid(s) => s.len(),
+            PathSegment::Spline(s) => s.len(),
+            PathSegment::Helix(s) => s.len(),
+            PathSegment::BSpline(s) => s.len(),
+            PathSegment::Trimmed(s) => s.len(),
+            PathSegment::Transformed(s) => s.len(),
+            PathSegment::Custom(s) => s.len(),
         }
     }
 
@@ -60,6 +114,13 @@ where
         match self {
             PathSegment::Linear(s) => s.position(distance_along_line),
             PathSegment::Circular(s) => s.position(distance_along_line),
+            PathSegment::Clothoid(s) => s.position(distance_along_line),
+            PathSegment::Spline(s) => s.position(distance_along_line),
+            PathSegment::Helix(s) => s.position(distance_along_line),
+            PathSegment::BSpline(s) => s.position(distance_along_line),
+            PathSegment::Trimmed(s) => s.position(distance_along_line),
+            PathSegment::Transformed(s) => s.position(distance_along_line),
+            PathSegment::Custom(s) => s.position(distance_along_line),
         }
     }
 
@@ -68,6 +129,13 @@ where
         match self {
             PathSegment::Linear(s) => s.tangent(distance_along_line),
             PathSegment::Circular(s) => s.tangent(distance_along_line),
+            PathSegment::Clothoid(s) => s.tangent(distance_along_line),
+            PathSegment::Spline(s) => s.tangent(distance_along_line),
+            PathSegment::Helix(s) => s.tangent(distance_along_line),
+            PathSegment::BSpline(s) => s.tangent(distance_along_line),
+            PathSegment::Trimmed(s) => s.tangent(distance_along_line),
+            PathSegment::Transformed(s) => s.tangent(distance_along_line),
+            PathSegment::Custom(s) => s.tangent(distance_along_line),
         }
     }
 
@@ -76,6 +144,13 @@ where
         match self {
             PathSegment::Linear(s) => s.curvature(distance_along_line),
             PathSegment::Circular(s) => s.curvature(distance_along_line),
+            PathSegment::Clothoid(s) => s.curvature(distance_along_line),
+            PathSegment::Spline(s) => s.curvature(distance_along_line),
+            PathSegment::Helix(s) => s.curvature(distance_along_line),
+            PathSegment::BSpline(s) => s.curvature(distance_along_line),
+            PathSegment::Trimmed(s) => s.curvature(distance_along_line),
+            PathSegment::Transformed(s) => s.curvature(distance_along_line),
+            PathSegment::Custom(s) => s.curvature(distance_along_line),
         }
     }
 
@@ -83,6 +158,13 @@ where
         match self {
             PathSegment::Linear(s) => s.tangent_and_curvature(distance_along_line),
             PathSegment::Circular(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Clothoid(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Spline(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Helix(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::BSpline(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Trimmed(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Transformed(s) => s.tangent_and_curvature(distance_along_line),
+            PathSegment::Custom(s) => s.tangent_and_curvature(distance_along_line),
         }
     }
 }
@@ -94,21 +176,220 @@ where
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
     Owned<f64, N>: Copy,
 {
+    /// Create a segment from user defined geometry
+    pub fn custom<S>(segment: S) -> Self
+    where
+        S: Segment<N> + 'static,
+    {
+        PathSegment::Custom(CustomPathSegment::new(segment))
+    }
+
     /// Clone segment and give it a start offset
-    // TODO: Trait
     pub fn with_start_offset(self, offset: f64) -> Self {
         match self {
             PathSegment::Linear(s) => PathSegment::Linear(s.with_start_offset(offset)),
             PathSegment::Circular(s) => PathSegment::Circular(s.with_start_offset(offset)),
+            PathSegment::Clothoid(s) => PathSegment::Clothoid(s.with_start_offset(offset)),
+            PathSegment::Spline(s) => PathSegment::Spline(s.with_start_offset(offset)),
+            PathSegment::Helix(s) => PathSegment::Helix(s.with_start_offset(offset)),
+            PathSegment::BSpline(s) => PathSegment::BSpline(s.with_start_offset(offset)),
+            PathSegment::Trimmed(s) => PathSegment::Trimmed(s.with_start_offset(offset)),
+            PathSegment::Transformed(s) => PathSegment::Transformed(s.with_start_offset(offset)),
+            PathSegment::Custom(s) => PathSegment::Custom(s.with_start_offset(offset)),
+        }
+    }
+
+    /// Get this segment traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        match self {
+            PathSegment::Linear(s) => PathSegment::Linear(s.reversed()),
+            PathSegment::Circular(s) => PathSegment::Circular(s.reversed()),
+            PathSegment::Clothoid(s) => PathSegment::Clothoid(s.reversed()),
+            PathSegment::Spline(s) => PathSegment::Spline(s.reversed()),
+            PathSegment::Helix(s) => PathSegment::Helix(s.reversed()),
+            PathSegment::BSpline(s) => PathSegment::BSpline(s.reversed()),
+            PathSegment::Trimmed(s) => PathSegment::Trimmed(s.reversed()),
+            PathSegment::Transformed(s) => PathSegment::Transformed(s.reversed()),
+            PathSegment::Custom(s) => PathSegment::Custom(s.reversed()),
+        }
+    }
+
+    /// Get this segment moved by a transform, with the same start offset
+    ///
+    /// Lines and B-splines keep their kind under any transform. Arcs, helices, clothoids and
+    /// splines keep their kind under transforms that keep their shape, as described in
+    /// `PathTransform::similarity_scale`. Other segments are wrapped in a `TransformedPathSegment`
+    /// that finds the transformed arc length numerically, which can be serialized if the
+    /// untransformed segment can.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Self {
+        let segment = match self {
+            PathSegment::Linear(s) => Some(PathSegment::Linear(s.transformed(transform))),
+            PathSegment::Circular(s) => s.transformed(transform).map(PathSegment::Circular),
+            PathSegment::Clothoid(s) => s.transformed(transform).map(PathSegment::Clothoid),
+            PathSegment::Spline(s) => s.transformed(transform).map(PathSegment::Spline),
+            PathSegment::Helix(s) => s.transformed(transform).map(PathSegment::Helix),
+            PathSegment::BSpline(s) => Some(PathSegment::BSpline(s.transformed(transform))),
+            PathSegment::Trimmed(s) => s.transformed(transform).map(PathSegment::Trimmed),
+            PathSegment::Transformed(s) => Some(PathSegment::Transformed(s.transformed(transform))),
+            PathSegment::Custom(_) => None,
+        };
+
+        segment.unwrap_or_else(|| {
+            PathSegment::Transformed(
+                TransformedPathSegment::new(self.clone(), transform.clone())
+                    .with_start_offset(self.start_offset()),
+            )
+        })
+    }
+
+    /// Get the part of this segment between two distances along the path
+    ///
+    /// Lines, arcs and helices are trimmed to a segment of the same kind. Other segments are
+    /// wrapped in a `TrimmedPathSegment`, which can be serialized if the untrimmed segment can.
+    /// The trimmed segment starts at `from`.
+    pub fn trim(&self, from: f64, to: f64) -> Self {
+        match self {
+            PathSegment::Linear(s) => PathSegment::Linear(s.trim(from, to)),
+            PathSegment::Circular(s) => PathSegment::Circular(s.trim(from, to)),
+            PathSegment::Helix(s) => PathSegment::Helix(s.trim(from, to)),
+            PathSegment::Trimmed(s) => PathSegment::Trimmed(s.trim(from, to)),
+            _ => PathSegment::Trimmed(
+                TrimmedPathSegment::new(self.clone(), from, to).with_start_offset(from),
+            ),
+        }
+    }
+
+    /// Get the distance along the path of the point on this segment closest to `point`
+    ///
+    /// Lines and arcs are projected onto exactly, unless axis weights have stretched an arc into
+    /// an ellipse. Other segments are sampled at even spacing and at their switching points, then
+    /// the closest sample is refined numerically.
+    pub fn project(&self, point: &Coord<N>) -> f64 {
+        match self {
+            PathSegment::Linear(s) => s.project(point),
+            PathSegment::Circular(s) if s.is_round() => s.project(point),
+            _ => self.project_numerically(point),
+        }
+    }
+
+    /// Find the closest point on a segment without an exact projection
+    fn project_numerically(&self, point: &Coord<N>) -> f64 {
+        let start = self.start_offset();
+        let end = self.end_offset();
+
+        let mut samples = (0..=PROJECTION_SAMPLES)
+            .map(|i| start + (end - start) * i as f64 / PROJECTION_SAMPLES as f64)
+            .chain(self.switching_points().into_iter().map(|p| start + p))
+            .collect::<Vec<f64>>();
+
+        samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        samples.dedup_by(|a, b| (*a - *b).abs() < TRAJECTORY_EPSILON);
+
+        let distance_to = |distance: f64| (self.position(distance) - point).norm_squared();
+
+        let (closest, closest_distance) = samples
+            .iter()
+            .map(|sample| distance_to(*sample))
+            .enumerate()
+            .fold((0, std::f64::INFINITY), |best, (index, distance)| {
+                if distance < best.1 {
+                    (index, distance)
+                } else {
+                    best
+                }
+            });
+
+        // The closest point lies between the samples either side of the closest sample
+        let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+        let mut low = samples[closest.saturating_sub(1)];
+        let mut high = samples[(closest + 1).min(samples.len() - 1)];
+
+        for _ in 0..PROJECTION_ITERATIONS {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+
+            if distance_to(a) < distance_to(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+
+        let refined = (low + high) / 2.0;
+
+        if distance_to(refined) < closest_distance {
+            refined
+        } else {
+            samples[closest]
+        }
+    }
+
+    /// Get the lowest and highest corners of a box that contains this whole segment
+    ///
+    /// Boxes are tight for lines. Arcs and helices are bounded by their full circle, clothoids by
+    /// the corner they blend, B-splines by their control points and trimmed segments by the segment
+    /// they were trimmed from. Other segments are sampled and the box padded by half the sample
+    /// spacing, which no part of the segment can stray beyond.
+    pub(crate) fn bounding_box(&self) -> (Coord<N>, Coord<N>) {
+        match self {
+            PathSegment::Linear(s) => bounds(&[s.start, s.end], 0.0),
+            PathSegment::Circular(s) => {
+                let reach = s.x.zip_map(&s.y, |x, y| x.hypot(y) * s.radius);
+
+                (&s.center - &reach, &s.center + &reach)
+            }
+            PathSegment::Helix(s) => {
+                let reach = s.x.zip_map(&s.y, |x, y| x.hypot(y) * s.radius);
+                let end_center = &s.center + &s.axis * (s.pitch * s.turns);
+
+                bounds(
+                    &[
+                        &s.center - &reach,
+                        &s.center + &reach,
+                        &end_center - &reach,
+                        &end_center + &reach,
+                    ],
+                    0.0,
+                )
+            }
+            PathSegment::Clothoid(s) => bounds(
+                &[
+                    s.start,
+                    &s.start + &s.x * s.corner_distance,
+                    s.position(s.end_offset),
+                ],
+                0.0,
+            ),
+            PathSegment::BSpline(s) => bounds(s.control_points(), 0.0),
+            PathSegment::Trimmed(s) => s.segment.bounding_box(),
+            _ => {
+                let start = self.start_offset();
+                let spacing = (self.end_offset() - start) / BOUNDS_SAMPLES as f64;
+
+                let samples = (0..=BOUNDS_SAMPLES)
+                    .map(|i| self.position(start + spacing * i as f64))
+                    .collect::<Vec<_>>();
+
+                bounds(&samples, spacing / 2.0)
+            }
         }
     }
 
     /// Get start offset of this segment
-    // TODO: Trait
+    ///
+    /// Offsets are kept by each segment rather than the `Segment` trait, whose distances are
+    /// measured from the start of the segment itself.
     pub fn start_offset(&self) -> f64 {
         match self {
             PathSegment::Linear(s) => s.start_offset,
             PathSegment::Circular(s) => s.start_offset,
+            PathSegment::Clothoid(s) => s.start_offset,
+            PathSegment::Spline(s) => s.start_offset,
+            PathSegment::Helix(s) => s.start_offset,
+            PathSegment::BSpline(s) => s.start_offset,
+            PathSegment::Trimmed(s) => s.start_offset,
+            PathSegment::Transformed(s) => s.start_offset,
+            PathSegment::Custom(s) => s.start_offset,
         }
     }
 
@@ -117,15 +398,52 @@ where
         match self {
             PathSegment::Linear(s) => s.end_offset(),
             PathSegment::Circular(s) => s.end_offset(),
+            PathSegment::Clothoid(s) => s.end_offset(),
+            PathSegment::Spline(s) => s.end_offset(),
+            PathSegment::Helix(s) => s.end_offset(),
+            PathSegment::BSpline(s) => s.end_offset(),
+            PathSegment::Trimmed(s) => s.end_offset(),
+            PathSegment::Transformed(s) => s.end_offset(),
+            PathSegment::Custom(s) => s.end_offset(),
         }
     }
 
     /// Get the switching points for this path segment
-    // TODO: Trait?
     pub fn switching_points(&self) -> Vec<f64> {
         match self {
             PathSegment::Linear(s) => s.switching_points(),
             PathSegment::Circular(s) => s.switching_points(),
+            PathSegment::Clothoid(s) => s.switching_points(),
+            PathSegment::Spline(s) => s.switching_points(),
+            PathSegment::Helix(s) => s.switching_points(),
+            PathSegment::BSpline(s) => s.switching_points(),
+            PathSegment::Trimmed(s) => s.switching_points(),
+            PathSegment::Transformed(s) => s.switching_points(),
+            PathSegment::Custom(s) => s.switching_points(),
         }
     }
 }
+
+/// Get the lowest and highest corners of a box around some points, padded on every side
+fn bounds<N>(points: &[Coord<N>], padding: f64) -> (Coord<N>, Coord<N>)
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let (low, high) = points.iter().fold(
+        (
+            Coord::repeat(std::f64::INFINITY),
+            Coord::repeat(std::f64::NEG_INFINITY),
+        ),
+        |(low, high), point| {
+            (
+                low.zip_map(point, |a, b| a.min(b)),
+                high.zip_map(point, |a, b| a.max(b)),
+            )
+        },
+    );
+
+    (low.add_scalar(-padding), high.add_scalar(padding))
+}
@@ -0,0 +1,311 @@
+use super::PathSegment;
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Greatest number of segments held in a leaf of the index
+const LEAF_SIZE: usize = 4;
+
+/// A node of the bounding box tree
+#[derive(Debug)]
+struct Node<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Lowest corner of the box around every segment below this node
+    low: Coord<N>,
+
+    /// Highest corner of the box around every segment below this node
+    high: Coord<N>,
+
+    /// Index into the segment order of the first segment in this leaf
+    first: usize,
+
+    /// Number of segments below this node
+    count: usize,
+
+    /// Index of the second child of a branch, or zero for a leaf. The first child always follows
+    /// its parent.
+    second: usize,
+}
+
+impl<N> Node<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the distance from a point to the nearest part of this node's box
+    fn distance_to(&self, point: &Coord<N>) -> f64 {
+        point
+            .iter()
+            .zip(self.low.iter().zip(self.high.iter()))
+            .map(|(p, (low, high))| (low - p).max(p - high).max(0.0).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// Bounding box tree over the segments of a path, used to find the segments nearest a point
+/// without checking every one
+#[derive(Debug)]
+pub(crate) struct SegmentIndex<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Tree nodes in depth first order, starting with the root
+    nodes: Vec<Node<N>>,
+
+    /// Segment indices, grouped so each leaf holds a contiguous range
+    order: Vec<usize>,
+}
+
+impl<N> SegmentIndex<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Build an index over some segments
+    pub(crate) fn new(segments: &[PathSegment<N>]) -> Self {
+        let boxes = segments
+            .iter()
+            .map(|segment| segment.bounding_box())
+            .collect::<Vec<_>>();
+
+        let mut index = Self {
+            nodes: Vec::with_capacity(2 * segments.len() / LEAF_SIZE + 1),
+            order: (0..segments.len()).collect(),
+        };
+
+        let _ = index.build(&boxes, 0, segments.len());
+
+        index
+    }
+
+    /// Add a node for a range of the segment order, splitting it in half along the axis the
+    /// segment centers are most spread out along. Returns the index of the new node.
+    fn build(&mut self, boxes: &[(Coord<N>, Coord<N>)], first: usize, end: usize) -> usize {
+        let (low, high) = self.order[first..end].iter().fold(
+            (
+                Coord::repeat(std::f64::INFINITY),
+                Coord::repeat(std::f64::NEG_INFINITY),
+            ),
+            |(low, high), segment| {
+                let (segment_low, segment_high) = &boxes[*segment];
+
+                (
+                    low.zip_map(segment_low, |a, b| a.min(b)),
+                    high.zip_map(segment_high, |a, b| a.max(b)),
+                )
+            },
+        );
+
+        let node = self.nodes.len();
+
+        self.nodes.push(Node {
+            low,
+            high,
+            first,
+            count: end - first,
+            second: 0,
+        });
+
+        if end - first <= LEAF_SIZE {
+            return node;
+        }
+
+        let center = |segment: usize| (&boxes[segment].0 + &boxes[segment].1) / 2.0;
+
+        let (center_low, center_high) = self.order[first..end].iter().fold(
+            (
+                Coord::repeat(std::f64::INFINITY),
+                Coord::repeat(std::f64::NEG_INFINITY),
+            ),
+            |(low, high), segment| {
+                let center = center(*segment);
+
+                (
+                    low.zip_map(&center, |a, b| a.min(b)),
+                    high.zip_map(&center, |a, b| a.max(b)),
+                )
+            },
+        );
+
+        let axis = (center_high - center_low).imax();
+        let middle = (end - first) / 2;
+
+        let _ = self.order[first..end].select_nth_unstable_by(middle, |a, b| {
+            center(*a)[axis]
+                .partial_cmp(&center(*b)[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let _ = self.build(boxes, first, first + middle);
+        let second = self.build(boxes, first + middle, end);
+
+        self.nodes[node].second = second;
+
+        node
+    }
+
+    /// Visit every segment that could be nearest to a point
+    ///
+    /// `distance_to` is called with the index of each segment whose box is no further away than
+    /// the nearest segment found so far, and must return the distance from the point to that
+    /// segment. Segments within `TRAJECTORY_EPSILON` of the nearest are all visited, so callers
+    /// can break ties.
+    pub(crate) fn nearest<F>(&self, point: &Coord<N>, mut distance_to: F)
+    where
+        F: FnMut(usize) -> f64,
+    {
+        let mut nearest = std::f64::INFINITY;
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let Node {
+                first,
+                count,
+                second,
+                ..
+            } = self.nodes[node];
+
+            if self.nodes[node].distance_to(point) > nearest + TRAJECTORY_EPSILON {
+                continue;
+            }
+
+            if second == 0 {
+                for segment in &self.order[first..first + count] {
+                    nearest = nearest.min(distance_to(*segment));
+                }
+            } else if self.nodes[node + 1].distance_to(point)
+                <= self.nodes[second].distance_to(point)
+            {
+                // Visit the nearer child first so more of the further one can be skipped
+                stack.push(second);
+                stack.push(node + 1);
+            } else {
+                stack.push(node + 1);
+                stack.push(second);
+            }
+        }
+    }
+}
+
+/// A segment index that is built the first time it is needed and shared between clones of a path
+///
+/// The index is derived entirely from the path's segments, so it is ignored when comparing paths.
+pub(crate) struct SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Index, once it has been built, with the number of segments and path length it was built for
+    index: RwLock<Option<(usize, f64, Arc<SegmentIndex<N>>)>>,
+}
+
+impl<N> SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the index for some segments, building it if it has not been built yet
+    ///
+    /// The index is rebuilt if the number of segments or the path length has changed since it was
+    /// built, as happens when `Path::segments` is edited directly.
+    pub(crate) fn get(&self, segments: &[PathSegment<N>]) -> Arc<SegmentIndex<N>> {
+        let count = segments.len();
+        let length = segments.last().map_or(0.0, PathSegment::end_offset);
+
+        if let Some((built_count, built_length, index)) =
+            &*self.index.read().unwrap_or_else(PoisonError::into_inner)
+        {
+            if *built_count == count && built_length.to_bits() == length.to_bits() {
+                return index.clone();
+            }
+        }
+
+        let index = Arc::new(SegmentIndex::new(segments));
+
+        *self.index.write().unwrap_or_else(PoisonError::into_inner) =
+            Some((count, length, index.clone()));
+
+        index
+    }
+}
+
+impl<N> Default for SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn default() -> Self {
+        Self {
+            index: RwLock::new(None),
+        }
+    }
+}
+
+impl<N> Clone for SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            index: RwLock::new(
+                self.index
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clone(),
+            ),
+        }
+    }
+}
+
+impl<N> PartialEq for SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<N> fmt::Debug for SegmentIndexCache<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SegmentIndexCache")
+    }
+}
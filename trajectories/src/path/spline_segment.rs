@@ -0,0 +1,448 @@
+use super::{ArcLengthTable, ParametricCurve, PathError, PathItem, PathTransform};
+use crate::Coord;
+use crate::TRAJECTORY_EPSILON;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Cubic spline path segment
+///
+/// A natural cubic spline through a list of waypoints, with continuous tangent and curvature. The
+/// spline is parameterised by the chord length between waypoints, then reparameterised by arc
+/// length so that distances along it behave like distances along any other segment.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct SplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Spline parameter at each waypoint
+    knots: Vec<f64>,
+
+    /// Polynomial coefficients of each span between two waypoints, lowest order first
+    coefficients: Vec<[Coord<N>; 4]>,
+
+    /// Arc length samples along the spline
+    arc_length: ArcLengthTable,
+
+    /// Length of this segment
+    pub length: f64,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> SplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a natural cubic spline that passes through each waypoint
+    ///
+    /// Returns an error if fewer than two waypoints are given, if a waypoint has a NaN or infinite
+    /// coordinate, or if two consecutive waypoints are at the same position.
+    pub fn from_waypoints(waypoints: &[Coord<N>]) -> Result<Self, PathError> {
+        if waypoints.len() < 2 {
+            return Err(PathError::TooFewWaypoints {
+                count: waypoints.len(),
+            });
+        }
+
+        if let Some(index) = waypoints
+            .iter()
+            .position(|waypoint| waypoint.iter().any(|c| !c.is_finite()))
+        {
+            return Err(PathError::NonFiniteCoordinate { index });
+        }
+
+        if let Some(index) = waypoints
+            .windows(2)
+            .position(|parts| (&parts[1] - &parts[0]).norm() < TRAJECTORY_EPSILON)
+        {
+            return Err(PathError::ZeroLengthSegment { index });
+        }
+
+        let mut knots = Vec::with_capacity(waypoints.len());
+        knots.push(0.0);
+
+        for parts in waypoints.windows(2) {
+            let previous = knots[knots.len() - 1];
+
+            knots.push(previous + (&parts[1] - &parts[0]).norm());
+        }
+
+        let second_derivatives = Self::second_derivatives(waypoints, &knots);
+
+        let coefficients = (0..waypoints.len() - 1)
+            .map(|span| {
+                let h = knots[span + 1] - knots[span];
+                let (start, end) = (&waypoints[span], &waypoints[span + 1]);
+                let (start_m, end_m) = (&second_derivatives[span], &second_derivatives[span + 1]);
+
+                [
+                    start.clone(),
+                    (end - start) / h - (start_m * 2.0 + end_m) * h / 6.0,
+                    start_m / 2.0,
+                    (end_m - start_m) / (6.0 * h),
+                ]
+            })
+            .collect();
+
+        let mut segment = Self {
+            knots,
+            coefficients,
+            arc_length: ArcLengthTable::default(),
+            length: 0.0,
+            start_offset: 0.0,
+            end_offset: 0.0,
+        };
+
+        segment.arc_length = ArcLengthTable::new(&segment, &segment.knots);
+        segment.length = segment.arc_length.length();
+        segment.end_offset = segment.length;
+
+        Ok(segment)
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.length,
+            ..self
+        }
+    }
+
+    /// Get this spline traversed from end to start, with the same start offset
+    ///
+    /// A natural spline through the same waypoints in reverse order follows the same curve.
+    pub fn reversed(&self) -> Self {
+        let mut waypoints = self.waypoints();
+
+        waypoints.reverse();
+
+        Self::from_waypoints(&waypoints)
+            .expect("Reversed spline waypoints must be valid")
+            .with_start_offset(self.start_offset)
+    }
+
+    /// Get this spline moved by a transform, with the same start offset
+    ///
+    /// A natural spline through the moved waypoints follows the moved curve if the transform keeps
+    /// the shape of the spline. Returns `None` otherwise.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Option<Self> {
+        let _ = transform.similarity_scale()?;
+
+        let waypoints = self
+            .waypoints()
+            .iter()
+            .map(|waypoint| transform.transform_point(waypoint))
+            .collect::<Vec<_>>();
+
+        Some(
+            Self::from_waypoints(&waypoints)
+                .expect("Transformed spline waypoints must be valid")
+                .with_start_offset(self.start_offset),
+        )
+    }
+
+    /// Get the waypoints this spline passes through
+    fn waypoints(&self) -> Vec<Coord<N>> {
+        let mut waypoints = self
+            .coefficients
+            .iter()
+            .map(|coefficients| coefficients[0].clone())
+            .collect::<Vec<_>>();
+
+        waypoints.push(self.derivatives(self.knots[self.knots.len() - 1]).0);
+
+        waypoints
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points for this spline
+    ///
+    /// Switching points are found numerically where a component of the tangent crosses zero,
+    /// and at local maxima of curvature. Positions are relative to the start of the spline.
+    pub fn switching_points(&self) -> Vec<f64> {
+        self.arc_length.switching_points(self)
+    }
+
+    /// Solve for the second derivative at each waypoint, which is zero at both ends of a natural
+    /// spline
+    fn second_derivatives(waypoints: &[Coord<N>], knots: &[f64]) -> Vec<Coord<N>> {
+        let count = waypoints.len();
+        let mut result = vec![Coord::zeros(); count];
+
+        if count < 3 {
+            return result;
+        }
+
+        // Forward elimination of the tridiagonal system for the interior waypoints
+        let mut diagonal: Vec<f64> = Vec::with_capacity(count - 2);
+        let mut rhs: Vec<Coord<N>> = Vec::with_capacity(count - 2);
+
+        for i in 1..count - 1 {
+            let before = knots[i] - knots[i - 1];
+            let after = knots[i + 1] - knots[i];
+
+            let mut d = 2.0 * (before + after);
+            let mut r = ((&waypoints[i + 1] - &waypoints[i]) / after
+                - (&waypoints[i] - &waypoints[i - 1]) / before)
+                * 6.0;
+
+            if let (Some(previous_d), Some(previous_r)) = (diagonal.last(), rhs.last()) {
+                let factor = before / previous_d;
+
+                d -= factor * before;
+                r -= previous_r * factor;
+            }
+
+            diagonal.push(d);
+            rhs.push(r);
+        }
+
+        for i in (1..count - 1).rev() {
+            let after = knots[i + 1] - knots[i];
+
+            result[i] = (&rhs[i - 1] - &result[i + 1] * after) / diagonal[i - 1];
+        }
+
+        result
+    }
+}
+
+impl<N> ParametricCurve<N> for SplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the position, first derivative and second derivative at a spline parameter
+    fn derivatives(&self, parameter: f64) -> (Coord<N>, Coord<N>, Coord<N>) {
+        let span = self
+            .knots
+            .partition_point(|knot| *knot <= parameter)
+            .saturating_sub(1)
+            .min(self.coefficients.len() - 1);
+
+        let t = parameter - self.knots[span];
+        let [a, b, c, d] = &self.coefficients[span];
+
+        (
+            a + (b + (c + d * t) * t) * t,
+            b + (c * 2.0 + d * (3.0 * t)) * t,
+            c * 2.0 + d * (6.0 * t),
+        )
+    }
+}
+
+impl<N> PathItem<N> for SplinePathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the arc length of this spline
+    fn len(&self) -> f64 {
+        self.length
+    }
+
+    /// Get position at a point along the spline
+    fn position(&self, distance_along_spline: f64) -> Coord<N> {
+        let parameter = self
+            .arc_length
+            .parameter_at(self, distance_along_spline - self.start_offset);
+
+        self.derivatives(parameter).0
+    }
+
+    /// Get the unit tangent at a point along the spline
+    fn tangent(&self, distance_along_spline: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_spline).0
+    }
+
+    /// Get the curvature (second derivative with respect to arc length) at a point along the
+    /// spline
+    fn curvature(&self, distance_along_spline: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_spline).1
+    }
+
+    fn tangent_and_curvature(&self, distance_along_spline: f64) -> (Coord<N>, Coord<N>) {
+        self.arc_length
+            .tangent_and_curvature(self, distance_along_spline - self.start_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::{Path, PathSegment, Trajectory};
+
+    #[test]
+    fn passes_through_waypoints() {
+        let waypoints = [
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.5),
+            TestCoord3::new(3.0, 1.0, 1.0),
+        ];
+
+        let spline = SplinePathSegment::from_waypoints(&waypoints).unwrap();
+
+        for (waypoint, knot) in waypoints.iter().zip(spline.knots.iter()) {
+            assert_near!(
+                spline.position(spline.arc_length.length_at(&spline, *knot)),
+                *waypoint
+            );
+        }
+
+        assert_near!(spline.position(spline.len()), waypoints[3]);
+    }
+
+    #[test]
+    fn straight_spline() {
+        let spline = SplinePathSegment::from_waypoints(&[
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+            TestCoord3::new(2.0, 2.0, 2.0),
+        ])
+        .unwrap();
+
+        let direction = TestCoord3::repeat(1.0).normalize();
+
+        assert_near!(spline.len(), 12.0f64.sqrt());
+
+        for i in 0..=10 {
+            let distance = spline.len() * i as f64 / 10.0;
+
+            assert_near!(spline.position(distance), direction * distance);
+            assert_near!(spline.tangent(distance), direction);
+            assert_near!(spline.curvature(distance), TestCoord3::zeros());
+        }
+    }
+
+    #[test]
+    fn arc_length_parameterisation() {
+        let spline = SplinePathSegment::from_waypoints(&[
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.5),
+            TestCoord3::new(3.0, 1.0, 1.0),
+        ])
+        .unwrap()
+        .with_start_offset(2.0);
+        let h = 1e-5;
+
+        for i in 1..100 {
+            let distance = 2.0 + spline.len() * i as f64 / 100.0;
+            let (tangent, curvature) = spline.tangent_and_curvature(distance);
+
+            assert!((tangent.norm() - 1.0).abs() < 1e-9);
+
+            let position_derivative =
+                (spline.position(distance + h) - spline.position(distance - h)) / (2.0 * h);
+            let tangent_derivative =
+                (spline.tangent(distance + h) - spline.tangent(distance - h)) / (2.0 * h);
+
+            assert!((position_derivative - tangent).norm() < 1e-6);
+            assert!((tangent_derivative - curvature).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn finds_switching_points() {
+        let spline = SplinePathSegment::from_waypoints(&[
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.0),
+        ])
+        .unwrap();
+
+        // The Y tangent crosses zero and curvature peaks at the middle of this symmetric spline
+        assert_eq!(spline.switching_points().len(), 1);
+        assert_near!(spline.switching_points()[0], spline.len() / 2.0);
+
+        let spline = SplinePathSegment::from_waypoints(&[
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.5),
+            TestCoord3::new(3.0, 1.0, 1.0),
+        ])
+        .unwrap();
+
+        for point in spline.switching_points() {
+            let (tangent, _) = spline.tangent_and_curvature(point);
+
+            let tangent_zero = tangent.iter().any(|c| c.abs() < 1e-6);
+            let curvature_peak = spline.curvature(point).norm()
+                >= spline.curvature(point - 1e-3).norm()
+                && spline.curvature(point).norm() >= spline.curvature(point + 1e-3).norm();
+
+            assert!(tangent_zero || curvature_peak);
+        }
+    }
+
+    #[test]
+    fn invalid_waypoints() {
+        assert_eq!(
+            SplinePathSegment::from_waypoints(&[TestCoord3::zeros()]),
+            Err(PathError::TooFewWaypoints { count: 1 })
+        );
+        assert_eq!(
+            SplinePathSegment::from_waypoints(&[
+                TestCoord3::zeros(),
+                TestCoord3::new(1.0, 0.0, 0.0),
+                TestCoord3::new(1.0, 0.0, 0.0),
+            ]),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+    }
+
+    #[test]
+    fn spline_trajectory() {
+        let spline = SplinePathSegment::from_waypoints(&[
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(2.0, 0.0, 0.5),
+            TestCoord3::new(3.0, 1.0, 1.0),
+        ])
+        .unwrap();
+
+        let path = Path::from_segments(vec![PathSegment::Spline(spline)]);
+        let options = trajectory_options_fixture();
+
+        let trajectory = Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            trajectory.position(trajectory.duration()),
+            TestCoord3::new(3.0, 1.0, 1.0)
+        );
+
+        assert_within_limits(&trajectory, &options);
+    }
+}
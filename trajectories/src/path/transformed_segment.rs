@@ -0,0 +1,172 @@
+use super::{ArcLengthTable, ParametricCurve, PathItem, PathSegment, PathTransform};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// A segment moved by a transform that changes its shape
+///
+/// The transformed curve is parameterised by distance along the original segment, and its own arc
+/// length is found numerically.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct TransformedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Untransformed segment
+    pub segment: Box<PathSegment<N>>,
+
+    /// Transform applied to the segment
+    pub transform: PathTransform<N>,
+
+    /// Arc length samples along the transformed segment
+    arc_length: ArcLengthTable,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> TransformedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Transform a segment
+    pub fn new(segment: PathSegment<N>, transform: PathTransform<N>) -> Self {
+        // Sample between switching points, where the curve may not be smooth
+        let length = segment.len();
+        let mut breakpoints = vec![0.0];
+
+        breakpoints.extend(
+            segment
+                .switching_points()
+                .into_iter()
+                .filter(|point| *point > 0.0 && *point < length),
+        );
+        breakpoints.push(length);
+
+        let mut transformed = Self {
+            segment: Box::new(segment),
+            transform,
+            arc_length: ArcLengthTable::default(),
+            start_offset: 0.0,
+            end_offset: 0.0,
+        };
+
+        transformed.arc_length = ArcLengthTable::new(&transformed, &breakpoints);
+        transformed.end_offset = transformed.arc_length.length();
+
+        transformed
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.arc_length.length(),
+            ..self
+        }
+    }
+
+    /// Get this segment traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        Self::new(self.segment.reversed(), self.transform.clone())
+            .with_start_offset(self.start_offset)
+    }
+
+    /// Get this segment moved by another transform, with the same start offset
+    ///
+    /// Both transforms are combined and applied to the untransformed segment.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Self {
+        Self::new(*self.segment.clone(), self.transform.then(transform))
+            .with_start_offset(self.start_offset)
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get switching points where a component of the tangent crosses zero or curvature peaks,
+    /// relative to the start of this segment
+    pub fn switching_points(&self) -> Vec<f64> {
+        self.arc_length.switching_points(self)
+    }
+}
+
+impl<N> ParametricCurve<N> for TransformedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the position, first derivative and second derivative at a distance along the original
+    /// segment
+    fn derivatives(&self, parameter: f64) -> (Coord<N>, Coord<N>, Coord<N>) {
+        let distance = self.segment.start_offset() + parameter;
+        let (tangent, curvature) = self.segment.tangent_and_curvature(distance);
+
+        (
+            self.transform
+                .transform_point(&self.segment.position(distance)),
+            self.transform.transform_vector(&tangent),
+            self.transform.transform_vector(&curvature),
+        )
+    }
+}
+
+impl<N> PathItem<N> for TransformedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get length of the transformed segment
+    fn len(&self) -> f64 {
+        self.arc_length.length()
+    }
+
+    /// Get position at a point along the segment
+    fn position(&self, distance_along_segment: f64) -> Coord<N> {
+        let parameter = self
+            .arc_length
+            .parameter_at(self, distance_along_segment - self.start_offset);
+
+        self.derivatives(parameter).0
+    }
+
+    /// Get the unit tangent at a point along the segment
+    fn tangent(&self, distance_along_segment: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_segment).0
+    }
+
+    /// Get the curvature at a point along the segment
+    fn curvature(&self, distance_along_segment: f64) -> Coord<N> {
+        self.tangent_and_curvature(distance_along_segment).1
+    }
+
+    fn tangent_and_curvature(&self, distance_along_segment: f64) -> (Coord<N>, Coord<N>) {
+        self.arc_length
+            .tangent_and_curvature(self, distance_along_segment - self.start_offset)
+    }
+}
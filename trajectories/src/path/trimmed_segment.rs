@@ -0,0 +1,173 @@
+use super::{PathItem, PathSegment, PathTransform};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Part of a segment that cannot be trimmed to a segment of the same kind
+///
+/// The untrimmed segment keeps its own start offset, and distances along this segment are mapped
+/// onto the part of it between `from` and `to`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound(
+        serialize = "Coord<N>: serde::Serialize",
+        deserialize = "Coord<N>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct TrimmedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Untrimmed segment
+    pub segment: Box<PathSegment<N>>,
+
+    /// Distance along the untrimmed segment's path where the trimmed part starts
+    pub from: f64,
+
+    /// Distance along the untrimmed segment's path where the trimmed part ends
+    pub to: f64,
+
+    /// Path start offset
+    pub start_offset: f64,
+
+    /// Start offset plus length
+    pub end_offset: f64,
+}
+
+impl<N> TrimmedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get the part of a segment between two distances along the path
+    pub fn new(segment: PathSegment<N>, from: f64, to: f64) -> Self {
+        Self {
+            segment: Box::new(segment),
+            from,
+            to,
+            start_offset: 0.0,
+            end_offset: to - from,
+        }
+    }
+
+    /// Clone with a start offset
+    pub fn with_start_offset(self, start_offset: f64) -> Self {
+        Self {
+            start_offset,
+            end_offset: start_offset + self.to - self.from,
+            ..self
+        }
+    }
+
+    /// Get this segment traversed from end to start, with the same start offset
+    pub fn reversed(&self) -> Self {
+        let start = self.segment.start_offset();
+        let end = self.segment.end_offset();
+
+        Self {
+            segment: Box::new(self.segment.reversed()),
+            from: start + end - self.to,
+            to: start + end - self.from,
+            ..self.clone()
+        }
+    }
+
+    /// Get this segment moved by a transform, with the same start offset
+    ///
+    /// Returns `None` if the transform scales lengths by different amounts in different directions,
+    /// as the trimmed distances would no longer match the transformed segment.
+    pub fn transformed(&self, transform: &PathTransform<N>) -> Option<Self> {
+        let scale = transform.similarity_scale()?;
+        let start = self.segment.start_offset();
+
+        Some(
+            Self {
+                segment: Box::new(self.segment.transformed(transform)),
+                from: start + (self.from - start) * scale,
+                to: start + (self.to - start) * scale,
+                ..self.clone()
+            }
+            .with_start_offset(self.start_offset),
+        )
+    }
+
+    /// Get the part of this segment between two distances along the path, starting at `from`
+    pub fn trim(&self, from: f64, to: f64) -> Self {
+        Self {
+            segment: self.segment.clone(),
+            from: self.from + from - self.start_offset,
+            to: self.from + to - self.start_offset,
+            ..self.clone()
+        }
+        .with_start_offset(from)
+    }
+
+    /// Get end offset
+    pub fn end_offset(&self) -> f64 {
+        self.end_offset
+    }
+
+    /// Get the switching points of the untrimmed segment that lie within the trimmed part,
+    /// relative to its start
+    pub fn switching_points(&self) -> Vec<f64> {
+        let start = self.segment.start_offset();
+        let length = self.len();
+
+        self.segment
+            .switching_points()
+            .into_iter()
+            .map(|point| start + point - self.from)
+            .filter(|point| *point > 0.0 && *point < length)
+            .collect()
+    }
+
+    /// Get the distance along the untrimmed segment's path for a distance along this segment
+    fn untrimmed(&self, distance_along_segment: f64) -> f64 {
+        self.from + distance_along_segment - self.start_offset
+    }
+}
+
+impl<N> PathItem<N> for TrimmedPathSegment<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Get length of the trimmed part
+    fn len(&self) -> f64 {
+        self.to - self.from
+    }
+
+    /// Get position at a point along the segment
+    fn position(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment
+            .position(self.untrimmed(distance_along_segment))
+    }
+
+    /// Get first derivative (tangent) at a point
+    fn tangent(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment.tangent(self.untrimmed(distance_along_segment))
+    }
+
+    /// Get second derivative (curvature) at a point
+    fn curvature(&self, distance_along_segment: f64) -> Coord<N> {
+        self.segment
+            .curvature(self.untrimmed(distance_along_segment))
+    }
+
+    fn tangent_and_curvature(&self, distance_along_segment: f64) -> (Coord<N>, Coord<N>) {
+        self.segment
+            .tangent_and_curvature(self.untrimmed(distance_along_segment))
+    }
+}
@@ -0,0 +1,48 @@
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    DefaultAllocator, DimName,
+};
+use std::fmt::Debug;
+
+/// User defined path segment geometry
+///
+/// Implement this trait to plan trajectories over geometry that isn't covered by the built in
+/// segments, then add it to a path with `PathSegment::custom`. Distances are measured along the
+/// segment from its own start, between `0.0` and `len()`; the path takes care of offsetting them.
+///
+/// `tangent` must return a unit vector, and `curvature` the derivative of the tangent with respect
+/// to distance, as the trajectory planner uses both to find velocity and acceleration limits.
+pub trait Segment<N>: Debug + Send + Sync
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+{
+    /// Get length of segment
+    fn len(&self) -> f64;
+
+    /// Get position at a distance from the start of the segment
+    fn position(&self, distance: f64) -> Coord<N>;
+
+    /// Get first derivative (tangent) at a distance from the start of the segment
+    fn tangent(&self, distance: f64) -> Coord<N>;
+
+    /// Get second derivative (curvature) at a distance from the start of the segment
+    fn curvature(&self, distance: f64) -> Coord<N>;
+
+    /// Get the tangent and curvature in one call
+    ///
+    /// Override this if both can be computed together more cheaply.
+    fn tangent_and_curvature(&self, distance: f64) -> (Coord<N>, Coord<N>) {
+        (self.tangent(distance), self.curvature(distance))
+    }
+
+    /// Get the switching points of this segment, relative to its start
+    ///
+    /// These are the distances where a component of the tangent changes sign, or where the
+    /// curvature changes abruptly or reaches a local maximum. Defaults to none.
+    fn switching_points(&self) -> Vec<f64> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,10 @@
+mod pose_path;
+mod pose_path_options;
+mod pose_trajectory;
+mod pose_trajectory_options;
+mod pose_trajectory_path;
+
+pub use self::pose_path::PosePath;
+pub use self::pose_path_options::PosePathOptions;
+pub use self::pose_trajectory::PoseTrajectory;
+pub use self::pose_trajectory_options::PoseTrajectoryOptions;
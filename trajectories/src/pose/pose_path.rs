@@ -0,0 +1,402 @@
+//! Paths through tool poses, with orientations interpolated by SLERP
+
+use super::PosePathOptions;
+use crate::{Coord, Path, PathError, PathItem, PathOptions};
+use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3, U3, U5};
+use std::sync::Arc;
+
+/// Axis of the planned path that holds the angle turned so far
+pub(crate) const ANGLE_AXIS: usize = 3;
+
+/// Weight of the distance moved so far when measuring the planned path, small enough to barely
+/// change the blends
+const DISTANCE_WEIGHT: f64 = 0.001;
+
+/// Distance either side of a point used to find how fast the orientation turns there
+const RATE_STEP: f64 = 0.000_01;
+
+/// A path through a list of poses, each made of a position and an orientation
+///
+/// Positions are joined by straight lines and orientations by SLERP, turning the shortest way
+/// around at an even rate along each line. Corners are blended as in `Path::from_waypoints`, and
+/// the orientation is blended between the rotations on either side of the corner as it goes
+/// around.
+///
+/// Internally, the path is planned through five axes: X, Y and Z, then the angle turned and the
+/// distance moved since the first pose. Both only increase along the path, so limiting their
+/// rates limits how fast the tool turns and moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosePath {
+    /// Path through the position, angle turned and distance moved at each pose, shared with
+    /// trajectories created by `PoseTrajectory::from_shared`
+    path: Arc<Path<U5>>,
+
+    /// Orientation at each pose
+    orientations: Vec<UnitQuaternion<f64>>,
+}
+
+impl PosePath {
+    /// Create a blended path through a list of poses
+    ///
+    /// # Panics
+    ///
+    /// Panics if the poses cannot form a valid path. Use `PosePath::try_from_poses` to get an
+    /// error instead.
+    pub fn from_poses(poses: &[Isometry3<f64>], options: PosePathOptions) -> Self {
+        Self::try_from_poses(poses, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a blended path through a list of poses, returning an error if they cannot form a
+    /// valid path
+    ///
+    /// Poses are checked as waypoints are in `Path::try_from_waypoints`, so two consecutive poses
+    /// must differ in position or orientation. The rotation weight must be a positive, finite
+    /// number.
+    pub fn try_from_poses(
+        poses: &[Isometry3<f64>],
+        options: PosePathOptions,
+    ) -> Result<Self, PathError> {
+        let PosePathOptions {
+            max_deviation,
+            blend,
+            rotation_weight,
+        } = options;
+
+        if !(rotation_weight.is_finite() && rotation_weight > 0.0) {
+            return Err(PathError::InvalidRotationWeight {
+                weight: rotation_weight,
+            });
+        }
+
+        let mut angle = 0.0;
+        let mut distance = 0.0;
+
+        let waypoints = poses
+            .iter()
+            .enumerate()
+            .map(|(index, pose)| {
+                if index > 0 {
+                    let previous = &poses[index - 1];
+
+                    angle += previous.rotation.angle_to(&pose.rotation);
+                    distance += (pose.translation.vector - previous.translation.vector).norm();
+                }
+
+                let position = &pose.translation.vector;
+
+                Coord::<U5>::new(position.x, position.y, position.z, angle, distance)
+            })
+            .collect::<Vec<_>>();
+
+        let path = Path::try_from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation,
+                blend,
+                axis_weights: Some(Coord::<U5>::new(
+                    1.0,
+                    1.0,
+                    1.0,
+                    rotation_weight,
+                    DISTANCE_WEIGHT,
+                )),
+                ..PathOptions::default()
+            },
+        )?;
+
+        Ok(Self {
+            path: Arc::new(path),
+            orientations: poses.iter().map(|pose| pose.rotation).collect(),
+        })
+    }
+
+    /// Get the path planned through, with the angle turned and distance moved as extra axes
+    pub(crate) fn path(&self) -> &Path<U5> {
+        &self.path
+    }
+
+    /// Get a shared reference to the path planned through
+    pub(crate) fn shared_path(&self) -> Arc<Path<U5>> {
+        Arc::clone(&self.path)
+    }
+
+    /// Get the length of the path, with turns measured using the rotation weight
+    pub fn len(&self) -> f64 {
+        self.path.len()
+    }
+
+    /// Get the position at a distance along the path
+    pub fn position(&self, distance: f64) -> Vector3<f64> {
+        self.path
+            .position(distance)
+            .fixed_rows::<U3>(0)
+            .into_owned()
+    }
+
+    /// Get the orientation at a distance along the path
+    pub fn orientation(&self, distance: f64) -> UnitQuaternion<f64> {
+        let index = self.path.segment_index_at_position(distance);
+        let angle = self.path.position(distance)[ANGLE_AXIS];
+
+        // Linear segment `i` joins poses `i` and `i + 1`, with a blend in between each pair
+        if index % 2 == 0 {
+            return self.turn(index / 2, angle);
+        }
+
+        let corner = (index + 1) / 2;
+        let corner_angle = self.path.waypoints()[corner][ANGLE_AXIS];
+        let blend = &self.path.segments[index];
+        let start = blend.position(blend.start_offset())[ANGLE_AXIS];
+        let end = blend.position(blend.end_offset())[ANGLE_AXIS];
+
+        if end <= start {
+            return self.orientations[corner];
+        }
+
+        // Split the angle turned in the blend between the rotation after the corner and the
+        // rotation before it, applied in that order to the corner's orientation. Their rates add
+        // up to the rate of the angle axis, so the orientation never turns faster than the
+        // trajectory limits allow. The share of the rotation after the corner eases in, so the
+        // orientation turns smoothly at both ends of the blend.
+        let after = end - corner_angle;
+        let progress = ((angle - start) / (end - start)).max(0.0).min(1.0);
+        let turned_after = (end - start) * eased_share(progress, after / (end - start));
+        let turned_before = angle - corner_angle - turned_after;
+
+        self.rotation(corner, turned_after)
+            * self.rotation(corner - 1, turned_before)
+            * self.orientations[corner]
+    }
+
+    /// Get the position and orientation at a distance along the path
+    pub fn pose(&self, distance: f64) -> Isometry3<f64> {
+        Isometry3::from_parts(
+            Translation3::from(self.position(distance)),
+            self.orientation(distance),
+        )
+    }
+
+    /// Get the rate the orientation turns at a distance along the path, as a rotation axis scaled
+    /// by the angle turned per unit distance
+    pub(crate) fn angular_rate(&self, distance: f64) -> Vector3<f64> {
+        let before = (distance - RATE_STEP).max(0.0);
+        let after = (distance + RATE_STEP).min(self.len());
+
+        self.orientation(before)
+            .rotation_to(&self.orientation(after))
+            .scaled_axis()
+            / (after - before)
+    }
+
+    /// Get the orientation after turning `angle` from the first pose, on the rotation from pose
+    /// `index` to the next pose
+    ///
+    /// Angles outside the rotation keep turning the same way.
+    fn turn(&self, index: usize, angle: f64) -> UnitQuaternion<f64> {
+        let turned = angle - self.path.waypoints()[index][ANGLE_AXIS];
+
+        self.rotation(index, turned) * self.orientations[index]
+    }
+
+    /// Get the rotation made by turning `angle` on the rotation from pose `index` to the next pose
+    fn rotation(&self, index: usize, angle: f64) -> UnitQuaternion<f64> {
+        let waypoints = self.path.waypoints();
+        let total = waypoints[index + 1][ANGLE_AXIS] - waypoints[index][ANGLE_AXIS];
+
+        if total > 0.0 {
+            self.orientations[index]
+                .rotation_to(&self.orientations[index + 1])
+                .powf(angle / total)
+        } else {
+            UnitQuaternion::identity()
+        }
+    }
+}
+
+/// Get the share of a blend's angle turned so far by the rotation after its corner, `progress`
+/// of the way through the blend
+///
+/// The rate of the rotation after the corner rises smoothly from zero to the whole rate, so that
+/// `share` of the angle has been turned by it at the end of the blend. The rise is centred so the
+/// share works out, and made as wide as it can be.
+fn eased_share(progress: f64, share: f64) -> f64 {
+    let share = share.max(0.0).min(1.0);
+    let centre = 1.0 - share;
+    let width = 2.0 * share.min(1.0 - share);
+    let rise_start = centre - width / 2.0;
+
+    if progress <= rise_start {
+        0.0
+    } else if progress < rise_start + width {
+        // Integral of the smoothstep rate over the rise
+        let x = (progress - rise_start) / width;
+
+        width * (x.powi(3) - x.powi(4) / 2.0)
+    } else {
+        width / 2.0 + progress - (rise_start + width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Blend;
+    use std::f64::consts::PI;
+
+    fn pose(x: f64, y: f64, z: f64, roll: f64, pitch: f64, yaw: f64) -> Isometry3<f64> {
+        Isometry3::from_parts(
+            Translation3::new(x, y, z),
+            UnitQuaternion::from_euler_angles(roll, pitch, yaw),
+        )
+    }
+
+    fn poses() -> Vec<Isometry3<f64>> {
+        vec![
+            pose(0.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+            pose(1.0, 0.0, 0.0, 0.0, 0.0, PI / 2.0),
+            pose(1.0, 1.0, 0.5, PI / 4.0, 0.0, PI / 2.0),
+            pose(1.0, 1.0, 0.5, PI / 4.0, PI / 3.0, PI / 2.0),
+            pose(0.0, 2.0, 0.0, 0.0, 0.0, PI),
+        ]
+    }
+
+    fn options() -> PosePathOptions {
+        PosePathOptions {
+            max_deviation: 0.05,
+            ..PosePathOptions::default()
+        }
+    }
+
+    #[test]
+    fn slerp_between_poses() {
+        let path = PosePath::from_poses(
+            &[
+                pose(0.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+                pose(2.0, 0.0, 0.0, 0.0, 0.0, PI / 2.0),
+            ],
+            options(),
+        );
+
+        // Distance moved is measured too, but barely adds to the length
+        assert_near!(
+            path.len(),
+            (4.0 + PI * PI / 4.0 + (2.0 * DISTANCE_WEIGHT).powi(2)).sqrt()
+        );
+
+        for i in 0..=10 {
+            let ratio = i as f64 / 10.0;
+            let pose = path.pose(path.len() * ratio);
+
+            assert_near!(pose.translation.vector, Vector3::new(2.0 * ratio, 0.0, 0.0));
+            assert_near!(
+                pose.rotation,
+                UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0 * ratio)
+            );
+            assert_near!(
+                path.angular_rate(path.len() * ratio).z,
+                PI / 2.0 / path.len()
+            );
+        }
+    }
+
+    #[test]
+    fn shortest_rotation() {
+        // Turning from 170 to -170 degrees goes through 180 degrees
+        let path = PosePath::from_poses(
+            &[
+                pose(0.0, 0.0, 0.0, 0.0, 0.0, PI * 17.0 / 18.0),
+                pose(1.0, 0.0, 0.0, 0.0, 0.0, -PI * 17.0 / 18.0),
+            ],
+            options(),
+        );
+
+        assert_near!(path.path().waypoints()[1][ANGLE_AXIS], PI / 9.0);
+        assert_near!(
+            path.orientation(path.len() / 2.0)
+                .angle_to(&UnitQuaternion::from_euler_angles(0.0, 0.0, PI)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn turn_in_place() {
+        let path = PosePath::from_poses(
+            &[
+                pose(1.0, 2.0, 3.0, 0.0, 0.0, 0.0),
+                pose(1.0, 2.0, 3.0, 0.0, 1.5, 0.0),
+            ],
+            PosePathOptions {
+                rotation_weight: 2.0,
+                ..options()
+            },
+        );
+
+        assert_near!(path.len(), 3.0);
+        assert_near!(path.position(1.0), Vector3::new(1.0, 2.0, 3.0));
+        assert_near!(
+            path.orientation(1.0),
+            UnitQuaternion::from_euler_angles(0.0, 0.5, 0.0)
+        );
+    }
+
+    #[test]
+    fn blend_orientations() {
+        for blend in [Blend::Circular, Blend::Clothoid].iter() {
+            let path = PosePath::from_poses(
+                &poses(),
+                PosePathOptions {
+                    blend: *blend,
+                    ..options()
+                },
+            );
+
+            // Ends of the path are at the first and last poses
+            assert_near!(path.pose(0.0), poses()[0]);
+            assert_near!(path.pose(path.len()), poses()[4]);
+
+            // Blends join the slerps either side without jumping or turning suddenly
+            let steps = 4000;
+            let step = path.len() / steps as f64;
+
+            for i in 1..steps {
+                let distance = i as f64 * step;
+                let rate = path.angular_rate(distance);
+
+                assert!(
+                    path.orientation(distance - step)
+                        .angle_to(&path.orientation(distance))
+                        < 0.01,
+                    "Orientation jumps at {}",
+                    distance
+                );
+                assert!(
+                    (path.angular_rate(distance + step) - rate).norm() < 0.05,
+                    "Turning rate jumps at {}",
+                    distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pose_errors() {
+        assert_eq!(
+            PosePath::try_from_poses(&poses()[0..1], options()),
+            Err(PathError::TooFewWaypoints { count: 1 })
+        );
+        assert_eq!(
+            PosePath::try_from_poses(&[poses()[1], poses()[2], poses()[2]], options()),
+            Err(PathError::ZeroLengthSegment { index: 1 })
+        );
+        assert_eq!(
+            PosePath::try_from_poses(
+                &poses(),
+                PosePathOptions {
+                    rotation_weight: 0.0,
+                    ..options()
+                }
+            ),
+            Err(PathError::InvalidRotationWeight { weight: 0.0 })
+        );
+    }
+}
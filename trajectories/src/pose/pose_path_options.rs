@@ -0,0 +1,27 @@
+use crate::Blend;
+
+/// Pose path creation options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PosePathOptions {
+    /// Maximum deviation from true ideal path
+    pub max_deviation: f64,
+
+    /// Type of curve used to blend corners
+    pub blend: Blend,
+
+    /// Distance that turning by one radian counts as when measuring the path
+    ///
+    /// Blends around corners turn and move at the same time, and are kept within `max_deviation`
+    /// of each pose measured with this weight. It does not change the trajectory limits.
+    pub rotation_weight: f64,
+}
+
+impl Default for PosePathOptions {
+    fn default() -> Self {
+        Self {
+            max_deviation: 0.001,
+            blend: Blend::Circular,
+            rotation_weight: 1.0,
+        }
+    }
+}
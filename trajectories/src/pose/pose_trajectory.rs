@@ -0,0 +1,278 @@
+//! Trajectories along pose paths
+
+use super::pose_trajectory_path::PoseTrajectoryPath;
+use super::{PosePath, PoseTrajectoryOptions};
+use crate::{Trajectory, TrajectoryError};
+use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3, U3, U5};
+use std::sync::Arc;
+
+/// Motion trajectory through a list of poses
+///
+/// A trajectory created with `PoseTrajectory::new()` borrows the `PosePath` it follows. Use
+/// `PoseTrajectory::from_shared()` to create a `'static` trajectory that can be stored alongside
+/// its path or sent to another thread. Each query takes a time along the trajectory, clamped to
+/// its start or end.
+#[derive(Debug)]
+pub struct PoseTrajectory<'a> {
+    /// Trajectory along the five axes the pose path is planned through
+    trajectory: Trajectory<'a, U5>,
+
+    /// Path this trajectory follows
+    path: PoseTrajectoryPath<'a>,
+}
+
+impl<'a> PoseTrajectory<'a> {
+    /// Create a new trajectory along a pose path, limited by the per-axis, linear and angular
+    /// limits in `options`
+    pub fn new(
+        path: &'a PosePath,
+        options: PoseTrajectoryOptions,
+    ) -> Result<Self, TrajectoryError> {
+        let trajectory = Trajectory::new(path.path(), options.planning_options())?;
+
+        Ok(Self {
+            trajectory,
+            path: PoseTrajectoryPath::Borrowed(path),
+        })
+    }
+
+    /// Get the path this trajectory follows
+    pub fn path(&self) -> &PosePath {
+        &self.path
+    }
+
+    /// Get duration of complete trajectory
+    pub fn duration(&self) -> f64 {
+        self.trajectory.duration()
+    }
+
+    /// Get the position at a time along the trajectory
+    pub fn position(&self, time: f64) -> Vector3<f64> {
+        self.trajectory
+            .position(time)
+            .fixed_rows::<U3>(0)
+            .into_owned()
+    }
+
+    /// Get the orientation at a time along the trajectory
+    pub fn orientation(&self, time: f64) -> UnitQuaternion<f64> {
+        self.path
+            .orientation(self.trajectory.state(time).path_position)
+    }
+
+    /// Get the position and orientation at a time along the trajectory
+    pub fn pose(&self, time: f64) -> Isometry3<f64> {
+        let state = self.trajectory.state(time);
+
+        Isometry3::from_parts(
+            Translation3::from(state.position.fixed_rows::<U3>(0).into_owned()),
+            self.path.orientation(state.path_position),
+        )
+    }
+
+    /// Get the linear velocity at a time along the trajectory
+    pub fn velocity(&self, time: f64) -> Vector3<f64> {
+        self.trajectory
+            .velocity(time)
+            .fixed_rows::<U3>(0)
+            .into_owned()
+    }
+
+    /// Get the angular velocity at a time along the trajectory, as a rotation axis scaled by the
+    /// rate of turning in radians per second
+    pub fn angular_velocity(&self, time: f64) -> Vector3<f64> {
+        let state = self.trajectory.state(time);
+
+        self.path.angular_rate(state.path_position) * state.path_velocity
+    }
+}
+
+impl PoseTrajectory<'static> {
+    /// Create a new trajectory that shares ownership of its path
+    ///
+    /// Accepts either a `PosePath` or an `Arc<PosePath>`. The resulting trajectory does not borrow
+    /// anything, so it can be stored next to the path or sent to another thread.
+    pub fn from_shared(
+        path: impl Into<Arc<PosePath>>,
+        options: PoseTrajectoryOptions,
+    ) -> Result<Self, TrajectoryError> {
+        let path = path.into();
+
+        let trajectory = Trajectory::from_shared(path.shared_path(), options.planning_options())?;
+
+        Ok(Self {
+            trajectory,
+            path: PoseTrajectoryPath::Shared(path),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::PosePathOptions;
+    use crate::{TrajectoryOptionsError, TRAJECTORY_EPSILON};
+    use std::f64::consts::PI;
+
+    fn pose(x: f64, y: f64, z: f64, yaw: f64) -> Isometry3<f64> {
+        Isometry3::new(Vector3::new(x, y, z), Vector3::z() * yaw)
+    }
+
+    fn options() -> PoseTrajectoryOptions {
+        PoseTrajectoryOptions {
+            velocity_limit: Vector3::new(1.0, 1.0, 1.0),
+            acceleration_limit: Vector3::new(2.0, 2.0, 2.0),
+            linear_velocity_limit: 1.0,
+            linear_acceleration_limit: 2.0,
+            angular_velocity_limit: 0.5,
+            angular_acceleration_limit: 1.0,
+            timestep: 0.001,
+            ..PoseTrajectoryOptions::default()
+        }
+    }
+
+    #[test]
+    fn linear_limit_along_diagonal() {
+        let path = PosePath::from_poses(
+            &[pose(0.0, 0.0, 0.0, 0.0), pose(4.0, 4.0, 0.0, 0.0)],
+            PosePathOptions::default(),
+        );
+
+        let trajectory = PoseTrajectory::new(&path, options()).unwrap();
+
+        // Each axis could move at 1.0, but the speed along the diagonal is limited to 1.0. Speeding
+        // up and slowing down at 2.0 takes a second, covering half a unit.
+        let length = 32.0f64.sqrt();
+
+        assert!((trajectory.duration() - (length - 0.5 + 1.0)).abs() < 0.01);
+        assert!((trajectory.velocity(trajectory.duration() / 2.0).norm() - 1.0).abs() < 0.001);
+        assert_near!(
+            trajectory.pose(trajectory.duration()),
+            pose(4.0, 4.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn angular_limit_turning_in_place() {
+        let path = PosePath::from_poses(
+            &[pose(1.0, 1.0, 1.0, 0.0), pose(1.0, 1.0, 1.0, 3.0)],
+            PosePathOptions::default(),
+        );
+
+        let trajectory = PoseTrajectory::new(&path, options()).unwrap();
+
+        // Turning at 0.5 rad/s after half a second spent speeding up and half slowing down
+        assert!((trajectory.duration() - (3.0 / 0.5 + 0.5)).abs() < 0.01);
+        assert_near!(
+            trajectory.angular_velocity(trajectory.duration() / 2.0),
+            Vector3::new(0.0, 0.0, 0.5)
+        );
+        assert_near!(
+            trajectory.position(trajectory.duration() / 2.0),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+        assert!(
+            trajectory
+                .orientation(trajectory.duration())
+                .angle_to(&UnitQuaternion::from_euler_angles(0.0, 0.0, 3.0))
+                < TRAJECTORY_EPSILON
+        );
+    }
+
+    #[test]
+    fn limits_respected_through_blends() {
+        let path = PosePath::from_poses(
+            &[
+                pose(0.0, 0.0, 0.0, 0.0),
+                pose(1.0, 0.0, 0.0, PI / 2.0),
+                pose(1.0, 1.0, 0.5, PI / 2.0),
+                Isometry3::new(
+                    Vector3::new(0.0, 1.0, 0.0),
+                    Vector3::new(0.5, 0.0, PI / 2.0),
+                ),
+                pose(0.0, 2.0, 0.0, -PI / 4.0),
+            ],
+            PosePathOptions {
+                max_deviation: 0.05,
+                ..PosePathOptions::default()
+            },
+        );
+
+        let options = options();
+        let trajectory = PoseTrajectory::new(&path, options).unwrap();
+        let steps = 2000;
+        let epsilon = 0.001;
+
+        for i in 0..=steps {
+            let time = trajectory.duration() * i as f64 / steps as f64;
+            let velocity = trajectory.velocity(time);
+
+            for axis in 0..3 {
+                assert!(velocity[axis].abs() <= options.velocity_limit[axis] + epsilon);
+            }
+
+            assert!(velocity.norm() <= options.linear_velocity_limit + epsilon);
+
+            // Blends between two rotations turn no faster than straight turns
+            assert!(
+                trajectory.angular_velocity(time).norm()
+                    <= options.angular_velocity_limit + epsilon
+            );
+        }
+
+        assert_near!(trajectory.pose(0.0), pose(0.0, 0.0, 0.0, 0.0));
+        assert_near!(
+            trajectory.pose(trajectory.duration()),
+            pose(0.0, 2.0, 0.0, -PI / 4.0)
+        );
+    }
+
+    #[test]
+    fn shared_trajectory() {
+        fn assert_send_sync_static<T: Send + Sync + 'static>(_: &T) {}
+
+        let path = Arc::new(PosePath::from_poses(
+            &[pose(0.0, 0.0, 0.0, 0.0), pose(1.0, 1.0, 0.0, 1.0)],
+            PosePathOptions::default(),
+        ));
+
+        let borrowed = PoseTrajectory::new(&path, options()).unwrap();
+        let shared = PoseTrajectory::from_shared(path.clone(), options()).unwrap();
+
+        assert_send_sync_static(&shared);
+        assert_near!(shared.duration(), borrowed.duration());
+        assert_eq!(shared.path(), &*path);
+
+        let duration = shared.duration();
+
+        let end = std::thread::spawn(move || shared.pose(duration))
+            .join()
+            .unwrap();
+
+        assert_near!(end, pose(1.0, 1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn locked_rotation() {
+        let path = PosePath::from_poses(
+            &[pose(0.0, 0.0, 0.0, 0.0), pose(1.0, 0.0, 0.0, 1.0)],
+            PosePathOptions::default(),
+        );
+
+        let result = PoseTrajectory::new(
+            &path,
+            PoseTrajectoryOptions {
+                angular_velocity_limit: 0.0,
+                ..options()
+            },
+        );
+
+        match result {
+            Err(TrajectoryError::InvalidOptions {
+                reason: TrajectoryOptionsError::LockedAxisMoved { axis: 3 },
+                ..
+            }) => (),
+            other => panic!("Expected locked rotation error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Coord, TrajectoryOptions};
+use nalgebra::{Vector3, U5};
+
+/// Pose trajectory creation options
+///
+/// The limits are applied to the five axes a `PosePath` is planned through, so an invalid
+/// angular limit is reported as axis 3 and an invalid linear limit as axis 4. Like the per-axis
+/// limits of `TrajectoryOptions`, a zero limit stops the path from turning or moving at all.
+#[derive(Debug, Clone, Copy)]
+pub struct PoseTrajectoryOptions {
+    /// Velocity limit for each of the X, Y and Z axes
+    pub velocity_limit: Vector3<f64>,
+
+    /// Acceleration limit for each of the X, Y and Z axes
+    pub acceleration_limit: Vector3<f64>,
+
+    /// Limit on the speed along the path
+    ///
+    /// Applied exactly along straight lines. In blends, the speed is kept at or below this limit.
+    pub linear_velocity_limit: f64,
+
+    /// Limit on the acceleration along the path
+    ///
+    /// Acceleration towards the inside of a blend is limited by the per-axis acceleration limits.
+    pub linear_acceleration_limit: f64,
+
+    /// Limit on the rate of turning, in radians per second
+    ///
+    /// Applied exactly while turning between two poses. In blends between two rotations, the rate
+    /// is kept at or below this limit while the axis of rotation changes.
+    pub angular_velocity_limit: f64,
+
+    /// Limit on how quickly the rate of turning changes, in radians per second squared
+    pub angular_acceleration_limit: f64,
+
+    /// Epsilon for comparing floats to a "close enough" threshold
+    pub epsilon: f64,
+
+    /// Timestep granularity that the trajectory should be generated to
+    pub timestep: f64,
+}
+
+impl Default for PoseTrajectoryOptions {
+    fn default() -> Self {
+        Self {
+            velocity_limit: Vector3::repeat(1.0),
+            acceleration_limit: Vector3::repeat(1.0),
+            linear_velocity_limit: 1.0,
+            linear_acceleration_limit: 1.0,
+            angular_velocity_limit: 1.0,
+            angular_acceleration_limit: 1.0,
+            epsilon: crate::TRAJECTORY_EPSILON,
+            timestep: 0.1,
+        }
+    }
+}
+
+impl PoseTrajectoryOptions {
+    /// Get options for each of the five axes a `PosePath` is planned through
+    pub(crate) fn planning_options(&self) -> TrajectoryOptions<U5> {
+        let limits = |per_axis: &Vector3<f64>, angular, linear| {
+            Coord::<U5>::new(per_axis.x, per_axis.y, per_axis.z, angular, linear)
+        };
+
+        TrajectoryOptions {
+            velocity_limit: limits(
+                &self.velocity_limit,
+                self.angular_velocity_limit,
+                self.linear_velocity_limit,
+            ),
+            acceleration_limit: limits(
+                &self.acceleration_limit,
+                self.angular_acceleration_limit,
+                self.linear_acceleration_limit,
+            ),
+            epsilon: self.epsilon,
+            timestep: self.timestep,
+        }
+    }
+}
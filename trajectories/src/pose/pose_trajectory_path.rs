@@ -0,0 +1,26 @@
+//! Pose path that a pose trajectory follows, either borrowed or shared
+
+use super::PosePath;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Reference to the pose path that a pose trajectory follows
+#[derive(Debug, Clone)]
+pub(crate) enum PoseTrajectoryPath<'a> {
+    /// Path borrowed from the caller
+    Borrowed(&'a PosePath),
+
+    /// Path shared with the caller, allowing the trajectory to be `'static`
+    Shared(Arc<PosePath>),
+}
+
+impl<'a> Deref for PoseTrajectoryPath<'a> {
+    type Target = PosePath;
+
+    fn deref(&self) -> &PosePath {
+        match self {
+            PoseTrajectoryPath::Borrowed(path) => path,
+            PoseTrajectoryPath::Shared(path) => path,
+        }
+    }
+}
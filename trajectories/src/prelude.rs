@@ -1,3 +1,3 @@
 //! Prelude
 
-pub use crate::path::PathItem;
+pub use crate::path::{PathItem, Segment};
@@ -0,0 +1,53 @@
+//! Paths and options shared between tests
+
+use super::TestCoord3;
+use crate::{Segment, TrajectoryOptions};
+use nalgebra::U3;
+use std::f64::consts::PI;
+
+/// Waypoints with corners of different angles, one of them out of the XY plane
+pub fn waypoints_fixture() -> Vec<TestCoord3> {
+    vec![
+        TestCoord3::new(0.0, 0.0, 0.0),
+        TestCoord3::new(1.0, 2.0, 0.0),
+        TestCoord3::new(1.5, 1.5, 0.0),
+        TestCoord3::new(3.0, 5.0, 0.0),
+        TestCoord3::new(4.0, 6.0, 1.0),
+        TestCoord3::new(5.0, 5.0, 1.0),
+    ]
+}
+
+/// Trajectory options with a velocity limit of 1 and acceleration limit of 2 on every axis
+pub fn trajectory_options_fixture() -> TrajectoryOptions<U3> {
+    TrajectoryOptions {
+        velocity_limit: TestCoord3::new(1.0, 1.0, 1.0),
+        acceleration_limit: TestCoord3::new(2.0, 2.0, 2.0),
+        timestep: 0.01,
+        ..TrajectoryOptions::default()
+    }
+}
+
+/// Quarter of a unit circle in the XY plane, centered on the origin
+///
+/// Tangent components only reach zero at the ends and the curvature is constant, so it has no
+/// switching points and keeps the default empty list.
+#[derive(Debug)]
+pub struct QuarterCircle;
+
+impl Segment<U3> for QuarterCircle {
+    fn len(&self) -> f64 {
+        PI / 2.0
+    }
+
+    fn position(&self, distance: f64) -> TestCoord3 {
+        TestCoord3::new(distance.cos(), distance.sin(), 0.0)
+    }
+
+    fn tangent(&self, distance: f64) -> TestCoord3 {
+        TestCoord3::new(-distance.sin(), distance.cos(), 0.0)
+    }
+
+    fn curvature(&self, distance: f64) -> TestCoord3 {
+        -self.position(distance)
+    }
+}
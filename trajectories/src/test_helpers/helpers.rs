@@ -3,7 +3,7 @@
 pub use crate::path::CircularPathSegment;
 use crate::path::PathItem;
 use crate::path::{Continuity, Path as TrajPath, PathSegment};
-use crate::Coord;
+use crate::{Coord, Trajectory, TrajectoryOptions};
 use csv;
 use nalgebra::allocator::Allocator;
 use nalgebra::allocator::SameShapeVectorAllocator;
@@ -41,6 +41,30 @@ where
         )
 }
 
+/// Draw a curved segment as a polyline through evenly spaced points along it
+fn segment_line<N>(segment: &PathSegment<N>, stroke: &str, stroke_width: u32) -> SvgPath
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let start = segment.position(segment.start_offset());
+
+    let data = (1..=100).fold(Data::new().move_to((start[0], start[1])), |data, i| {
+        let pos = segment.position(segment.start_offset() + segment.len() * i as f64 / 100.0);
+
+        data.line_to((pos[0], pos[1]))
+    });
+
+    SvgPath::new()
+        .set("fill", "none")
+        .set("stroke", stroke)
+        .set("stroke-width", stroke_width)
+        .set("vector-effect", "non-scaling-stroke")
+        .set("d", data)
+}
+
 fn cross_centered_at<N>(center: &Coord<N>, stroke: &str, stroke_width: f32) -> SvgPath
 where
     N: DimName + Copy,
@@ -299,6 +323,21 @@ where
                             )))
                     })
             }
+
+            PathSegment::Clothoid(_) => document = document.add(segment_line(segment, "blue", 3)),
+
+            PathSegment::Spline(_) => document = document.add(segment_line(segment, "purple", 3)),
+
+            PathSegment::Helix(_) => document = document.add(segment_line(segment, "green", 3)),
+
+            PathSegment::BSpline(_) => document = document.add(segment_line(segment, "purple", 3)),
+
+            PathSegment::Trimmed(_) => document = document.add(segment_line(segment, "purple", 3)),
+            PathSegment::Transformed(_) => {
+                document = document.add(segment_line(segment, "purple", 3))
+            }
+
+            PathSegment::Custom(_) => document = document.add(segment_line(segment, "green", 3)),
         }
     }
 
@@ -329,6 +368,19 @@ pub fn debug_path_switching_points<N>(
 
             // PathSegment::Circular(ref circ) => document = document.add(draw_blend_circle(&circ)),
             PathSegment::Circular(_) => (),
+
+            PathSegment::Clothoid(_) => (),
+
+            PathSegment::Spline(_) => document = document.add(segment_line(segment, "red", 1)),
+
+            PathSegment::Helix(_) => document = document.add(segment_line(segment, "red", 1)),
+
+            PathSegment::BSpline(_) => document = document.add(segment_line(segment, "red", 1)),
+
+            PathSegment::Trimmed(_) => document = document.add(segment_line(segment, "red", 1)),
+            PathSegment::Transformed(_) => document = document.add(segment_line(segment, "red", 1)),
+
+            PathSegment::Custom(_) => document = document.add(segment_line(segment, "red", 1)),
         }
     }
 
@@ -401,6 +453,21 @@ pub fn debug_path_point<N>(
                             )))
                     })
             }
+
+            PathSegment::Clothoid(_) => document = document.add(segment_line(segment, "blue", 3)),
+
+            PathSegment::Spline(_) => document = document.add(segment_line(segment, "purple", 3)),
+
+            PathSegment::Helix(_) => document = document.add(segment_line(segment, "green", 3)),
+
+            PathSegment::BSpline(_) => document = document.add(segment_line(segment, "purple", 3)),
+
+            PathSegment::Trimmed(_) => document = document.add(segment_line(segment, "purple", 3)),
+            PathSegment::Transformed(_) => {
+                document = document.add(segment_line(segment, "purple", 3))
+            }
+
+            PathSegment::Custom(_) => document = document.add(segment_line(segment, "green", 3)),
         }
     }
 
@@ -491,3 +558,23 @@ where
 
     wtr.flush().expect("Flush");
 }
+
+/// Assert that sampled velocity and acceleration stay within the trajectory limits on every axis
+///
+/// The planner integrates in discrete timesteps, so a small overshoot is allowed.
+pub fn assert_within_limits<N>(trajectory: &Trajectory<N>, options: &TrajectoryOptions<N>)
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    for sample in trajectory.sample(0.01).unwrap() {
+        let acceleration = trajectory.acceleration(sample.time);
+
+        for axis in 0..N::dim() {
+            assert!(sample.velocity[axis].abs() <= options.velocity_limit[axis] + 1e-3);
+            assert!(acceleration[axis].abs() <= options.acceleration_limit[axis] * 1.05);
+        }
+    }
+}
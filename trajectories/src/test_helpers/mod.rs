@@ -2,9 +2,11 @@
 //!
 //! These utilities should NOT be used in production code
 
+mod fixtures;
 mod helpers;
 mod profile;
 
+pub use self::fixtures::*;
 pub use self::helpers::*;
 pub use self::profile::*;
 pub use crate::trajectory::trajectory_step::TrajectoryStep;
@@ -0,0 +1,56 @@
+//! Full kinematic state at a point in time along a trajectory
+
+use super::TrajectoryStep;
+use crate::path::{Path, PathItem};
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Position, velocity and acceleration of each axis at a point in time along a trajectory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KinematicState<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    /// Position of each axis
+    pub position: Coord<N>,
+
+    /// Velocity of each axis
+    pub velocity: Coord<N>,
+
+    /// Acceleration of each axis
+    pub acceleration: Coord<N>,
+
+    /// Distance along the path
+    pub path_position: f64,
+
+    /// Velocity along the path
+    pub path_velocity: f64,
+}
+
+impl<N> KinematicState<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Evaluate the state of each axis from an interpolated step and path acceleration
+    pub(crate) fn from_path_step(path: &Path<N>, step: TrajectoryStep, acceleration: f64) -> Self {
+        let segment = path.segment_at_position(step.position);
+        let (tangent, curvature) = segment.tangent_and_curvature(step.position);
+
+        Self {
+            position: segment.position(step.position),
+            velocity: tangent * step.velocity,
+            acceleration: tangent * acceleration + curvature * step.velocity.powi(2),
+            path_position: step.position,
+            path_velocity: step.velocity,
+        }
+    }
+}
@@ -1,7 +1,23 @@
+mod kinematic_state;
+mod sample;
+mod trajectory_error;
 mod trajectory_options;
+mod trajectory_options_builder;
+mod trajectory_options_error;
+mod trajectory_path;
+mod trajectory_sampler;
+#[cfg(feature = "serde")]
+mod trajectory_serde;
 pub(crate) mod trajectory_step;
 
+pub use self::kinematic_state::KinematicState;
+pub use self::sample::{Sample, TrajectorySamples};
+pub use self::trajectory_error::TrajectoryError;
 pub use self::trajectory_options::TrajectoryOptions;
+pub use self::trajectory_options_builder::TrajectoryOptionsBuilder;
+pub use self::trajectory_options_error::TrajectoryOptionsError;
+use self::trajectory_path::TrajectoryPath;
+pub use self::trajectory_sampler::TrajectorySampler;
 pub(crate) use self::trajectory_step::TrajectoryStep;
 use crate::path::{Path, PathItem};
 use crate::trajectory_builder::TrajectoryBuilder;
@@ -11,9 +27,14 @@ use nalgebra::{
     storage::Owned,
     DefaultAllocator, DimName,
 };
+use std::sync::Arc;
 use std::time::Instant;
 
 /// Motion trajectory
+///
+/// A trajectory created with `Trajectory::new()` borrows the path it follows. Use
+/// `Trajectory::from_shared()` to create a `'static` trajectory that can be stored alongside its
+/// path or sent to another thread.
 #[derive(Debug)]
 pub struct Trajectory<'a, N>
 where
@@ -23,7 +44,7 @@ where
     Owned<f64, N>: Copy,
 {
     trajectory: Vec<TrajectoryStep>,
-    path: &'a Path<N>,
+    path: TrajectoryPath<'a, N>,
 }
 
 impl<'a, N> Trajectory<'a, N>
@@ -33,18 +54,35 @@ where
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
     Owned<f64, N>: Copy,
 {
-    // TODO: Stop panicking all over the place and actually use the error arm of this `Result`
     /// Create a new trajectory from a given path and max velocity and acceleration
-    pub fn new(path: &'a Path<N>, options: TrajectoryOptions<N>) -> Result<Self, String> {
+    pub fn new(path: &'a Path<N>, options: TrajectoryOptions<N>) -> Result<Self, TrajectoryError> {
+        let trajectory = Self::build(path, options)?;
+
+        Ok(Self {
+            path: TrajectoryPath::Borrowed(path),
+            trajectory,
+        })
+    }
+
+    /// Generate the steps of a trajectory along a path
+    fn build(
+        path: &Path<N>,
+        options: TrajectoryOptions<N>,
+    ) -> Result<Vec<TrajectoryStep>, TrajectoryError> {
         let start = Instant::now();
 
-        let builder = TrajectoryBuilder::from_path(path, options);
+        let builder = TrajectoryBuilder::from_path(path, options)?;
 
         let trajectory = builder.into_steps()?;
 
         info!("Built trajectory in {} ms", start.elapsed().as_millis());
 
-        Ok(Self { path, trajectory })
+        Ok(trajectory)
+    }
+
+    /// Get the path this trajectory follows
+    pub fn path(&self) -> &Path<N> {
+        &self.path
     }
 
     /// Get duration of complete trajectory
@@ -53,85 +91,82 @@ where
     }
 
     /// Get a position in n-dimensional space given a time along the trajectory
+    ///
+    /// Times outside the trajectory are clamped to its start or end.
     pub fn position(&self, time: f64) -> Coord<N> {
-        let (previous, current) = self.trajectory_segment(time);
-
-        // trace!(
-        //     "RS get_pos (time;prev_pos;prev_vel;curr_pos;curr_vel),{},{},{},{},{}",
-        //     time,
-        //     previous.position,
-        //     previous.velocity,
-        //     current.position,
-        //     current.velocity
-        // );
-        instrument!(
-            "get_pos",
-            (
-                time,
-                previous.position,
-                previous.velocity,
-                current.position,
-                current.velocity
-            )
-        );
-
-        let duration = current.time - previous.time;
-        let acceleration = 2.0
-            * (current.position - previous.position - duration * previous.velocity)
-            / duration.powi(2);
-
-        let duration = time - previous.time;
+        let step = self
+            .trajectory_segment(time)
+            .map(|(previous, current)| {
+                instrument!(
+                    "get_pos",
+                    (
+                        time,
+                        previous.position,
+                        previous.velocity,
+                        current.position,
+                        current.velocity
+                    )
+                );
+
+                previous.interpolate(current, time)
+            })
+            .unwrap_or_default();
 
-        let position = previous.position
-            + duration * previous.velocity
-            + 0.5 * duration.powi(2) * acceleration;
+        self.path.position(step.position)
+    }
 
-        self.path.position(position)
+    /// Get a position like `.position()`, with each periodic axis of the path wrapped into the
+    /// range from zero up to its period as described in `Path::wrap`
+    pub fn wrapped_position(&self, time: f64) -> Coord<N> {
+        self.path.wrap(&self.position(time))
     }
 
     /// Get velocity for each joint at a time along the path
     pub fn velocity(&self, time: f64) -> Coord<N> {
-        let (previous, current) = self.trajectory_segment(time);
+        let (step, _) = self.path_state(time);
 
-        let duration = current.time - previous.time;
-        let acceleration = 2.0
-            * (current.position - previous.position - duration * previous.velocity)
-            / duration.powi(2);
-
-        let duration = time - previous.time;
-
-        let position = previous.position
-            + duration * previous.velocity
-            + 0.5 * duration.powi(2) * acceleration;
+        self.path.tangent(step.position) * step.velocity
+    }
 
-        let velocity = previous.velocity + duration * acceleration;
+    /// Get acceleration for each joint at a time along the path
+    pub fn acceleration(&self, time: f64) -> Coord<N> {
+        let (step, acceleration) = self.path_state(time);
+        let (tangent, curvature) = self.path.tangent_and_curvature(step.position);
 
-        self.path.tangent(position) * velocity
+        tangent * acceleration + curvature * step.velocity.powi(2)
     }
 
     /// Get position and velocity at a time along the path
     ///
     /// Use this method as a more optimised way of calling both `.position()` and `.velocity()
     pub fn position_and_velocity(&self, time: f64) -> (Coord<N>, Coord<N>) {
-        let (previous, current) = self.trajectory_segment(time);
+        let (step, _) = self.path_state(time);
 
-        let duration = current.time - previous.time;
-        let acceleration = 2.0
-            * (current.position - previous.position - duration * previous.velocity)
-            / duration.powi(2);
+        (
+            self.path.position(step.position),
+            self.path.tangent(step.position) * step.velocity,
+        )
+    }
 
-        let duration = time - previous.time;
+    /// Get position, velocity and acceleration at a time along the path
+    ///
+    /// Use this method as a more optimised way of calling `.position()`, `.velocity()` and
+    /// `.acceleration()`.
+    pub fn state(&self, time: f64) -> KinematicState<N> {
+        let (step, acceleration) = self.path_state(time);
 
-        let position = previous.position
-            + duration * previous.velocity
-            + 0.5 * duration.powi(2) * acceleration;
+        KinematicState::from_path_step(&self.path, step, acceleration)
+    }
 
-        let velocity = previous.velocity + duration * acceleration;
+    /// Get a state like `.state()`, with each periodic axis of the position wrapped into the
+    /// range from zero up to its period as described in `Path::wrap`
+    pub fn wrapped_state(&self, time: f64) -> KinematicState<N> {
+        let state = self.state(time);
 
-        (
-            self.path.position(position),
-            self.path.tangent(position) * velocity,
-        )
+        KinematicState {
+            position: self.path.wrap(&state.position),
+            ..state
+        }
     }
 
     /// Get a reference to the generated trajectory
@@ -139,46 +174,96 @@ where
         &self.trajectory
     }
 
-    // TODO: Return an Option
+    /// Create a sampler for querying the trajectory at increasing times
+    ///
+    /// The sampler remembers where the last query landed, so playing back the trajectory in order
+    /// does not search from the start for each sample.
+    pub fn sampler(&self) -> TrajectorySampler<'_, 'a, N> {
+        TrajectorySampler::new(self)
+    }
+
+    /// Iterate over samples of the trajectory spaced `dt` seconds apart
+    ///
+    /// The final sample always lies exactly at the end of the trajectory. Returns an error if `dt`
+    /// is not a finite positive number.
+    pub fn sample(&self, dt: f64) -> Result<TrajectorySamples<'_, 'a, N>, TrajectoryError> {
+        TrajectorySamples::new(self.sampler(), self.duration(), dt)
+    }
+
     /// Get the (previous_segment, segment) of the trajectory that the given time lies on
     ///
     /// This gets an interval of the trajectory along which `time` lies. Other methods interpolate
-    /// along this interval and do things like find the exact position at the given time.
-    fn trajectory_segment(&self, time: f64) -> (&TrajectoryStep, &TrajectoryStep) {
-        // If there is only one segment, create a "window" over just itself
-        // TODO: Gracefully handle case where trajectory is empty
-        if self.trajectory.len() < 2 {
-            return (&self.trajectory[0], &self.trajectory[0]);
-        }
+    /// along this interval and do things like find the exact position at the given time. Times
+    /// outside the trajectory return the first or last interval. Returns `None` if the trajectory
+    /// is empty.
+    fn trajectory_segment(&self, time: f64) -> Option<(&TrajectoryStep, &TrajectoryStep)> {
+        segment_index(&self.trajectory, time).map(|index| segment_window(&self.trajectory, index))
+    }
 
-        self.trajectory
-            .windows(2)
-            .find_map(|window| {
-                if let [ref prev, ref curr] = window {
-                    if time >= prev.time && time <= curr.time {
-                        Some((prev, curr))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| {
-                panic!(
-                    "Expected to find traj segment for time {} (total time {:?})",
-                    time,
-                    self.trajectory.last().map(|s| s.time)
+    /// Get the interpolated position, velocity and acceleration along the path at a given time
+    fn path_state(&self, time: f64) -> (TrajectoryStep, f64) {
+        self.trajectory_segment(time)
+            .map(|(previous, current)| {
+                (
+                    previous.interpolate(current, time),
+                    previous.interpolate_acceleration(current, time),
                 )
             })
+            .unwrap_or_default()
+    }
+}
+
+impl<N> Trajectory<'static, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a new trajectory that shares ownership of its path
+    ///
+    /// Accepts either a `Path` or an `Arc<Path>`. The resulting trajectory does not borrow
+    /// anything, so it can be stored next to the path or sent to another thread.
+    pub fn from_shared(
+        path: impl Into<Arc<Path<N>>>,
+        options: TrajectoryOptions<N>,
+    ) -> Result<Self, TrajectoryError> {
+        let path = path.into();
+
+        let trajectory = Self::build(&path, options)?;
+
+        Ok(Self {
+            path: TrajectoryPath::Shared(path),
+            trajectory,
+        })
     }
 }
 
+/// Find the index of the step at the end of the interval that `time` lies on
+///
+/// If there is only one step, a "window" over just itself is used. Times outside the steps are
+/// clamped to the first or last interval.
+fn segment_index(steps: &[TrajectoryStep], time: f64) -> Option<usize> {
+    if steps.is_empty() {
+        return None;
+    }
+
+    let index = steps.partition_point(|step| step.time < time);
+
+    Some(index.max(1).min(steps.len() - 1))
+}
+
+/// Get the (previous, current) steps of the interval ending at `index`
+fn segment_window(steps: &[TrajectoryStep], index: usize) -> (&TrajectoryStep, &TrajectoryStep) {
+    (&steps[index.saturating_sub(1)], &steps[index])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_helpers::*;
-    use crate::PathOptions;
+    use crate::{PathOptions, PathSegment, Segment, TrajectoryOptionsError};
+    use std::f64::consts::PI;
 
     #[test]
     fn create_example_cpp_trajectory() {
@@ -197,6 +282,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.001,
+                ..PathOptions::default()
             },
         );
 
@@ -212,26 +298,371 @@ mod tests {
         )
         .unwrap();
 
-        let mut t = 0.0;
         let duration = traj.duration();
 
-        while t < duration {
-            let p = traj.position(t);
-            let v = traj.velocity(t);
+        for sample in traj.sample(0.1).unwrap() {
+            rows.push(TrajectoryStepRow::from_coords(
+                sample.time,
+                &sample.position,
+                &sample.velocity,
+            ));
+        }
 
-            rows.push(TrajectoryStepRow::from_coords(t, &p, &v));
+        write_debug_csv("../target/plot_native.csv".into(), &rows);
+
+        assert_eq!(traj.trajectory.len(), 14814);
+        assert_near!(duration, 14.802832847319937);
+    }
 
-            t += 0.1;
+    #[test]
+    fn trajectory_segment_lookup() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.01,
+                ..TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
+
+        let duration = traj.duration();
+
+        for i in 0..=100 {
+            let time = duration * i as f64 / 100.0;
+
+            let expected = traj
+                .trajectory
+                .windows(2)
+                .find(|window| time >= window[0].time && time <= window[1].time)
+                .map(|window| (&window[0], &window[1]));
+
+            assert_eq!(traj.trajectory_segment(time), expected);
         }
+    }
 
-        let p_final = traj.position(duration);
-        let v_final = traj.velocity(duration);
+    #[test]
+    fn out_of_range_times_are_clamped() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
 
-        rows.push(TrajectoryStepRow::from_coords(duration, &p_final, &v_final));
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
 
-        write_debug_csv("../target/plot_native.csv".into(), &rows);
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.01,
+                ..TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
 
-        assert_eq!(traj.trajectory.len(), 14814);
-        assert_near!(duration, 14.802832847319937);
+        let duration = traj.duration();
+
+        assert_near!(traj.position(-1.0), TestCoord3::new(0.0, 0.0, 0.0));
+        assert_near!(traj.velocity(-1.0), TestCoord3::new(0.0, 0.0, 0.0));
+        assert_near!(traj.position(duration + 1.0), traj.position(duration));
+        assert_near!(traj.velocity(duration + 1.0), traj.velocity(duration));
+        assert_near!(
+            traj.position(duration + 1.0),
+            TestCoord3::new(1.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn empty_trajectory_segment() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let traj = Trajectory {
+            trajectory: Vec::new(),
+            path: TrajectoryPath::Borrowed(&path),
+        };
+
+        assert_eq!(traj.trajectory_segment(1.0), None);
+        assert_near!(traj.position(1.0), TestCoord3::new(0.0, 0.0, 0.0));
+        assert_near!(traj.sampler().position(1.0), TestCoord3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn acceleration_matches_velocity_derivative() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(
+            &waypoints,
+            PathOptions {
+                max_deviation: 0.1,
+                ..PathOptions::default()
+            },
+        );
+
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.001,
+                ..TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
+
+        let duration = traj.duration();
+        let h = 0.00001;
+
+        // Path acceleration is constant between steps, so difference velocity around the middle
+        // of each interval
+        for window in traj.trajectory.windows(2).step_by(10) {
+            let time = (window[0].time + window[1].time) / 2.0;
+
+            let derivative = (traj.velocity(time + h) - traj.velocity(time - h)) / (2.0 * h);
+
+            assert!(
+                (traj.acceleration(time) - derivative).amax() < 0.0001,
+                "Acceleration {:?} does not match velocity derivative {:?} at time {}",
+                traj.acceleration(time),
+                derivative,
+                time
+            );
+        }
+
+        // Trajectory is at rest outside its duration
+        assert_near!(traj.acceleration(-1.0), TestCoord3::zeros());
+        assert_near!(traj.acceleration(duration + 1.0), TestCoord3::zeros());
+    }
+
+    #[test]
+    fn state_matches_individual_queries() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.01,
+                ..TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
+
+        let duration = traj.duration();
+
+        for i in 0..=100 {
+            let time = duration * i as f64 / 100.0;
+
+            let state = traj.state(time);
+
+            assert_near!(state.position, traj.position(time));
+            assert_near!(state.velocity, traj.velocity(time));
+            assert_near!(state.acceleration, traj.acceleration(time));
+            assert_near!(state.position, path.position(state.path_position));
+            assert_near!(
+                state.velocity,
+                path.tangent(state.path_position) * state.path_velocity
+            );
+        }
+    }
+
+    #[test]
+    fn shared_trajectory() {
+        fn assert_send_sync_static<T: Send + Sync + 'static>(_: &T) {}
+
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let options = TrajectoryOptions {
+            timestep: 0.01,
+            ..TrajectoryOptions::default()
+        };
+
+        let path = Arc::new(Path::from_waypoints(&waypoints, PathOptions::default()));
+
+        let borrowed = Trajectory::new(&path, options).unwrap();
+        let shared = Trajectory::from_shared(path.clone(), options).unwrap();
+
+        assert_send_sync_static(&shared);
+        assert_eq!(shared.trajectory(), borrowed.trajectory());
+        assert_eq!(shared.path(), &*path);
+
+        let duration = shared.duration();
+
+        let end = std::thread::spawn(move || shared.position(duration))
+            .join()
+            .unwrap();
+
+        assert_near!(end, TestCoord3::new(1.0, 1.0, 0.0));
+
+        // Owned paths are moved into the trajectory
+        let owned = Trajectory::from_shared((*path).clone(), options).unwrap();
+
+        assert_eq!(owned.trajectory(), borrowed.trajectory());
+    }
+
+    #[test]
+    fn zero_timestep_is_an_error() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.0,
+                ..TrajectoryOptions::default()
+            },
+        );
+
+        match traj {
+            Err(TrajectoryError::InvalidOptions {
+                position, velocity, ..
+            }) => {
+                assert_eq!(position, 0.0);
+                assert_eq!(velocity, 0.0);
+            }
+            other => panic!("Expected invalid options error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn locked_axis_not_moved() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(1.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 10.0, 0.0),
+            TestCoord3::new(1.0, 10.0, 10.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let options = TrajectoryOptions::builder()
+            .timestep(0.001)
+            .epsilon(0.000001)
+            .lock_axis(0)
+            .build()
+            .unwrap();
+
+        let traj = Trajectory::new(&path, options).unwrap();
+
+        assert_near!(
+            traj.position(traj.duration()),
+            TestCoord3::new(1.0, 10.0, 10.0)
+        );
+
+        // Velocity limit of the other axes must still be respected
+        assert!(traj
+            .trajectory()
+            .iter()
+            .all(|step| step.velocity <= 1.0 + 0.001));
+    }
+
+    #[test]
+    fn locked_axis_moved() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let options = TrajectoryOptions::builder().lock_axis(2).build().unwrap();
+
+        match Trajectory::new(&path, options) {
+            Err(TrajectoryError::InvalidOptions {
+                position, reason, ..
+            }) => {
+                assert_eq!(reason, TrajectoryOptionsError::LockedAxisMoved { axis: 2 });
+                assert!(position > 0.9 && position < 1.1);
+            }
+            other => panic!("Expected locked axis error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn locked_axis_moved_between_samples() {
+        /// Straight line along X that lifts off Z and comes back down twice, flat at its start,
+        /// middle and end
+        #[derive(Debug)]
+        struct Bumps;
+
+        impl Segment<nalgebra::U3> for Bumps {
+            fn len(&self) -> f64 {
+                1.0
+            }
+
+            fn position(&self, distance: f64) -> TestCoord3 {
+                TestCoord3::new(distance, 0.0, 0.1 * (2.0 * PI * distance).sin().powi(2))
+            }
+
+            fn tangent(&self, distance: f64) -> TestCoord3 {
+                TestCoord3::new(1.0, 0.0, 0.2 * PI * (4.0 * PI * distance).sin())
+            }
+
+            fn curvature(&self, distance: f64) -> TestCoord3 {
+                TestCoord3::new(0.0, 0.0, 0.8 * PI * PI * (4.0 * PI * distance).cos())
+            }
+        }
+
+        let path = Path::from_segments(vec![PathSegment::custom(Bumps)]);
+        let options = TrajectoryOptions::builder().lock_axis(2).build().unwrap();
+
+        match Trajectory::new(&path, options) {
+            Err(TrajectoryError::InvalidOptions {
+                position, reason, ..
+            }) => {
+                assert_eq!(reason, TrajectoryOptionsError::LockedAxisMoved { axis: 2 });
+                assert!(position > 0.0 && position < 0.5);
+            }
+            other => panic!("Expected locked axis error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn locked_axis_moved_less_than_epsilon() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(1.0, 0.0, 0.000_000_1),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let options = TrajectoryOptions::builder()
+            .timestep(0.001)
+            .epsilon(0.000_001)
+            .lock_axis(2)
+            .build()
+            .unwrap();
+
+        // The locked axis doesn't limit the velocity or acceleration, so the planner doesn't stall
+        let traj = Trajectory::new(&path, options).unwrap();
+
+        assert!((traj.duration() - 2.0).abs() < 0.01);
     }
 }
@@ -0,0 +1,213 @@
+//! Fixed rate sampling of a trajectory
+
+use super::TrajectorySampler;
+use crate::{Coord, TrajectoryError};
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// A sample of a trajectory at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    /// Time along the trajectory
+    pub time: f64,
+
+    /// Position of each axis
+    pub position: Coord<N>,
+
+    /// Velocity of each axis
+    pub velocity: Coord<N>,
+}
+
+/// Iterator over evenly spaced samples of a trajectory
+///
+/// Created by `Trajectory::sample()`.
+#[derive(Debug)]
+pub struct TrajectorySamples<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    sampler: TrajectorySampler<'t, 'a, N>,
+    dt: f64,
+    duration: f64,
+    /// Index of the next sample
+    index: usize,
+    /// Index of the final sample, which lies exactly at the end of the trajectory
+    last: usize,
+}
+
+impl<'t, 'a, N> TrajectorySamples<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a new sample iterator
+    ///
+    /// Returns an error if `dt` is not a finite positive number.
+    pub(crate) fn new(
+        sampler: TrajectorySampler<'t, 'a, N>,
+        duration: f64,
+        dt: f64,
+    ) -> Result<Self, TrajectoryError> {
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(TrajectoryError::InvalidSampleInterval { interval: dt });
+        }
+
+        // Don't emit a sample a rounding error away from the end of the trajectory as well as the
+        // end sample itself
+        let last = (duration / dt - crate::TRAJECTORY_EPSILON).ceil().max(0.0) as usize;
+
+        Ok(Self {
+            sampler,
+            dt,
+            duration,
+            index: 0,
+            last,
+        })
+    }
+
+    /// Wrap each periodic axis of the sampled positions into the range from zero up to its
+    /// period, as described in `Path::wrap`
+    pub fn with_wrapped_positions(self) -> Self {
+        Self {
+            sampler: self.sampler.with_wrapped_positions(),
+            ..self
+        }
+    }
+}
+
+impl<'t, 'a, N> Iterator for TrajectorySamples<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    type Item = Sample<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index > self.last {
+            return None;
+        }
+
+        // Multiply instead of accumulating to prevent drift
+        let time = if self.index == self.last {
+            self.duration
+        } else {
+            self.index as f64 * self.dt
+        };
+
+        self.index += 1;
+
+        let (position, velocity) = self.sampler.position_and_velocity(time);
+
+        Some(Sample {
+            time,
+            position,
+            velocity,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.last + 1).saturating_sub(self.index);
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'t, 'a, N> ExactSizeIterator for TrajectorySamples<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::*;
+    use crate::{Path, PathOptions, Trajectory, TrajectoryError};
+
+    #[test]
+    fn samples_are_evenly_spaced() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+        let traj = Trajectory::new(&path, trajectory_options_fixture()).unwrap();
+
+        let duration = traj.duration();
+        let samples = traj.sample(0.1).unwrap().collect::<Vec<_>>();
+
+        assert_eq!(samples.len(), (duration / 0.1).ceil() as usize + 1);
+        assert_eq!(traj.sample(0.1).unwrap().len(), samples.len());
+
+        for (i, sample) in samples[0..samples.len() - 1].iter().enumerate() {
+            assert_eq!(sample.time, i as f64 * 0.1);
+            assert_near!(sample.position, traj.position(sample.time));
+            assert_near!(sample.velocity, traj.velocity(sample.time));
+        }
+
+        let end = samples.last().unwrap();
+
+        assert_eq!(end.time, duration);
+        assert_near!(end.position, TestCoord3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn sample_interval_divides_duration() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+        let traj = Trajectory::new(&path, trajectory_options_fixture()).unwrap();
+
+        let duration = traj.duration();
+        let samples = traj.sample(duration / 10.0).unwrap().collect::<Vec<_>>();
+
+        // No duplicate end sample from rounding
+        assert_eq!(samples.len(), 11);
+        assert_eq!(samples.last().unwrap().time, duration);
+        assert!(samples[9].time < duration);
+    }
+
+    #[test]
+    fn invalid_sample_interval() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+        let traj = Trajectory::new(&path, trajectory_options_fixture()).unwrap();
+
+        assert_eq!(
+            traj.sample(0.0).err(),
+            Some(TrajectoryError::InvalidSampleInterval { interval: 0.0 })
+        );
+        assert_eq!(
+            traj.sample(-0.1).err(),
+            Some(TrajectoryError::InvalidSampleInterval { interval: -0.1 })
+        );
+        assert!(traj.sample(std::f64::NAN).is_err());
+        assert!(traj.sample(std::f64::INFINITY).is_err());
+    }
+}
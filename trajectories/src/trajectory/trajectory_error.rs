@@ -0,0 +1,115 @@
+use super::TrajectoryOptionsError;
+use std::error::Error;
+use std::fmt;
+
+/// Errors that can occur when generating a trajectory
+///
+/// Variants raised while generating a trajectory, other than `SwitchingPointSearchPanicked`,
+/// carry the position along the path and the path velocity at the point where generation failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrajectoryError {
+    /// Forward integration produced a negative velocity
+    NegativeVelocity {
+        /// Position along the path
+        position: f64,
+        /// Path velocity
+        velocity: f64,
+    },
+
+    /// Backward integration did not intersect the trajectory generated by forward integration
+    BackwardIntegrationFailed {
+        /// Position along the path
+        position: f64,
+        /// Path velocity
+        velocity: f64,
+    },
+
+    /// Forward integration stopped at a limit curve, but no switching point was found after it to
+    /// continue from
+    SwitchingPointNotFound {
+        /// Position along the path where forward integration stopped
+        position: f64,
+        /// Path velocity
+        velocity: f64,
+    },
+
+    /// A thread searching the path for switching points panicked
+    SwitchingPointSearchPanicked,
+
+    /// Integration was attempted on a trajectory with no steps
+    EmptyTrajectory {
+        /// Position along the path
+        position: f64,
+        /// Path velocity
+        velocity: f64,
+    },
+
+    /// The trajectory options are invalid for the given path
+    InvalidOptions {
+        /// Position along the path
+        position: f64,
+        /// Path velocity
+        velocity: f64,
+        /// Reason the options are invalid
+        reason: TrajectoryOptionsError,
+    },
+
+    /// A trajectory was sampled at an interval that is not a finite positive number
+    InvalidSampleInterval {
+        /// Sample interval in seconds
+        interval: f64,
+    },
+}
+
+impl fmt::Display for TrajectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrajectoryError::NegativeVelocity { position, velocity } => write!(
+                f,
+                "Integrate forward velocity cannot be less than zero, position {}, velocity {}",
+                position, velocity
+            ),
+            TrajectoryError::BackwardIntegrationFailed { position, velocity } => write!(
+                f,
+                "Integrate backward did not hit start trajectory, position {}, velocity {}",
+                position, velocity
+            ),
+            TrajectoryError::SwitchingPointNotFound { position, velocity } => write!(
+                f,
+                "Could not find next switching point, position {}, velocity {}",
+                position, velocity
+            ),
+            TrajectoryError::SwitchingPointSearchPanicked => {
+                write!(f, "Switching point search panicked")
+            }
+            TrajectoryError::EmptyTrajectory { position, velocity } => write!(
+                f,
+                "Attempted to integrate an empty trajectory, position {}, velocity {}",
+                position, velocity
+            ),
+            TrajectoryError::InvalidOptions {
+                position,
+                velocity,
+                reason,
+            } => write!(
+                f,
+                "Invalid trajectory options: {}, position {}, velocity {}",
+                reason, position, velocity
+            ),
+            TrajectoryError::InvalidSampleInterval { interval } => write!(
+                f,
+                "Sample interval must be a finite positive number, got {}",
+                interval
+            ),
+        }
+    }
+}
+
+impl Error for TrajectoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TrajectoryError::InvalidOptions { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::{TrajectoryOptionsBuilder, TrajectoryOptionsError};
 use crate::Coord;
 use nalgebra::allocator::SameShapeVectorAllocator;
 use nalgebra::storage::Owned;
@@ -40,3 +41,138 @@ where
         }
     }
 }
+
+impl<N> TrajectoryOptions<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    /// Create a builder that validates each option
+    pub fn builder() -> TrajectoryOptionsBuilder<N> {
+        TrajectoryOptionsBuilder::new()
+    }
+
+    /// Check that every axis limit is zero or a finite positive number, and that the epsilon and
+    /// timestep are finite positive numbers
+    pub fn validate(&self) -> Result<(), TrajectoryOptionsError> {
+        if let Some((axis, value)) = invalid_limit(&self.velocity_limit) {
+            return Err(TrajectoryOptionsError::InvalidVelocityLimit { axis, value });
+        }
+
+        if let Some((axis, value)) = invalid_limit(&self.acceleration_limit) {
+            return Err(TrajectoryOptionsError::InvalidAccelerationLimit { axis, value });
+        }
+
+        if !(self.epsilon.is_finite() && self.epsilon > 0.0) {
+            return Err(TrajectoryOptionsError::InvalidEpsilon {
+                value: self.epsilon,
+            });
+        }
+
+        if !(self.timestep.is_finite() && self.timestep > 0.0) {
+            return Err(TrajectoryOptionsError::InvalidTimestep {
+                value: self.timestep,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether an axis is locked by a zero velocity or acceleration limit
+    ///
+    /// A locked axis must not be moved by the path the trajectory is generated for.
+    pub fn is_axis_locked(&self, axis: usize) -> bool {
+        self.velocity_limit[axis] == 0.0 || self.acceleration_limit[axis] == 0.0
+    }
+}
+
+/// Find the first axis limit that is negative, NaN or infinite
+fn invalid_limit<N>(limit: &Coord<N>) -> Option<(usize, f64)>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    limit
+        .iter()
+        .cloned()
+        .enumerate()
+        .find(|(_, value)| !(value.is_finite() && *value >= 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn builder_defaults() {
+        let options = TrajectoryOptions::<nalgebra::U3>::builder()
+            .build()
+            .unwrap();
+        let default = TrajectoryOptions::<nalgebra::U3>::default();
+
+        assert_eq!(options.velocity_limit, default.velocity_limit);
+        assert_eq!(options.acceleration_limit, default.acceleration_limit);
+        assert_eq!(options.epsilon, default.epsilon);
+        assert_eq!(options.timestep, default.timestep);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_limits() {
+        assert_eq!(
+            TrajectoryOptions::builder()
+                .velocity_limit(TestCoord3::new(1.0, -1.0, 1.0))
+                .build()
+                .unwrap_err(),
+            TrajectoryOptionsError::InvalidVelocityLimit {
+                axis: 1,
+                value: -1.0
+            }
+        );
+
+        match TrajectoryOptions::builder()
+            .acceleration_limit(TestCoord3::new(1.0, 1.0, std::f64::NAN))
+            .build()
+        {
+            Err(TrajectoryOptionsError::InvalidAccelerationLimit { axis: 2, .. }) => (),
+            other => panic!("Expected invalid acceleration limit, got {:?}", other),
+        }
+
+        assert_eq!(
+            TrajectoryOptions::<nalgebra::U3>::builder()
+                .timestep(0.0)
+                .build()
+                .unwrap_err(),
+            TrajectoryOptionsError::InvalidTimestep { value: 0.0 }
+        );
+        assert_eq!(
+            TrajectoryOptions::<nalgebra::U3>::builder()
+                .epsilon(-0.1)
+                .build()
+                .unwrap_err(),
+            TrajectoryOptionsError::InvalidEpsilon { value: -0.1 }
+        );
+    }
+
+    #[test]
+    fn builder_locks_axis() {
+        let options = TrajectoryOptions::<nalgebra::U3>::builder()
+            .lock_axis(2)
+            .build()
+            .unwrap();
+
+        assert!(!options.is_axis_locked(0));
+        assert!(options.is_axis_locked(2));
+        assert_eq!(options.velocity_limit, TestCoord3::new(1.0, 1.0, 0.0));
+
+        assert_eq!(
+            TrajectoryOptions::<nalgebra::U3>::builder()
+                .lock_axis(3)
+                .build()
+                .unwrap_err(),
+            TrajectoryOptionsError::AxisOutOfRange { axis: 3 }
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use super::{TrajectoryOptions, TrajectoryOptionsError};
+use crate::Coord;
+use nalgebra::allocator::SameShapeVectorAllocator;
+use nalgebra::storage::Owned;
+use nalgebra::DefaultAllocator;
+use nalgebra::DimName;
+
+/// Builder for validated trajectory options
+///
+/// Created with `TrajectoryOptions::builder()`. Any option not set falls back to the value given by
+/// `TrajectoryOptions::default()`.
+#[derive(Debug, Clone, Copy)]
+pub struct TrajectoryOptionsBuilder<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    options: TrajectoryOptions<N>,
+
+    /// The first axis passed to `lock_axis` that doesn't exist
+    invalid_axis: Option<usize>,
+}
+
+impl<N> TrajectoryOptionsBuilder<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    Owned<f64, N>: Copy,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            options: TrajectoryOptions::default(),
+            invalid_axis: None,
+        }
+    }
+
+    /// Set the velocity limit for each axis
+    pub fn velocity_limit(self, velocity_limit: Coord<N>) -> Self {
+        Self {
+            options: TrajectoryOptions {
+                velocity_limit,
+                ..self.options
+            },
+            ..self
+        }
+    }
+
+    /// Set the acceleration limit for each axis
+    pub fn acceleration_limit(self, acceleration_limit: Coord<N>) -> Self {
+        Self {
+            options: TrajectoryOptions {
+                acceleration_limit,
+                ..self.options
+            },
+            ..self
+        }
+    }
+
+    /// Set the epsilon used for "close enough" float comparisons
+    pub fn epsilon(self, epsilon: f64) -> Self {
+        Self {
+            options: TrajectoryOptions {
+                epsilon,
+                ..self.options
+            },
+            ..self
+        }
+    }
+
+    /// Set the timestep the trajectory is generated with
+    pub fn timestep(self, timestep: f64) -> Self {
+        Self {
+            options: TrajectoryOptions {
+                timestep,
+                ..self.options
+            },
+            ..self
+        }
+    }
+
+    /// Lock an axis by setting its velocity and acceleration limits to zero
+    ///
+    /// Generating a trajectory will fail if the path moves a locked axis.
+    pub fn lock_axis(mut self, axis: usize) -> Self {
+        if axis < self.options.velocity_limit.len() {
+            self.options.velocity_limit[axis] = 0.0;
+            self.options.acceleration_limit[axis] = 0.0;
+        } else {
+            self.invalid_axis = self.invalid_axis.or(Some(axis));
+        }
+
+        self
+    }
+
+    /// Validate and create the trajectory options
+    pub fn build(self) -> Result<TrajectoryOptions<N>, TrajectoryOptionsError> {
+        if let Some(axis) = self.invalid_axis {
+            return Err(TrajectoryOptionsError::AxisOutOfRange { axis });
+        }
+
+        self.options.validate()?;
+
+        Ok(self.options)
+    }
+}
@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::fmt;
+
+/// Reasons why a set of trajectory options is invalid
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrajectoryOptionsError {
+    /// An axis velocity limit is negative, NaN or infinite
+    InvalidVelocityLimit {
+        /// Axis index
+        axis: usize,
+        /// Given limit
+        value: f64,
+    },
+
+    /// An axis acceleration limit is negative, NaN or infinite
+    InvalidAccelerationLimit {
+        /// Axis index
+        axis: usize,
+        /// Given limit
+        value: f64,
+    },
+
+    /// The epsilon is not a finite number greater than zero
+    InvalidEpsilon {
+        /// Given epsilon
+        value: f64,
+    },
+
+    /// The timestep is not a finite number greater than zero
+    InvalidTimestep {
+        /// Given timestep
+        value: f64,
+    },
+
+    /// An axis index was given that is outside the number of axes of the trajectory
+    AxisOutOfRange {
+        /// Axis index
+        axis: usize,
+    },
+
+    /// An axis locked by a zero velocity or acceleration limit is moved by the path
+    LockedAxisMoved {
+        /// Axis index
+        axis: usize,
+    },
+}
+
+impl fmt::Display for TrajectoryOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrajectoryOptionsError::InvalidVelocityLimit { axis, value } => write!(
+                f,
+                "Velocity limit for axis {} must be zero or a positive number, got {}",
+                axis, value
+            ),
+            TrajectoryOptionsError::InvalidAccelerationLimit { axis, value } => write!(
+                f,
+                "Acceleration limit for axis {} must be zero or a positive number, got {}",
+                axis, value
+            ),
+            TrajectoryOptionsError::InvalidEpsilon { value } => {
+                write!(f, "Epsilon must be greater than zero, got {}", value)
+            }
+            TrajectoryOptionsError::InvalidTimestep { value } => {
+                write!(f, "Timestep must be greater than zero, got {}", value)
+            }
+            TrajectoryOptionsError::AxisOutOfRange { axis } => {
+                write!(f, "Axis {} does not exist", axis)
+            }
+            TrajectoryOptionsError::LockedAxisMoved { axis } => {
+                write!(f, "Axis {} is locked but is moved by the path", axis)
+            }
+        }
+    }
+}
+
+impl Error for TrajectoryOptionsError {}
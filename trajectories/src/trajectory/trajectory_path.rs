@@ -0,0 +1,43 @@
+//! Path that a trajectory follows, either borrowed or shared
+
+use crate::path::Path;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Reference to the path that a trajectory follows
+#[derive(Debug, Clone)]
+pub(crate) enum TrajectoryPath<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Path borrowed from the caller
+    Borrowed(&'a Path<N>),
+
+    /// Path shared with the caller, allowing the trajectory to be `'static`
+    Shared(Arc<Path<N>>),
+}
+
+impl<'a, N> Deref for TrajectoryPath<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    type Target = Path<N>;
+
+    fn deref(&self) -> &Path<N> {
+        match self {
+            TrajectoryPath::Borrowed(path) => path,
+            TrajectoryPath::Shared(path) => path,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::{segment_index, segment_window, KinematicState, Trajectory, TrajectoryStep};
+use crate::path::PathItem;
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+
+/// Stateful sampler over a trajectory
+///
+/// Remembers the trajectory interval of the last query. Monotonic playback only checks the
+/// current and next interval for each sample, falling back to a binary search for larger jumps.
+/// Positions can be wrapped along the path's periodic axes with `.with_wrapped_positions()`.
+#[derive(Debug)]
+pub struct TrajectorySampler<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    trajectory: &'t Trajectory<'a, N>,
+    index: usize,
+    wrapped: bool,
+}
+
+impl<'t, 'a, N> TrajectorySampler<'t, 'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    /// Create a new sampler starting at the beginning of the trajectory
+    pub(crate) fn new(trajectory: &'t Trajectory<'a, N>) -> Self {
+        let index = trajectory.trajectory.len().saturating_sub(1).min(1);
+
+        Self {
+            trajectory,
+            index,
+            wrapped: false,
+        }
+    }
+
+    /// Wrap each periodic axis of the positions this sampler returns into the range from zero up
+    /// to its period, as described in `Path::wrap`
+    pub fn with_wrapped_positions(self) -> Self {
+        Self {
+            wrapped: true,
+            ..self
+        }
+    }
+
+    /// Get a position in n-dimensional space given a time along the trajectory
+    pub fn position(&mut self, time: f64) -> Coord<N> {
+        let (step, _) = self.path_state(time);
+
+        self.wrap(self.trajectory.path.position(step.position))
+    }
+
+    /// Get velocity for each joint at a time along the path
+    pub fn velocity(&mut self, time: f64) -> Coord<N> {
+        let (step, _) = self.path_state(time);
+
+        self.trajectory.path.tangent(step.position) * step.velocity
+    }
+
+    /// Get acceleration for each joint at a time along the path
+    pub fn acceleration(&mut self, time: f64) -> Coord<N> {
+        let (step, acceleration) = self.path_state(time);
+        let (tangent, curvature) = self.trajectory.path.tangent_and_curvature(step.position);
+
+        tangent * acceleration + curvature * step.velocity.powi(2)
+    }
+
+    /// Get position and velocity at a time along the path
+    pub fn position_and_velocity(&mut self, time: f64) -> (Coord<N>, Coord<N>) {
+        let (step, _) = self.path_state(time);
+
+        (
+            self.wrap(self.trajectory.path.position(step.position)),
+            self.trajectory.path.tangent(step.position) * step.velocity,
+        )
+    }
+
+    /// Get position, velocity and acceleration at a time along the path
+    pub fn state(&mut self, time: f64) -> KinematicState<N> {
+        let (step, acceleration) = self.path_state(time);
+        let state = KinematicState::from_path_step(&self.trajectory.path, step, acceleration);
+
+        KinematicState {
+            position: self.wrap(state.position),
+            ..state
+        }
+    }
+
+    /// Wrap a position if this sampler wraps positions
+    fn wrap(&self, position: Coord<N>) -> Coord<N> {
+        if self.wrapped {
+            self.trajectory.path.wrap(&position)
+        } else {
+            position
+        }
+    }
+
+    /// Get the interpolated position, velocity and acceleration along the path at a given time
+    fn path_state(&mut self, time: f64) -> (TrajectoryStep, f64) {
+        self.seek(time)
+            .map(|(previous, current)| {
+                (
+                    previous.interpolate(current, time),
+                    previous.interpolate_acceleration(current, time),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Move the cached index to the interval that `time` lies on
+    fn seek(&mut self, time: f64) -> Option<(&'t TrajectoryStep, &'t TrajectoryStep)> {
+        let steps: &'t [TrajectoryStep] = &self.trajectory.trajectory;
+        let last = steps.len().checked_sub(1)?;
+
+        let contains = |index: usize| {
+            let (previous, current) = segment_window(steps, index);
+
+            time >= previous.time && time <= current.time
+        };
+
+        self.index = if contains(self.index) {
+            self.index
+        } else if self.index < last && contains(self.index + 1) {
+            self.index + 1
+        } else if time > steps[self.index].time {
+            self.index + segment_index(&steps[self.index..], time)?
+        } else {
+            segment_index(&steps[..=self.index], time)?
+        };
+
+        Some(segment_window(steps, self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::*;
+    use crate::{Path, PathOptions, Trajectory, TrajectoryOptions};
+
+    #[test]
+    fn sampler_matches_trajectory() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 1.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+
+        let traj = Trajectory::new(
+            &path,
+            TrajectoryOptions {
+                timestep: 0.01,
+                ..TrajectoryOptions::default()
+            },
+        )
+        .unwrap();
+
+        let duration = traj.duration();
+        let mut sampler = traj.sampler();
+
+        // Forwards at a finer and coarser rate than the trajectory timestep, then jump around
+        let times = (0..=1000)
+            .map(|i| duration * i as f64 / 1000.0)
+            .chain((0..=10).map(|i| duration * i as f64 / 10.0))
+            .chain(vec![
+                duration * 0.75,
+                duration * 0.25,
+                -1.0,
+                duration + 1.0,
+                0.0,
+            ]);
+
+        for time in times {
+            let (position, velocity) = sampler.position_and_velocity(time);
+
+            assert_near!(position, traj.position(time));
+            assert_near!(velocity, traj.velocity(time));
+            assert_near!(sampler.position(time), position);
+            assert_near!(sampler.velocity(time), velocity);
+            assert_near!(sampler.acceleration(time), traj.acceleration(time));
+            assert_eq!(sampler.state(time), traj.state(time));
+        }
+    }
+}
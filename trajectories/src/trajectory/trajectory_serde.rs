@@ -0,0 +1,170 @@
+//! Serde support for `Trajectory`
+//!
+//! A trajectory is serialized along with the path it follows. Deserialized trajectories share
+//! ownership of their path, so they can be evaluated without running the planner again.
+
+use super::{Trajectory, TrajectoryPath, TrajectoryStep};
+use crate::envelope;
+use crate::path::Path;
+use crate::Coord;
+use nalgebra::{
+    allocator::{Allocator, SameShapeVectorAllocator},
+    storage::Owned,
+    DefaultAllocator, DimName,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::Arc;
+
+/// Borrowed serialized representation of a trajectory
+#[derive(Serialize)]
+#[serde(rename = "Trajectory", bound(serialize = "Coord<N>: serde::Serialize"))]
+struct TrajectoryRef<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    path: &'a Path<N>,
+    trajectory: &'a [TrajectoryStep],
+}
+
+/// Owned serialized representation of a trajectory
+#[derive(Deserialize)]
+#[serde(
+    rename = "Trajectory",
+    bound(deserialize = "Coord<N>: serde::Deserialize<'de>")
+)]
+struct TrajectoryData<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    path: Path<N>,
+    trajectory: Vec<TrajectoryStep>,
+}
+
+impl<'a, N> Serialize for Trajectory<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+    Coord<N>: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        envelope::serialize(
+            &TrajectoryRef {
+                path: &self.path,
+                trajectory: &self.trajectory,
+            },
+            serializer,
+        )
+    }
+}
+
+impl<'de, 'a, N> Deserialize<'de> for Trajectory<'a, N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+    Coord<N>: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let TrajectoryData { path, trajectory } = envelope::deserialize(deserializer)?;
+
+        Ok(Self {
+            path: TrajectoryPath::Shared(Arc::new(path)),
+            trajectory,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::*;
+    use crate::{Path, PathOptions, Trajectory};
+    use nalgebra::U3;
+
+    fn assert_same_trajectory(a: &Trajectory<U3>, b: &Trajectory<U3>) {
+        assert_eq!(a.trajectory().len(), b.trajectory().len());
+        assert_near!(a.duration(), b.duration());
+
+        for sample in a.sample(0.1).unwrap() {
+            assert_near!(b.position(sample.time), sample.position);
+            assert_near!(b.velocity(sample.time), sample.velocity);
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let traj = Trajectory::from_shared(
+            Path::from_waypoints(&waypoints, PathOptions::default()),
+            trajectory_options_fixture(),
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&traj).unwrap();
+        let deserialized: Trajectory<'static, U3> = serde_json::from_str(&json).unwrap();
+
+        assert_same_trajectory(&traj, &deserialized);
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let traj = Trajectory::from_shared(
+            Path::from_waypoints(&waypoints, PathOptions::default()),
+            trajectory_options_fixture(),
+        )
+        .unwrap();
+
+        let bytes = bincode::serialize(&traj).unwrap();
+        let deserialized: Trajectory<'static, U3> = bincode::deserialize(&bytes).unwrap();
+
+        assert_same_trajectory(&traj, &deserialized);
+        assert_eq!(deserialized.trajectory(), traj.trajectory());
+        assert_eq!(deserialized.path(), traj.path());
+    }
+
+    #[test]
+    fn borrowed_trajectory_round_trip() {
+        let waypoints: Vec<TestCoord3> = vec![
+            TestCoord3::new(0.0, 0.0, 0.0),
+            TestCoord3::new(0.0, 1.0, 0.0),
+            TestCoord3::new(1.0, 1.0, 0.0),
+        ];
+
+        let path = Path::from_waypoints(&waypoints, PathOptions::default());
+        let borrowed = Trajectory::new(&path, trajectory_options_fixture()).unwrap();
+        let shared = Trajectory::from_shared(path.clone(), trajectory_options_fixture()).unwrap();
+
+        let json = serde_json::to_string(&borrowed).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&shared).unwrap());
+
+        let deserialized: Trajectory<'static, U3> = serde_json::from_str(&json).unwrap();
+
+        assert_same_trajectory(&borrowed, &deserialized);
+    }
+}
@@ -2,6 +2,7 @@
 
 /// Trajectory step
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TrajectoryStep {
     /// Position
     pub position: f64,
@@ -31,6 +32,49 @@ impl TrajectoryStep {
     pub fn time(mut self, time: f64) {
         self.time = time;
     }
+
+    /// Interpolate between this step and the next step with constant acceleration
+    ///
+    /// `time` is clamped to lie between the times of the two steps.
+    pub(crate) fn interpolate(&self, next: &TrajectoryStep, time: f64) -> TrajectoryStep {
+        let duration = next.time - self.time;
+
+        // Zero length interval, e.g. a trajectory with a single step
+        if duration <= 0.0 {
+            return *self;
+        }
+
+        let acceleration = self.acceleration_to(next);
+
+        let duration = time.max(self.time).min(next.time) - self.time;
+
+        Self {
+            position: self.position
+                + duration * self.velocity
+                + 0.5 * duration.powi(2) * acceleration,
+            velocity: self.velocity + duration * acceleration,
+            time: self.time + duration,
+        }
+    }
+
+    /// Get the path acceleration at a time between this step and the next step
+    ///
+    /// Returns zero for times outside the interval, where the trajectory is held at rest at its
+    /// start or end.
+    pub(crate) fn interpolate_acceleration(&self, next: &TrajectoryStep, time: f64) -> f64 {
+        if next.time > self.time && time >= self.time && time <= next.time {
+            self.acceleration_to(next)
+        } else {
+            0.0
+        }
+    }
+
+    /// Constant path acceleration required to get from this step to the next step
+    fn acceleration_to(&self, next: &TrajectoryStep) -> f64 {
+        let duration = next.time - self.time;
+
+        2.0 * (next.position - self.position - duration * self.velocity) / duration.powi(2)
+    }
 }
 
 impl Default for TrajectoryStep {
@@ -8,10 +8,19 @@ use nalgebra::{
     storage::Owned,
     DefaultAllocator, DimName,
 };
+use std::cmp::Ordering;
+
+/// Number of evenly spaced points along each segment checked for movement of a locked axis
+const LOCKED_AXIS_SAMPLES: usize = 100;
 
 /// Find the maximum allowable velocity at a point, limited by either max acceleration or max
 /// velocity.
-pub fn max_velocity_at<N>(path: &Path<N>, position_along_path: f64, limit_type: LimitType<N>) -> f64
+pub fn max_velocity_at<N>(
+    path: &Path<N>,
+    position_along_path: f64,
+    limit_type: LimitType<N>,
+    options: &TrajectoryOptions<N>,
+) -> f64
 where
     N: DimName + Copy,
     DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
@@ -21,7 +30,7 @@ where
     match limit_type {
         LimitType::Velocity(velocity_limit) => {
             let tangent = path.tangent(position_along_path);
-            let result = velocity_limit.component_div(&tangent).amin();
+            let result = limit_div(&velocity_limit, &tangent, options.epsilon).amin();
 
             instrument!("max_vel_from_vel", (position_along_path, result));
 
@@ -91,7 +100,7 @@ where
         LimitType::Velocity(velocity_limit) => {
             let (tangent, curvature) = path.tangent_and_curvature(position_along_path);
             let tangent_abs = tangent.abs();
-            let velocity = velocity_limit.component_div(&tangent_abs);
+            let velocity = limit_div(&velocity_limit, &tangent_abs, options.epsilon);
 
             let calc = -(velocity_limit.component_mul(&curvature))
                 .component_div(&tangent.component_mul(&tangent_abs));
@@ -110,10 +119,12 @@ where
                 path,
                 position_along_path + options.epsilon,
                 LimitType::Acceleration(acceleration_limit),
+                options,
             ) - max_velocity_at(
                 path,
                 position_along_path - options.epsilon,
                 LimitType::Acceleration(acceleration_limit),
+                options,
             )) / (2.0 * options.epsilon)
         }
     }
@@ -150,7 +161,12 @@ where
                 acceleration_limit_component,
                 (derivative_component, second_derivative_component),
             )| {
-                if *derivative_component != 0.0 {
+                // Like `limit_div`, locked axes that aren't moved by the path don't limit the
+                // acceleration
+                let locked = *acceleration_limit_component == 0.0
+                    && derivative_component.abs() <= options.epsilon;
+
+                if *derivative_component != 0.0 && !locked {
                     acc.min(
                         acceleration_limit_component / derivative_component.abs()
                             - factor * second_derivative_component * velocity.powi(2)
@@ -182,3 +198,74 @@ where
 {
     max_acceleration_at(path, &pos_vel, min_max, options) / pos_vel.velocity
 }
+
+/// Find the first axis locked by a zero limit in `options` that is moved by the path
+///
+/// Each segment is checked at its switching points and at evenly spaced samples along it, for a
+/// locked axis that moves away from where the segment starts or has a tangent component larger
+/// than `options.epsilon`. Returns the axis index and the position along the path where the
+/// movement was found.
+pub fn locked_axis_movement<N>(
+    path: &Path<N>,
+    options: &TrajectoryOptions<N>,
+) -> Option<(usize, f64)>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    let n = nalgebra::dimension::<Coord<N>>();
+    let locked = (0..n)
+        .filter(|axis| options.is_axis_locked(*axis))
+        .collect::<Vec<usize>>();
+
+    if locked.is_empty() {
+        return None;
+    }
+
+    path.segments.iter().find_map(|segment| {
+        let start = segment.start_offset();
+        let end = segment.end_offset();
+        let start_position = segment.position(start);
+
+        let mut samples = (0..=LOCKED_AXIS_SAMPLES)
+            .map(|i| start + (end - start) * i as f64 / LOCKED_AXIS_SAMPLES as f64)
+            .chain(segment.switching_points().into_iter().map(|p| start + p))
+            .collect::<Vec<f64>>();
+
+        samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        samples.into_iter().find_map(|position| {
+            let tangent = segment.tangent(position);
+            let moved = segment.position(position) - &start_position;
+
+            locked
+                .iter()
+                .find(|axis| {
+                    tangent[**axis].abs() > options.epsilon || moved[**axis].abs() > options.epsilon
+                })
+                .map(|axis| (*axis, position))
+        })
+    })
+}
+
+/// Divide an axis limit by a derivative of the path, component-wise
+///
+/// Locked axes (zero limit) that aren't moved by the path (derivative no larger than `epsilon`)
+/// don't limit the velocity, so result in infinity instead of zero or `0 / 0`.
+fn limit_div<N>(limit: &Coord<N>, derivative: &Coord<N>, epsilon: f64) -> Coord<N>
+where
+    N: DimName + Copy,
+    DefaultAllocator: SameShapeVectorAllocator<f64, N, N>,
+    <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
+    Owned<f64, N>: Copy,
+{
+    limit.zip_map(derivative, |limit, derivative| {
+        if limit == 0.0 && derivative.abs() <= epsilon {
+            std::f64::INFINITY
+        } else {
+            limit / derivative
+        }
+    })
+}
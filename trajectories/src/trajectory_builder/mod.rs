@@ -10,11 +10,11 @@ use self::min_max::MinMax;
 use self::path_position::PathPosition;
 use self::trajectory_switching_point::TrajectorySwitchingPoint;
 use self::trajectory_switching_points::TrajectorySwitchingPoints;
-use crate::path::{Continuity, PathItem};
+use crate::path::PathItem;
 use crate::trajectory::TrajectoryStep;
-use crate::{Path, TrajectoryOptions};
+use crate::{Path, TrajectoryError, TrajectoryOptions, TrajectoryOptionsError};
 use limits::{
-    max_acceleration_at, max_acceleration_derivative_at, max_velocity_at,
+    locked_axis_movement, max_acceleration_at, max_acceleration_derivative_at, max_velocity_at,
     max_velocity_derivative_at,
 };
 use nalgebra::{
@@ -42,15 +42,34 @@ where
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
     Owned<f64, N>: Copy,
 {
-    pub fn from_path(path: &'a Path<N>, options: TrajectoryOptions<N>) -> Self {
-        Self {
+    pub fn from_path(
+        path: &'a Path<N>,
+        options: TrajectoryOptions<N>,
+    ) -> Result<Self, TrajectoryError> {
+        options
+            .validate()
+            .map_err(|reason| TrajectoryError::InvalidOptions {
+                position: 0.0,
+                velocity: 0.0,
+                reason,
+            })?;
+
+        if let Some((axis, position)) = locked_axis_movement(path, &options) {
+            return Err(TrajectoryError::InvalidOptions {
+                position,
+                velocity: 0.0,
+                reason: TrajectoryOptionsError::LockedAxisMoved { axis },
+            });
+        }
+
+        Ok(Self {
             path,
             options,
-            switching_points: TrajectorySwitchingPoints::from_path(&path, options).unwrap(),
-        }
+            switching_points: TrajectorySwitchingPoints::from_path(&path, options)?,
+        })
     }
 
-    pub fn into_steps(self) -> Result<Vec<TrajectoryStep>, String> {
+    pub fn into_steps(self) -> Result<Vec<TrajectoryStep>, TrajectoryError> {
         let mut trajectory = vec![TrajectoryStep::new(0.0, 0.0)];
         let mut switching_point = TrajectorySwitchingPoint {
             before_acceleration: 0.0,
@@ -63,6 +82,7 @@ where
             pos: TrajectoryStep::new(0.0, 0.0),
         };
 
+        let mut missed_switching_point = None;
         let mut dbg_iter = 0;
 
         loop {
@@ -110,7 +130,16 @@ where
 
                 switching_point = new_switching_point;
             } else {
-                // Break if we've reached the end of the path
+                // No switching points are left, so the rest of the trajectory is found by
+                // integrating backward from the end of the path. If that does not meet the
+                // trajectory, the search missed a switching point after the stop position.
+                let last = trajectory.last().unwrap_or(&switching_point.pos);
+
+                missed_switching_point = Some(TrajectoryError::SwitchingPointNotFound {
+                    position: last.position,
+                    velocity: last.velocity,
+                });
+
                 break;
             }
 
@@ -135,21 +164,19 @@ where
         }
 
         // Backwards integrate last section
+        let end = TrajectorySwitchingPoint {
+            pos: TrajectoryStep::new(self.path.len(), 0.0),
+            before_acceleration: max_acceleration_at(
+                &self.path,
+                &TrajectoryStep::new(self.path.len(), 0.0),
+                MinMax::Min,
+                &self.options,
+            ),
+            after_acceleration: 0.0,
+        };
         let (splice_index, updated_traj) = self
-            .integrate_backward(
-                &trajectory,
-                &TrajectorySwitchingPoint {
-                    pos: TrajectoryStep::new(self.path.len(), 0.0),
-                    before_acceleration: max_acceleration_at(
-                        &self.path,
-                        &TrajectoryStep::new(self.path.len(), 0.0),
-                        MinMax::Min,
-                        &self.options,
-                    ),
-                    after_acceleration: 0.0,
-                },
-            )
-            .map_err(|e| format!("Last section integrate backwards failed: {}", e))?;
+            .integrate_backward(&trajectory, &end)
+            .map_err(|error| missed_switching_point.unwrap_or(error))?;
 
         let _ = trajectory.split_off(splice_index);
         trajectory.extend(updated_traj);
@@ -182,17 +209,19 @@ where
         &self,
         trajectory: &[TrajectoryStep],
         start_acceleration: f64,
-    ) -> Result<(Vec<TrajectoryStep>, PathPosition, f64), String> {
+    ) -> Result<(Vec<TrajectoryStep>, PathPosition, f64), TrajectoryError> {
         let mut new_points = Vec::new();
-        let last = trajectory
-            .last()
-            .ok_or_else(|| String::from("Attempted to get last element of empty trajectory"))?;
+        let last = trajectory.last().ok_or(TrajectoryError::EmptyTrajectory {
+            position: 0.0,
+            velocity: 0.0,
+        })?;
         let TrajectoryStep {
             mut position,
             mut velocity,
             ..
         } = last;
         let mut acceleration = start_acceleration;
+        let mut next_discontinuity = self.path.next_discontinuity(position);
 
         loop {
             trace!(
@@ -202,11 +231,11 @@ where
                 self.path.len()
             );
 
-            // TODO: Keep next_discontinuity iterator cached for perf
-            let next_discontinuity = self.path.switching_points().iter().find(|switching_point| {
-                switching_point.position > position
-                    && switching_point.continuity == Continuity::Discontinuous
-            });
+            // Only search for the next discontinuity once the previously found one has been passed
+            if next_discontinuity.map_or(false, |discontinuity| discontinuity.position <= position)
+            {
+                next_discontinuity = self.path.next_discontinuity(position);
+            }
 
             let old_position = position;
             let old_velocity = velocity;
@@ -230,16 +259,14 @@ where
 
                 break Ok((new_points, PathPosition::End, position));
             } else if velocity < 0.0 {
-                break Err(format!(
-                    "Integrate forward velocity cannot be 0, position {}, velocity {}",
-                    position, velocity
-                ));
+                break Err(TrajectoryError::NegativeVelocity { position, velocity });
             }
 
             let max_velocity_at_position = max_velocity_at(
                 self.path,
                 position,
                 LimitType::Velocity(self.options.velocity_limit),
+                &self.options,
             );
 
             if velocity > max_velocity_at_position
@@ -251,6 +278,7 @@ where
                             self.path,
                             old_position,
                             LimitType::Velocity(self.options.velocity_limit),
+                            &self.options,
                         ),
                     ),
                     MinMax::Min,
@@ -276,12 +304,13 @@ where
                     self.path,
                     position,
                     LimitType::Acceleration(self.options.acceleration_limit),
+                    &self.options,
                 )
                 || velocity > max_velocity_at_position
             {
                 let overshoot = new_points
                     .pop()
-                    .ok_or_else(|| String::from("Attempted to pop last element off empty vec"))?;
+                    .ok_or(TrajectoryError::EmptyTrajectory { position, velocity })?;
                 let last_point = new_points.last().unwrap_or(last);
 
                 let mut before = last_point.position;
@@ -304,6 +333,7 @@ where
                         self.path,
                         midpoint,
                         LimitType::Velocity(self.options.velocity_limit),
+                        &self.options,
                     );
 
                     if midpoint_velocity > max_midpoint_velocity
@@ -315,6 +345,7 @@ where
                                     self.path,
                                     before,
                                     LimitType::Velocity(self.options.velocity_limit),
+                                    &self.options,
                                 ),
                             ),
                             MinMax::Min,
@@ -334,6 +365,7 @@ where
                             self.path,
                             midpoint,
                             LimitType::Acceleration(self.options.acceleration_limit),
+                            &self.options,
                         )
                         || midpoint_velocity > max_midpoint_velocity
                     {
@@ -353,10 +385,12 @@ where
                     self.path,
                     after,
                     LimitType::Acceleration(self.options.acceleration_limit),
+                    &self.options,
                 ) < max_velocity_at(
                     self.path,
                     after,
                     LimitType::Velocity(self.options.velocity_limit),
+                    &self.options,
                 ) {
                     if let Some(next) = next_discontinuity {
                         if after > next.position {
@@ -401,7 +435,7 @@ where
         &self,
         start_trajectory: &[TrajectoryStep],
         start_switching_point: &TrajectorySwitchingPoint,
-    ) -> Result<(usize, Vec<TrajectoryStep>), String> {
+    ) -> Result<(usize, Vec<TrajectoryStep>), TrajectoryError> {
         let TrajectorySwitchingPoint {
             pos:
                 TrajectoryStep {
@@ -461,10 +495,12 @@ where
                 );
 
                 if velocity < 0.0 {
-                    return Err(format!(
-                        "Velocity cannot be less than zero at position {}, got {} (acceleration {}, slope {})",
-                        position, velocity, before_acceleration, slope
-                    ));
+                    debug!(
+                        "Integrate backward velocity less than zero (acceleration {}, slope {})",
+                        before_acceleration, slope
+                    );
+
+                    return Err(TrajectoryError::BackwardIntegrationFailed { position, velocity });
                 }
             } else {
                 parts = it.next();
@@ -491,9 +527,7 @@ where
                             + start2.position.min(
                                 new_trajectory
                                     .last()
-                                    .ok_or_else(|| {
-                                        String::from("Could not get last point of empty trajectory")
-                                    })?
+                                    .ok_or(TrajectoryError::EmptyTrajectory { position, velocity })?
                                     .position,
                             )
                 {
@@ -518,7 +552,7 @@ where
             }
         }
 
-        Err(format!("Path is invalid: Integrate backwards did not hit start trajectory, start position {} velocity {}", position, velocity))
+        Err(TrajectoryError::BackwardIntegrationFailed { position, velocity })
 
         // let TrajectorySwitchingPoint {
         //     pos:
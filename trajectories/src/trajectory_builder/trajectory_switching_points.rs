@@ -6,13 +6,12 @@ use super::{
     LimitType, MinMax, TrajectoryStep, TrajectorySwitchingPoint,
 };
 use crate::path::{Continuity, PathItem};
-use crate::{Path, TrajectoryOptions};
+use crate::{Path, TrajectoryError, TrajectoryOptions};
 use nalgebra::{
     allocator::{Allocator, SameShapeVectorAllocator},
     storage::Owned,
     DefaultAllocator, DimName,
 };
-use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
@@ -42,61 +41,69 @@ where
     <DefaultAllocator as Allocator<f64, N>>::Buffer: Send + Sync,
     Owned<f64, N>: Copy,
 {
-    pub fn from_path(path: &'a Path<N>, options: TrajectoryOptions<N>) -> Result<Self, String> {
+    pub fn from_path(
+        path: &'a Path<N>,
+        options: TrajectoryOptions<N>,
+    ) -> Result<Self, TrajectoryError> {
         let all_start = Instant::now();
 
-        let p = Arc::new(path.clone());
+        let (velocity_switching_points, acceleration_switching_points) = thread::scope(|scope| {
+            let velocity_switching_points = scope.spawn(|| {
+                let start = Instant::now();
 
-        let path_v = p.clone();
-        let path_a = p.clone();
+                let mut points = Vec::new();
+                let mut pos = 0.0;
 
-        let velocity_switching_points = thread::spawn(move || {
-            let start = Instant::now();
+                while let Some(point) =
+                    Self::find_next_velocity_switching_point(path, pos, &options)
+                {
+                    points.push(point);
+                    debug!("Vel point {}", pos);
+                    pos = point.pos.position;
+                }
 
-            let mut points = Vec::new();
-            let mut pos = 0.0;
+                info!(
+                    "Found {} velocity switching points in {} ms",
+                    points.len(),
+                    start.elapsed().as_millis()
+                );
 
-            while let Some(point) = Self::find_next_velocity_switching_point(&path_v, pos, &options)
-            {
-                points.push(point);
-                debug!("Vel point {}", pos);
-                pos = point.pos.position;
-            }
+                points
+            });
 
-            info!(
-                "Found {} velocity switching points in {} ms",
-                points.len(),
-                start.elapsed().as_millis()
-            );
-
-            points
-        });
+            let acceleration_switching_points = scope.spawn(|| {
+                let start = Instant::now();
 
-        let acceleration_switching_points = thread::spawn(move || {
-            let start = Instant::now();
+                let mut points = Vec::new();
+                let mut pos = 0.0;
 
-            let mut points = Vec::new();
-            let mut pos = 0.0;
+                while let Some(point) =
+                    Self::find_next_acceleration_switching_point(path, pos, &options)
+                {
+                    points.push(point);
+                    debug!("Accel point {}", pos);
+                    pos = point.pos.position;
+                }
 
-            while let Some(point) =
-                Self::find_next_acceleration_switching_point(&path_a, pos, &options)
-            {
-                points.push(point);
-                debug!("Accel point {}", pos);
-                pos = point.pos.position;
-            }
+                info!(
+                    "Found {} acceleration switching points in {} ms",
+                    points.len(),
+                    start.elapsed().as_millis()
+                );
 
-            info!(
-                "Found {} acceleration switching points in {} ms",
-                points.len(),
-                start.elapsed().as_millis()
-            );
+                points
+            });
 
-            points
+            (
+                velocity_switching_points.join(),
+                acceleration_switching_points.join(),
+            )
         });
 
-        let velocity_switching_points = velocity_switching_points.join().unwrap();
-        let acceleration_switching_points = acceleration_switching_points.join().unwrap();
+        let velocity_switching_points =
+            velocity_switching_points.map_err(|_| TrajectoryError::SwitchingPointSearchPanicked)?;
+        let acceleration_switching_points = acceleration_switching_points
+            .map_err(|_| TrajectoryError::SwitchingPointSearchPanicked)?;
 
         info!(
             "Switching point total time: {} ms",
@@ -157,6 +164,7 @@ where
                             path,
                             point.pos.position,
                             LimitType::Velocity(options.velocity_limit),
+                            options,
                         )
             })
             .cloned();
@@ -185,12 +193,14 @@ where
                                 path,
                                 point.pos.position - options.epsilon,
                                 LimitType::Acceleration(options.acceleration_limit),
+                                options,
                             )
                             && point.pos.velocity
                                 <= max_velocity_at(
                                     path,
                                     point.pos.position + options.epsilon,
                                     LimitType::Acceleration(options.acceleration_limit),
+                                    options,
                                 )))
             })
             .cloned();
@@ -250,19 +260,21 @@ where
         position_along_path: f64,
         options: &TrajectoryOptions<N>,
     ) -> Option<TrajectorySwitchingPoint> {
-        path.switching_points_iter()
-            .skip_while(|point| point.position <= position_along_path)
+        path.switching_points_after(position_along_path)
+            .iter()
             .find_map(|current_point| match current_point.continuity {
                 Continuity::Discontinuous => {
                     let before_velocity = max_velocity_at(
                         path,
                         current_point.position - options.epsilon,
                         LimitType::Acceleration(options.acceleration_limit),
+                        options,
                     );
                     let after_velocity = max_velocity_at(
                         path,
                         current_point.position + options.epsilon,
                         LimitType::Acceleration(options.acceleration_limit),
+                        options,
                     );
 
                     let velocity = before_velocity.min(after_velocity);
@@ -321,6 +333,7 @@ where
                         path,
                         current_point.position,
                         LimitType::Acceleration(options.acceleration_limit),
+                        options,
                     );
 
                     let low_deriv = max_velocity_derivative_at(
@@ -373,7 +386,12 @@ where
             &path,
             &TrajectoryStep::new(
                 position,
-                max_velocity_at(path, position, LimitType::Velocity(options.velocity_limit)),
+                max_velocity_at(
+                    path,
+                    position,
+                    LimitType::Velocity(options.velocity_limit),
+                    options,
+                ),
             ),
             MinMax::Min,
             &options,
@@ -383,7 +401,7 @@ where
             position,
             LimitType::Velocity(options.velocity_limit),
             &options,
-        );;
+        );
 
         // Move along path until a sign change is detected. This defines an interval within which a
         // velocity switching point occurs. Think of the peak or trough of a sawtooth wave.
@@ -396,7 +414,12 @@ where
                 &path,
                 &TrajectoryStep::new(
                     position,
-                    max_velocity_at(path, position, LimitType::Velocity(options.velocity_limit)),
+                    max_velocity_at(
+                        path,
+                        position,
+                        LimitType::Velocity(options.velocity_limit),
+                        options,
+                    ),
                 ),
                 MinMax::Min,
                 &options,
@@ -439,7 +462,12 @@ where
                 &path,
                 &TrajectoryStep::new(
                     position,
-                    max_velocity_at(path, position, LimitType::Velocity(options.velocity_limit)),
+                    max_velocity_at(
+                        path,
+                        position,
+                        LimitType::Velocity(options.velocity_limit),
+                        options,
+                    ),
                 ),
                 MinMax::Min,
                 &options,
@@ -461,6 +489,7 @@ where
                 path,
                 after_position,
                 LimitType::Velocity(options.velocity_limit),
+                options,
             ),
         );
 
@@ -472,6 +501,7 @@ where
                     path,
                     prev_position,
                     LimitType::Velocity(options.velocity_limit),
+                    options,
                 ),
             ),
             MinMax::Min,
@@ -524,6 +554,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.001,
+                ..PathOptions::default()
             },
         );
 
@@ -581,6 +612,7 @@ mod tests {
             &waypoints,
             PathOptions {
                 max_deviation: 0.001,
+                ..PathOptions::default()
             },
         );
 
@@ -20,6 +20,7 @@ fn compare_test_1() {
         &waypoints,
         PathOptions {
             max_deviation: 0.001,
+            ..PathOptions::default()
         },
     );
 
@@ -85,7 +86,13 @@ fn compare_test_2() {
     // Same epsilon as C++ hardcoded value
     let epsilon = 0.000001;
 
-    let rust_path = Path::from_waypoints(&waypoints, PathOptions { max_deviation });
+    let rust_path = Path::from_waypoints(
+        &waypoints,
+        PathOptions {
+            max_deviation,
+            ..PathOptions::default()
+        },
+    );
 
     let rust_path_len = rust_path.len();
 
@@ -151,16 +158,14 @@ fn compare_test_2() {
         cpp_rows.push(TrajectoryStepRow::from_parts(time, &c_pos, &c_vel));
     }
 
-    for i in (0..rust_trajectory.duration() as usize).step_by(step_size) {
-        let time: f64 = i as f64;
-
-        let r_p = rust_trajectory.position(time);
-        let r_v = rust_trajectory.velocity(time);
+    for sample in rust_trajectory.sample(step_size as f64).unwrap() {
+        let r_p = sample.position;
+        let r_v = sample.velocity;
 
         let r_pos = TestPoint::from([r_p[0], r_p[1], r_p[2]]);
         let r_vel = TestPoint::from([r_v[0], r_v[1], r_v[2]]);
 
-        rust_rows.push(TrajectoryStepRow::from_parts(time, &r_pos, &r_vel));
+        rust_rows.push(TrajectoryStepRow::from_parts(sample.time, &r_pos, &r_vel));
     }
 
     write_debug_csv("../target/compare_cpp_output.csv".into(), &cpp_rows);
@@ -182,11 +187,11 @@ fn compare_test_2() {
             .collect::<Vec<f64>>(),
     );
 
-    for i in (0..rust_trajectory.duration() as usize).step_by(step_size) {
-        let time: f64 = i as f64;
+    for sample in rust_trajectory.sample(step_size as f64).unwrap() {
+        let time = sample.time;
 
-        let r_p = rust_trajectory.position(time);
-        let r_v = rust_trajectory.velocity(time);
+        let r_p = sample.position;
+        let r_v = sample.velocity;
 
         let c_p = unsafe { cpp_trajectory.getPosition(time) };
         let c_v = unsafe { cpp_trajectory.getVelocity(time) };
@@ -18,6 +18,7 @@ fn test_1() {
         &waypoints,
         PathOptions {
             max_deviation: 100.0,
+            ..PathOptions::default()
         },
     );
 
@@ -48,6 +49,7 @@ fn test_2() {
         &waypoints,
         PathOptions {
             max_deviation: 100.0,
+            ..PathOptions::default()
         },
     );
 
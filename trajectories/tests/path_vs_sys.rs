@@ -24,6 +24,7 @@ fn compare_path_length_large_deviation() {
         &waypoints,
         PathOptions {
             max_deviation: 100.0,
+            ..PathOptions::default()
         },
     );
 
@@ -72,6 +73,7 @@ fn compare_path_length() {
         &waypoints,
         PathOptions {
             max_deviation: 0.001,
+            ..PathOptions::default()
         },
     );
 
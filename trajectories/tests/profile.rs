@@ -26,6 +26,7 @@ fn profile_native() {
         &waypoints,
         PathOptions {
             max_deviation: 0.001,
+            ..PathOptions::default()
         },
     );
 
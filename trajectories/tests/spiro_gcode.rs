@@ -21,7 +21,13 @@ fn spiro_gcode() {
     // Same epsilon as C++ hardcoded value
     let epsilon = 0.000001;
 
-    let rust_path = Path::from_waypoints(&waypoints, PathOptions { max_deviation });
+    let rust_path = Path::from_waypoints(
+        &waypoints,
+        PathOptions {
+            max_deviation,
+            ..PathOptions::default()
+        },
+    );
 
     let cpp_path = unsafe {
         path_create(